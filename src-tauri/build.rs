@@ -1,3 +1,30 @@
+use std::path::PathBuf;
+use std::{env, fs};
+
+/// Mermaid runtime inlined into self-contained exports (see `converter.rs`).
+const MERMAID_JS: &str = "../node_modules/mermaid/dist/mermaid.min.js";
+
 fn main() {
+    bundle_mermaid();
     tauri_build::build()
 }
+
+/// Copy the Mermaid runtime into `OUT_DIR` for `include_str!`. Release
+/// builds require it; in debug builds without `npm install` an empty file is
+/// written instead, and exports of documents with diagrams that must work
+/// offline (self-contained HTML, PDF) fail.
+fn bundle_mermaid() {
+    println!("cargo:rerun-if-changed={}", MERMAID_JS);
+    let out = PathBuf::from(env::var("OUT_DIR").unwrap()).join("mermaid.min.js");
+    let js = fs::read(MERMAID_JS).unwrap_or_else(|_| {
+        if env::var("PROFILE").as_deref() == Ok("release") {
+            panic!("{} not found; run `npm install` before a release build", MERMAID_JS);
+        }
+        println!(
+            "cargo:warning={} not found (run `npm install`); offline exports of documents with Mermaid diagrams will fail",
+            MERMAID_JS
+        );
+        Vec::new()
+    });
+    fs::write(&out, js).unwrap();
+}
@@ -0,0 +1,66 @@
+//! Crash-safe file replacement.
+//!
+//! `write_atomic` writes into a temporary file in the destination's directory,
+//! fsyncs it, and renames it over the target, so readers (and the app after a
+//! crash or a full disk) see either the old file or the complete new one, never
+//! a truncated mix. The temp file name starts with a dot, so it is hidden from
+//! the tree, search and the watcher while it exists.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+pub fn write_atomic(path: &Path, contents: &[u8]) -> io::Result<()> {
+    // Replace the file a symlink points at, not the link itself.
+    let target = resolve_symlink(path);
+    let dir = match target.parent() {
+        Some(p) if !p.as_os_str().is_empty() => p.to_path_buf(),
+        _ => PathBuf::from("."),
+    };
+    let existing = fs::metadata(&target).ok();
+
+    let mut tmp = temp_builder(existing.is_some()).tempfile_in(&dir)?;
+    tmp.write_all(contents)?;
+    if let Some(meta) = &existing {
+        tmp.as_file().set_permissions(meta.permissions())?;
+    }
+    tmp.as_file().sync_all()?;
+    tmp.persist(&target).map_err(|e| e.error)?;
+
+    sync_dir(&dir);
+    Ok(())
+}
+
+fn resolve_symlink(path: &Path) -> PathBuf {
+    match fs::symlink_metadata(path) {
+        Ok(meta) if meta.file_type().is_symlink() => {
+            fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+        }
+        _ => path.to_path_buf(),
+    }
+}
+
+fn temp_builder(replacing: bool) -> tempfile::Builder<'static, 'static> {
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(".markdownui-").suffix(".tmp");
+    // New files get the usual 0666 & !umask instead of tempfile's private 0600.
+    #[cfg(unix)]
+    if !replacing {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(fs::Permissions::from_mode(0o666));
+    }
+    #[cfg(not(unix))]
+    let _ = replacing;
+    builder
+}
+
+/// Persist the rename itself. Directories can't be opened for syncing on
+/// Windows, and a failure here doesn't undo the write, so it is best effort.
+fn sync_dir(dir: &Path) {
+    #[cfg(unix)]
+    if let Ok(handle) = fs::File::open(dir) {
+        let _ = handle.sync_all();
+    }
+    #[cfg(not(unix))]
+    let _ = dir;
+}
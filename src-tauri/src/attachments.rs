@@ -0,0 +1,127 @@
+//! Non-markdown files kept next to documents: images, PDFs, audio and video.
+//!
+//! Files with one of `EXTENSIONS` show up in the tree as `Attachment` nodes
+//! and are renamed, moved and deleted like documents, but are not indexed or
+//! versioned. Imported files (pasted or dropped images) go to the assets
+//! folder from `StorageConfig::assets_folder`:
+//!
+//! ```text
+//! assets        <root>/assets, shared by the whole workspace
+//! ./assets      an `assets` folder next to the document
+//! .             the document's own folder
+//! ```
+//!
+//! Imports never overwrite: `image.png` becomes `image-1.png`, `image-2.png`, ...
+
+use std::fs::{self, OpenOptions};
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use crate::links;
+
+/// Extensions (lowercase) of files shown as attachments.
+pub const EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "ico", "avif", "tif", "tiff", // images
+    "pdf", "csv", "zip", // documents
+    "mp3", "wav", "ogg", "m4a", "flac", // audio
+    "mp4", "webm", "mov", // video
+];
+
+const IMAGE_EXTENSIONS: &[&str] = &[
+    "png", "jpg", "jpeg", "gif", "webp", "svg", "bmp", "ico", "avif", "tif", "tiff",
+];
+
+pub const DEFAULT_ASSETS_FOLDER: &str = "assets";
+
+fn extension(path: &Path) -> Option<String> {
+    path.extension().map(|e| e.to_string_lossy().to_lowercase())
+}
+
+pub fn is_attachment(path: &Path) -> bool {
+    extension(path).is_some_and(|e| EXTENSIONS.contains(&e.as_str()))
+}
+
+pub fn is_image(path: &Path) -> bool {
+    extension(path).is_some_and(|e| IMAGE_EXTENSIONS.contains(&e.as_str()))
+}
+
+/// File name for renaming the attachment at `old` to `new_name`: the old
+/// extension is kept unless `new_name` brings an attachment extension of its own.
+pub fn rename_target(new_name: &str, old: &Path) -> String {
+    match extension(old) {
+        Some(ext) if !is_attachment(Path::new(new_name)) => format!("{}.{}", new_name, ext),
+        _ => new_name.to_string(),
+    }
+}
+
+/// Folder that files imported for `document` go to, per the `setting`
+/// described in the module docs. Fails if it would leave the workspace `root`.
+pub fn assets_dir(root: &Path, document: &Path, setting: &str) -> Result<PathBuf, String> {
+    let setting = setting.trim().replace('\\', "/");
+    let dir = if setting == "." || setting.starts_with("./") || setting.starts_with("../") {
+        document.parent().unwrap_or(root).join(&setting)
+    } else {
+        root.join(setting.trim_start_matches('/'))
+    };
+    let dir = links::normalize(&dir);
+    if !dir.starts_with(root) {
+        return Err(format!("Assets folder {} is outside the workspace root", dir.display()));
+    }
+    Ok(dir)
+}
+
+/// A file name that is safe on every platform: path separators and reserved
+/// characters become `-`.
+pub fn sanitize_file_name(name: &str) -> Result<String, String> {
+    let cleaned: String = name
+        .trim()
+        .chars()
+        .map(|c| if "/\\:*?\"<>|".contains(c) || c.is_control() { '-' } else { c })
+        .collect();
+    let cleaned = cleaned.trim_start_matches('.').to_string();
+    if cleaned.is_empty() {
+        return Err(format!("Invalid file name: {:?}", name));
+    }
+    Ok(cleaned)
+}
+
+/// Write `data` to `dir/name`, or to `name-1`, `name-2`, ... if taken. The
+/// file is created exclusively, so a concurrent import can't be overwritten.
+pub fn write_unique(dir: &Path, name: &str, data: &[u8]) -> Result<PathBuf, String> {
+    let path = Path::new(name);
+    let stem = path.file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default();
+    let ext = path.extension().map(|e| format!(".{}", e.to_string_lossy())).unwrap_or_default();
+    fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+    let mut n = 0;
+    loop {
+        let candidate = if n == 0 {
+            dir.join(name)
+        } else {
+            dir.join(format!("{}-{}{}", stem, n, ext))
+        };
+        match OpenOptions::new().write(true).create_new(true).open(&candidate) {
+            Ok(mut file) => {
+                return file
+                    .write_all(data)
+                    .map(|_| candidate.clone())
+                    .map_err(|e| format!("Failed to write {}: {}", candidate.display(), e));
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => n += 1,
+            Err(e) => return Err(format!("Failed to create {}: {}", candidate.display(), e)),
+        }
+    }
+}
+
+/// Markdown that links `file` from `document`: an image for images, a plain
+/// link otherwise.
+pub fn markdown_link(document: &Path, file: &Path) -> String {
+    let dir = document.parent().unwrap_or(Path::new(""));
+    let destination = links::encode_destination(&links::relative_path(dir, file));
+    let name = file.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+    if is_image(file) {
+        let alt = file.file_stem().map(|n| n.to_string_lossy().to_string()).unwrap_or_default();
+        format!("![{}]({})", alt, destination)
+    } else {
+        format!("[{}]({})", name, destination)
+    }
+}
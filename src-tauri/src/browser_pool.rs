@@ -0,0 +1,116 @@
+//! One headless Chrome shared by every export.
+//!
+//! Launching Chrome takes about a second, so the app keeps a single
+//! `HeadlessBrowser` in its state instead of launching one per export:
+//!
+//! - it is launched on first use, not at startup;
+//! - at most `max_concurrent` exports use it at once, the rest wait their
+//!   turn (each export works in its own page, and pages are reused);
+//! - a browser that has crashed or stopped answering is replaced by a new
+//!   one, and an export that failed because Chrome went away mid-way is
+//!   retried once on the new browser;
+//! - after `idle_timeout` without exports Chrome is shut down, to be
+//!   launched again when next needed.
+//!
+//! The UI's export commands and the MCP server share the pool.
+
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::{Mutex, Semaphore};
+
+use crate::converter::HeadlessBrowser;
+
+/// Exports rendering at the same time.
+pub const DEFAULT_MAX_CONCURRENT: usize = 4;
+
+/// How long Chrome is kept running after the last export.
+pub const DEFAULT_IDLE_TIMEOUT: Duration = Duration::from_secs(120);
+
+pub type BrowserPoolArc = Arc<BrowserPool>;
+
+pub struct BrowserPool {
+    /// The running browser, if any.
+    browser: Mutex<Option<Arc<HeadlessBrowser>>>,
+    permits: Semaphore,
+    max_concurrent: usize,
+    idle_timeout: Duration,
+    /// Counts exports, so an idle check can tell whether any ran since it
+    /// was scheduled.
+    uses: AtomicU64,
+}
+
+impl BrowserPool {
+    pub fn new(max_concurrent: usize, idle_timeout: Duration) -> BrowserPoolArc {
+        Arc::new(BrowserPool {
+            browser: Mutex::new(None),
+            permits: Semaphore::new(max_concurrent),
+            max_concurrent,
+            idle_timeout,
+            uses: AtomicU64::new(0),
+        })
+    }
+
+    /// Run `task` with the shared browser once a slot is free, launching
+    /// Chrome if it isn't running. If the task fails and Chrome turns out to
+    /// have died, it is run again on a fresh browser.
+    pub async fn run<T, F, Fut>(self: &Arc<Self>, task: F) -> Result<T, String>
+    where
+        F: Fn(Arc<HeadlessBrowser>) -> Fut,
+        Fut: Future<Output = Result<T, String>>,
+    {
+        let permit = self
+            .permits
+            .acquire()
+            .await
+            .map_err(|e| format!("Browser pool is closed: {}", e))?;
+        self.uses.fetch_add(1, Ordering::SeqCst);
+
+        let browser = self.browser().await?;
+        let result = match task(Arc::clone(&browser)).await {
+            Err(_) if !browser.is_alive().await => task(self.browser().await?).await,
+            result => result,
+        };
+
+        drop(permit);
+        self.schedule_idle_shutdown();
+        result
+    }
+
+    /// The running browser, or a newly launched one if there is none or it
+    /// has stopped answering.
+    async fn browser(&self) -> Result<Arc<HeadlessBrowser>, String> {
+        let mut current = self.browser.lock().await;
+        if let Some(browser) = current.as_ref() {
+            if browser.is_alive().await {
+                return Ok(Arc::clone(browser));
+            }
+        }
+        let browser = Arc::new(HeadlessBrowser::launch().await?);
+        *current = Some(Arc::clone(&browser));
+        Ok(browser)
+    }
+
+    /// Shut Chrome down after `idle_timeout` unless another export has
+    /// started by then.
+    fn schedule_idle_shutdown(self: &Arc<Self>) {
+        let pool = Arc::clone(self);
+        let uses = self.uses.load(Ordering::SeqCst);
+        tokio::spawn(async move {
+            tokio::time::sleep(pool.idle_timeout).await;
+            let mut current = pool.browser.lock().await;
+            let idle = pool.uses.load(Ordering::SeqCst) == uses
+                && pool.permits.available_permits() == pool.max_concurrent;
+            if !idle {
+                return;
+            }
+            // Another holder can only be an export still finishing; then the
+            // browser is killed when that export drops it.
+            if let Some(browser) = current.take().and_then(|b| Arc::try_unwrap(b).ok()) {
+                browser.close().await;
+            }
+        });
+    }
+}
@@ -1,10 +1,25 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::atomic::write_atomic;
+use crate::documents::DocumentTypes;
+use crate::pdf_layout::PdfOptions;
+use crate::workspace_filter::{FilterSettings, WorkspaceFilter};
+
+const CONFIG_FILE: &str = "storage_config.json";
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkspaceEntry {
     pub name: String,
     pub path: PathBuf,
+    /// Overrides `StorageConfig::document_types` for this workspace.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub document_types: Option<DocumentTypes>,
+    /// Include/exclude globs and hidden entries (see `workspace_filter.rs`).
+    #[serde(default, skip_serializing_if = "FilterSettings::is_empty")]
+    pub filter: FilterSettings,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -13,35 +28,101 @@ pub struct StorageConfig {
     pub workspaces: Vec<WorkspaceEntry>,
     #[serde(default = "default_mcp_port")]
     pub mcp_port: u16,
+    /// Days to keep deleted entries in a workspace trash; None keeps them forever.
+    #[serde(default)]
+    pub trash_retention_days: Option<u32>,
+    /// Where imported attachments go: a folder inside the workspace root
+    /// (`assets`) or, starting with `./`, next to the document (see `attachments.rs`).
+    #[serde(default = "default_assets_folder")]
+    pub assets_folder: String,
+    /// Files shown and indexed as documents (see `documents.rs`).
+    #[serde(default)]
+    pub document_types: DocumentTypes,
+    /// Named PDF layouts offered when exporting (see `pdf_layout.rs`).
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub pdf_presets: BTreeMap<String, PdfOptions>,
 }
 
 fn default_mcp_port() -> u16 {
     3333
 }
 
+fn default_assets_folder() -> String {
+    crate::attachments::DEFAULT_ASSETS_FOLDER.to_string()
+}
+
 impl Default for StorageConfig {
     fn default() -> Self {
         StorageConfig {
             workspaces: Vec::new(),
             mcp_port: 3333,
+            trash_retention_days: None,
+            assets_folder: default_assets_folder(),
+            document_types: DocumentTypes::default(),
+            pdf_presets: BTreeMap::new(),
         }
     }
 }
 
 impl StorageConfig {
-    pub fn load(app_data_dir: &Path) -> Self {
-        let config_path = app_data_dir.join("storage_config.json");
-        match std::fs::read_to_string(&config_path) {
-            Ok(json) => serde_json::from_str(&json).unwrap_or_default(),
-            Err(_) => StorageConfig::default(),
+    /// Load the config, falling back to defaults when there is none yet.
+    ///
+    /// A config file that exists but can't be read or parsed is never silently
+    /// replaced: an unparsable file is moved aside to
+    /// `storage_config.json.corrupt-<unix time>` and the returned message tells
+    /// the user where their old settings went.
+    pub fn load(app_data_dir: &Path) -> (Self, Option<String>) {
+        let config_path = app_data_dir.join(CONFIG_FILE);
+        let json = match std::fs::read_to_string(&config_path) {
+            Ok(json) => json,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return (StorageConfig::default(), None)
+            }
+            Err(e) => {
+                return (
+                    StorageConfig::default(),
+                    Some(format!(
+                        "Could not read {}: {}. Using default settings.",
+                        config_path.display(),
+                        e
+                    )),
+                )
+            }
+        };
+
+        match serde_json::from_str(&json) {
+            Ok(config) => (config, None),
+            Err(parse_err) => {
+                let stamp = SystemTime::now()
+                    .duration_since(UNIX_EPOCH)
+                    .map(|d| d.as_secs())
+                    .unwrap_or_default();
+                let backup = app_data_dir.join(format!("{}.corrupt-{}", CONFIG_FILE, stamp));
+                let message = match std::fs::rename(&config_path, &backup) {
+                    Ok(()) => format!(
+                        "Settings file was corrupt ({}). It was backed up to {} and default settings were loaded.",
+                        parse_err,
+                        backup.display()
+                    ),
+                    Err(e) => format!(
+                        "Settings file {} is corrupt ({}) and could not be backed up: {}",
+                        config_path.display(),
+                        parse_err,
+                        e
+                    ),
+                };
+                (StorageConfig::default(), Some(message))
+            }
         }
     }
 
     pub fn save(&self, app_data_dir: &Path) -> Result<(), String> {
-        let config_path = app_data_dir.join("storage_config.json");
+        let config_path = app_data_dir.join(CONFIG_FILE);
         let json = serde_json::to_string_pretty(self)
             .map_err(|e| format!("Failed to serialize config: {}", e))?;
-        std::fs::write(&config_path, json)
+        std::fs::create_dir_all(app_data_dir)
+            .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        write_atomic(&config_path, json.as_bytes())
             .map_err(|e| format!("Failed to write config: {}", e))
     }
 
@@ -49,9 +130,32 @@ impl StorageConfig {
         self.workspaces.push(WorkspaceEntry {
             name: name.to_string(),
             path,
+            document_types: None,
+            filter: FilterSettings::default(),
         });
     }
 
+    /// Document types of the workspace rooted at `root`: its own override,
+    /// or the global setting.
+    pub fn document_types_for(&self, root: &Path) -> DocumentTypes {
+        self.workspaces
+            .iter()
+            .find(|w| w.path == root)
+            .and_then(|w| w.document_types.clone())
+            .unwrap_or_else(|| self.document_types.clone())
+    }
+
+    /// Listing rules of the workspace rooted at `root`.
+    pub fn workspace_filter(&self, root: &Path) -> WorkspaceFilter {
+        let settings = self
+            .workspaces
+            .iter()
+            .find(|w| w.path == root)
+            .map(|w| w.filter.clone())
+            .unwrap_or_default();
+        WorkspaceFilter::new(root, &settings, self.document_types_for(root))
+    }
+
     /// Remove a workspace root whose path matches the given path.
     /// Returns true if a workspace was removed.
     pub fn remove_workspace_by_path(&mut self, path: &Path) -> bool {
@@ -1,7 +1,16 @@
 use bytes::Bytes;
+use chromiumoxide::error::CdpError;
+use chromiumoxide::page::Page;
 use serde::{Deserialize, Serialize};
 use shiva::core::TransformerTrait;
 use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use crate::front_matter;
+use crate::links::{self, Link};
+use crate::pdf_layout::PdfOptions;
+use crate::storage::StorageError;
 
 /// Supported export formats
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +31,101 @@ impl ExportFormat {
     }
 }
 
+/// Image formats a Mermaid diagram can be rendered to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum DiagramFormat {
+    Svg,
+    Png,
+}
+
+impl DiagramFormat {
+    /// Parse format from string
+    pub fn from_str(s: &str) -> Result<Self, String> {
+        match s.to_lowercase().as_str() {
+            "svg" => Ok(DiagramFormat::Svg),
+            "png" => Ok(DiagramFormat::Png),
+            _ => Err(format!("Unsupported diagram format: {}. Supported: svg, png", s)),
+        }
+    }
+}
+
+/// How an export is assembled.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportOptions {
+    /// Inline the bundled Mermaid runtime instead of loading it from the CDN,
+    /// so the exported file renders the same without network access.
+    #[serde(default)]
+    pub self_contained: bool,
+    /// Render Mermaid diagrams to inline SVG in headless Chrome, so readers
+    /// need no JavaScript. Without Chrome the runtime is used instead.
+    #[serde(default)]
+    pub static_diagrams: bool,
+    /// Longest to wait, in seconds, for fonts, images and diagrams before a
+    /// PDF is printed (default `DEFAULT_READY_TIMEOUT_SECS`).
+    #[serde(default)]
+    pub ready_timeout_secs: Option<u64>,
+    /// Paper, margins, header and footer of PDF exports (see `pdf_layout.rs`).
+    #[serde(default)]
+    pub pdf: PdfOptions,
+}
+
+/// How long a PDF export waits for the page to finish rendering by default.
+pub const DEFAULT_READY_TIMEOUT_SECS: u64 = 30;
+
+impl ExportOptions {
+    pub fn ready_timeout(&self) -> Duration {
+        Duration::from_secs(self.ready_timeout_secs.unwrap_or(DEFAULT_READY_TIMEOUT_SECS))
+    }
+}
+
+/// A Mermaid block that could not be rendered.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiagramError {
+    /// 0-based position of the block among the document's Mermaid blocks.
+    pub index: usize,
+    /// Mermaid's error message, e.g. the parse error and its line.
+    pub message: String,
+}
+
+/// A Mermaid block rendered to an image, or Mermaid's error for it.
+#[derive(Debug, Clone)]
+pub struct RenderedDiagram {
+    pub index: usize,
+    pub image: Result<Vec<u8>, String>,
+}
+
+impl RenderedDiagram {
+    pub fn error(&self) -> Option<DiagramError> {
+        self.image.as_ref().err().map(|message| DiagramError {
+            index: self.index,
+            message: message.clone(),
+        })
+    }
+
+    /// The image, or Mermaid's error as `StorageError::InvalidDiagram`.
+    pub fn into_image(self) -> Result<Vec<u8>, String> {
+        let index = self.index;
+        self.image
+            .map_err(|reason| StorageError::InvalidDiagram { index, reason }.into())
+    }
+}
+
+/// Result of exporting a document, besides the file itself.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ExportReport {
+    /// Diagrams that Mermaid rejected; they are exported as their source.
+    pub diagram_errors: Vec<DiagramError>,
+}
+
+/// Mermaid runtime copied from `node_modules` by `build.rs`; empty when it
+/// wasn't installed at build time.
+const MERMAID_RUNTIME: &str = include_str!(concat!(env!("OUT_DIR"), "/mermaid.min.js"));
+
+/// Mermaid loaded from the CDN, for exports that aren't self-contained.
+const MERMAID_CDN_SCRIPT: &str =
+    r#"<script src="https://cdn.jsdelivr.net/npm/mermaid@10/dist/mermaid.min.js"></script>"#;
+
 /// HTML template with modern styling and Mermaid.js support
 const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
 <html lang="en">
@@ -170,6 +274,11 @@ const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
             border-radius: 6px;
         }
         
+        .mermaid-error {
+            text-align: left;
+            border-left: 4px solid #d73a49;
+        }
+        
         @media print {
             body {
                 max-width: none;
@@ -193,16 +302,19 @@ const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
         }
     </style>
     <!-- Mermaid.js for diagram rendering -->
-    <script src="https://cdn.jsdelivr.net/npm/mermaid@10/dist/mermaid.min.js"></script>
+    {{MERMAID_SCRIPT}}
     <script>
         document.addEventListener('DOMContentLoaded', function() {
+            if (typeof mermaid === 'undefined') return;
             // Detect dark mode
             const isDark = window.matchMedia && window.matchMedia('(prefers-color-scheme: dark)').matches;
             mermaid.initialize({
-                startOnLoad: true,
+                startOnLoad: false,
                 theme: isDark ? 'dark' : 'default',
                 securityLevel: 'loose'
             });
+            // Settles once every diagram has rendered or failed; PDF export waits for it
+            window.mermaidRendered = mermaid.run({ querySelector: '.mermaid', suppressErrors: true });
         });
     </script>
 </head>
@@ -214,11 +326,36 @@ const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
 /// Unique placeholder for mermaid blocks that won't be modified by Shiva
 const MERMAID_PLACEHOLDER: &str = "MERMAID_DIAGRAM_PLACEHOLDER_";
 
-/// Convert markdown content to the specified format
-pub fn convert_markdown(content: &str, format: &ExportFormat) -> Result<Vec<u8>, String> {
-    // Extract mermaid blocks and replace with placeholders
-    let (processed_content, mermaid_blocks) = extract_mermaid_blocks(content);
-    
+/// Convert markdown content to the specified format. `wiki_link` maps a
+/// `[[wiki link]]` to a link destination; links it can't resolve are
+/// rendered as plain text. `diagrams` are the document's Mermaid blocks
+/// pre-rendered to SVG (see `HeadlessBrowser::render_diagrams`); when empty,
+/// the blocks are left to the Mermaid runtime in the reader's browser.
+pub fn convert_markdown(
+    content: &str,
+    format: &ExportFormat,
+    options: &ExportOptions,
+    diagrams: &[RenderedDiagram],
+    wiki_link: &dyn Fn(&Link) -> Option<String>,
+) -> Result<Vec<u8>, String> {
+    // Front matter is metadata, not part of the rendered document
+    let body = front_matter::body(content);
+
+    // Extract mermaid blocks and replace with placeholders, before wiki
+    // links are replaced so `[[...]]` shapes in diagrams are left alone
+    let (body, mermaid_blocks) = extract_mermaid_blocks(body);
+
+    // Turn wiki links into regular markdown links
+    let processed_content = links::replace_wiki_links(&body, |link| {
+        let text = links::display_text(link)
+            .replace('[', "\\[")
+            .replace(']', "\\]");
+        match wiki_link(link) {
+            Some(href) => format!("[{}]({})", text, href),
+            None => text,
+        }
+    });
+
     // Parse markdown to Common Document Model
     let input_bytes = Bytes::from(processed_content);
     let document = shiva::markdown::Transformer::parse(&input_bytes)
@@ -230,7 +367,7 @@ pub fn convert_markdown(content: &str, format: &ExportFormat) -> Result<Vec<u8>,
             let raw_html = shiva::html::Transformer::generate(&document)
                 .map_err(|e| format!("Failed to generate HTML: {:?}", e))?;
             
-            // Extract title from first heading or use default
+            // Extract title from front matter, first heading, or use default
             let title = extract_title(content);
             
             // Convert raw HTML and inject mermaid divs back
@@ -239,30 +376,99 @@ pub fn convert_markdown(content: &str, format: &ExportFormat) -> Result<Vec<u8>,
             // Replace placeholders with actual mermaid divs
             for (i, mermaid_code) in mermaid_blocks.iter().enumerate() {
                 let placeholder = format!("{}{}", MERMAID_PLACEHOLDER, i);
-                let mermaid_div = format!(
-                    "<div class=\"mermaid\">\n{}\n</div>",
-                    mermaid_code.trim()
-                );
+                let mermaid_div = match diagrams.iter().find(|d| d.index == i).map(|d| &d.image) {
+                    Some(Ok(svg)) => format!(
+                        "<div class=\"mermaid\">\n{}\n</div>",
+                        String::from_utf8_lossy(svg)
+                    ),
+                    Some(Err(message)) => format!(
+                        "<div class=\"mermaid mermaid-error\">\n<p>Diagram {} could not be rendered: {}</p>\n<pre><code>{}</code></pre>\n</div>",
+                        i + 1,
+                        escape_html(message),
+                        escape_html(mermaid_code.trim())
+                    ),
+                    None => format!(
+                        "<div class=\"mermaid\">\n{}\n</div>",
+                        mermaid_code.trim()
+                    ),
+                };
                 html_content = html_content.replace(&placeholder, &mermaid_div);
             }
             
-            // Wrap with styled template
+            // Pre-rendered diagrams need no runtime; it would try to render
+            // the SVGs again.
+            let script = if diagrams.is_empty() {
+                mermaid_script(options)
+            } else {
+                String::new()
+            };
+            
+            // Wrap with styled template. The script goes in last and only
+            // into the head, so placeholder text in the document stays as is.
             let styled_html = HTML_TEMPLATE
                 .replace("{{TITLE}}", &title)
-                .replace("{{CONTENT}}", &html_content);
+                .replace("{{CONTENT}}", &html_content)
+                .replacen("{{MERMAID_SCRIPT}}", &script, 1);
             
             Bytes::from(styled_html.into_bytes())
         }
-        // PDF format is handled in main.rs via convert_html_to_pdf
+        // PDF format is handled in main.rs via HeadlessBrowser::html_to_pdf
         // This arm should never be reached since PDF goes through HTML first
         ExportFormat::Pdf => {
-            return Err("PDF format should be handled via convert_html_to_pdf".to_string());
+            return Err("PDF format should be handled via HeadlessBrowser::html_to_pdf".to_string());
         }
     };
 
     Ok(output_bytes.to_vec())
 }
 
+/// The `<script>` that provides Mermaid: the inlined runtime for
+/// self-contained exports, the CDN otherwise. Self-contained exports built
+/// without a bundled runtime get none, leaving diagrams as their source.
+fn mermaid_script(options: &ExportOptions) -> String {
+    if !options.self_contained {
+        return MERMAID_CDN_SCRIPT.to_string();
+    }
+    if !has_mermaid_runtime() {
+        return String::new();
+    }
+    inline_mermaid_runtime()
+}
+
+/// Whether `build.rs` found a Mermaid runtime to bundle.
+pub fn has_mermaid_runtime() -> bool {
+    !MERMAID_RUNTIME.is_empty()
+}
+
+/// Fail when no Mermaid runtime was bundled, for exports that promise to
+/// render diagrams without network access.
+pub fn require_mermaid_runtime() -> Result<(), String> {
+    if has_mermaid_runtime() {
+        Ok(())
+    } else {
+        Err("Mermaid was not bundled into this build, so diagrams can't be rendered offline; run `npm install` and rebuild".to_string())
+    }
+}
+
+/// The bundled runtime as an inline `<script>`.
+fn inline_mermaid_runtime() -> String {
+    // `</script` inside the runtime would end the element early.
+    format!("<script>\n{}\n</script>", MERMAID_RUNTIME.replace("</script", "<\\/script"))
+}
+
+/// The Mermaid blocks of a document, in order, as `convert_markdown` and
+/// the diagram export number them.
+pub fn mermaid_sources(content: &str) -> Vec<String> {
+    extract_mermaid_blocks(front_matter::body(content)).1
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
 /// Extract mermaid blocks from markdown and replace with unique placeholders
 /// Supports both ```mermaid (standard) and :::mermaid (Azure DevOps) syntax
 /// Returns the processed markdown and a list of mermaid diagram contents
@@ -303,9 +509,16 @@ fn extract_mermaid_blocks(content: &str) -> (String, Vec<String>) {
     (result, mermaid_blocks)
 }
 
-/// Extract title from first heading in markdown content
+/// Extract title from the front matter `title` key, falling back to the
+/// first heading after the front matter
 fn extract_title(content: &str) -> String {
-    for line in content.lines() {
+    if let Some(title) = front_matter::metadata(content)
+        .as_ref()
+        .and_then(front_matter::title)
+    {
+        return title.to_string();
+    }
+    for line in front_matter::body(content).lines() {
         let trimmed = line.trim();
         if trimmed.starts_with("# ") {
             return trimmed[2..].trim().to_string();
@@ -323,65 +536,282 @@ pub fn check_chrome_available() -> Result<PathBuf, String> {
         .map_err(|_| "Chrome, Chromium, or Edge is required for PDF export. Please install a Chromium-based browser.".to_string())
 }
 
-/// Convert HTML content to PDF using Chrome (async)
-/// Uses chromiumoxide's new headless mode to suppress window flash on Windows
-pub async fn convert_html_to_pdf(html: &str) -> Result<Vec<u8>, String> {
-    use chromiumoxide::browser::{Browser, BrowserConfig};
-    use chromiumoxide::cdp::browser_protocol::page::PrintToPdfParams;
-    use futures::StreamExt;
-    
-    // Build browser config with new headless mode (Chrome 112+)
-    // This uses --headless=new which should suppress window flash better
-    let config = BrowserConfig::builder()
-        .new_headless_mode()
-        .window_size(1200, 1600)
-        .arg("--disable-gpu")
-        .arg("--no-first-run")
-        .arg("--no-default-browser-check")
-        .build()
-        .map_err(|e| format!("Failed to build browser config: {:?}", e))?;
-    
-    // Launch browser
-    let (browser, mut handler) = Browser::launch(config)
-        .await
-        .map_err(|e| format!("Failed to launch browser: {:?}", e))?;
-    
-    // Spawn handler task
-    let handler_task = tokio::spawn(async move {
-        while let Some(_) = handler.next().await {}
-    });
-    
-    // Create a new page
-    let page = browser.new_page("about:blank")
-        .await
-        .map_err(|e| format!("Failed to create page: {:?}", e))?;
-    
-    // Set HTML content
-    page.set_content(html)
-        .await
-        .map_err(|e| format!("Failed to set HTML content: {:?}", e))?;
-    
-    // Wait for content to render (especially for Mermaid.js diagrams)
-    tokio::time::sleep(std::time::Duration::from_millis(1000)).await;
-    
-    // Print to PDF with A4 size and margins
-    let pdf_params = PrintToPdfParams::builder()
-        .paper_width(8.27)   // A4 width in inches
-        .paper_height(11.69) // A4 height in inches
-        .margin_top(0.5)
-        .margin_bottom(0.5)
-        .margin_left(0.5)
-        .margin_right(0.5)
-        .print_background(true)
-        .build();
-    
-    let pdf_bytes = page.pdf(pdf_params)
-        .await
-        .map_err(|e| format!("Failed to generate PDF: {:?}", e))?;
-    
-    // Close browser
-    drop(browser);
-    handler_task.abort();
-    
-    Ok(pdf_bytes)
+/// Page that diagrams are rendered in: the bundled runtime and an empty
+/// stage for PNG screenshots.
+const DIAGRAM_PAGE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+    <meta charset="UTF-8">
+    {{MERMAID_SCRIPT}}
+    <script>
+        mermaid.initialize({ startOnLoad: false, theme: 'default', securityLevel: 'strict' });
+    </script>
+    <style>
+        body { margin: 0; background: #ffffff; }
+        #stage { position: absolute; left: 0; top: 0; }
+    </style>
+</head>
+<body><div id="stage"></div></body>
+</html>"#;
+
+/// Renders diagram `{{SOURCE}}` (a JSON string) as `{{ID}}`; with `{{PNG}}`
+/// the SVG is also placed on the stage at its natural size. Resolves to
+/// `{ svg }` or `{ error }`.
+const RENDER_DIAGRAM_JS: &str = r#"(async () => {
+    const id = {{ID}};
+    try {
+        const { svg } = await mermaid.render(id, {{SOURCE}});
+        if ({{PNG}}) {
+            const stage = document.getElementById('stage');
+            stage.innerHTML = svg;
+            const el = stage.querySelector('svg');
+            const box = el.viewBox.baseVal;
+            el.setAttribute('width', box.width);
+            el.setAttribute('height', box.height);
+            el.style.maxWidth = 'none';
+        }
+        return { svg };
+    } catch (e) {
+        // Mermaid leaves its error graphic behind in the page.
+        document.getElementById('d' + id)?.remove();
+        return { error: String((e && e.message) || e) };
+    }
+})()"#;
+
+/// Resolves once the page has loaded, its fonts are ready, every image has
+/// decoded (or failed to) and the Mermaid runtime, if any, has settled.
+const PAGE_READY_JS: &str = r#"(async () => {
+    if (document.readyState !== 'complete') {
+        await new Promise((resolve) => window.addEventListener('load', resolve, { once: true }));
+    }
+    await document.fonts.ready;
+    await Promise.all(Array.from(document.images, (img) => img.decode().catch(() => {})));
+    if (window.mermaidRendered) await window.mermaidRendered.catch(() => {});
+    return true;
+})()"#;
+
+/// What `PAGE_READY_JS` is still waiting for, in words.
+const PAGE_PENDING_JS: &str = r#"(() => {
+    const pending = [];
+    if (document.readyState !== 'complete') pending.push('page load');
+    if (document.fonts.status !== 'loaded') pending.push('fonts');
+    for (const img of document.images) {
+        if (!img.complete) pending.push('image ' + (img.getAttribute('src') || '').slice(0, 200));
+    }
+    if (typeof mermaid !== 'undefined') {
+        document.querySelectorAll('.mermaid').forEach((el, i) => {
+            if (!el.hasAttribute('data-processed')) pending.push('Mermaid diagram ' + (i + 1));
+        });
+    }
+    return pending;
+})()"#;
+
+#[derive(Deserialize)]
+struct RenderOutcome {
+    svg: Option<String>,
+    error: Option<String>,
+}
+
+/// A headless Chrome for rendering diagrams and printing PDFs, usually
+/// shared through `BrowserPool`. Pages are reused between calls. The browser
+/// is shut down when this is dropped.
+pub struct HeadlessBrowser {
+    browser: chromiumoxide::browser::Browser,
+    handler: tokio::task::JoinHandle<()>,
+    /// Blank pages left by earlier calls.
+    idle_pages: Mutex<Vec<Page>>,
+}
+
+impl HeadlessBrowser {
+    /// Launch Chrome in the new headless mode (Chrome 112+), which
+    /// suppresses the window flash on Windows.
+    pub async fn launch() -> Result<Self, String> {
+        use chromiumoxide::browser::{Browser, BrowserConfig};
+        use futures::StreamExt;
+
+        let config = BrowserConfig::builder()
+            .new_headless_mode()
+            .window_size(1200, 1600)
+            .arg("--disable-gpu")
+            .arg("--no-first-run")
+            .arg("--no-default-browser-check")
+            .build()
+            .map_err(|e| format!("Failed to build browser config: {:?}", e))?;
+
+        let (browser, mut handler) = Browser::launch(config)
+            .await
+            .map_err(|e| format!("Failed to launch browser: {:?}", e))?;
+
+        let handler = tokio::spawn(async move {
+            while let Some(event) = handler.next().await {
+                // The connection is gone when Chrome exits or crashes.
+                if matches!(event, Err(CdpError::Ws(_))) {
+                    break;
+                }
+            }
+        });
+
+        Ok(HeadlessBrowser { browser, handler, idle_pages: Mutex::new(Vec::new()) })
+    }
+
+    /// Whether Chrome is still running and answering.
+    pub async fn is_alive(&self) -> bool {
+        !self.handler.is_finished()
+            && tokio::time::timeout(Duration::from_secs(5), self.browser.version())
+                .await
+                .is_ok_and(|version| version.is_ok())
+    }
+
+    /// Ask Chrome to exit and wait until it has.
+    pub async fn close(mut self) {
+        if self.browser.close().await.is_ok() {
+            let _ = self.browser.wait().await;
+        }
+    }
+
+    /// A blank page: a reused one, or a new one.
+    async fn page(&self) -> Result<Page, String> {
+        let reused = self.idle_pages.lock().ok().and_then(|mut pages| pages.pop());
+        match reused {
+            Some(page) => Ok(page),
+            None => self.browser.new_page("about:blank")
+                .await
+                .map_err(|e| format!("Failed to create page: {:?}", e)),
+        }
+    }
+
+    /// Hand `page` back for reuse once it is blank again. Pages that failed
+    /// somewhere along the way are closed instead, since their state is
+    /// unknown.
+    async fn release_page(&self, page: Page, reusable: bool) {
+        if reusable && page.goto("about:blank").await.is_ok() {
+            if let Ok(mut pages) = self.idle_pages.lock() {
+                pages.push(page);
+                return;
+            }
+        }
+        let _ = page.close().await;
+    }
+
+    /// Render Mermaid diagram `index` of `sources`, or all of them, to
+    /// `format`. A diagram Mermaid rejects gets its error message instead of
+    /// an image; only browser failures fail the whole call.
+    pub async fn render_diagrams(
+        &self,
+        sources: &[String],
+        index: Option<usize>,
+        format: DiagramFormat,
+    ) -> Result<Vec<RenderedDiagram>, String> {
+        let indices: Vec<usize> = match index {
+            Some(i) if i >= sources.len() => {
+                return Err(format!(
+                    "No Mermaid diagram {} in the document ({} found)",
+                    i,
+                    sources.len()
+                ));
+            }
+            Some(i) => vec![i],
+            None => (0..sources.len()).collect(),
+        };
+        if indices.is_empty() {
+            return Ok(Vec::new());
+        }
+        require_mermaid_runtime()?;
+
+        let page = self.page().await?;
+        let rendered = Self::render_on(&page, sources, indices, format).await;
+        self.release_page(page, rendered.is_ok()).await;
+        rendered
+    }
+
+    async fn render_on(
+        page: &Page,
+        sources: &[String],
+        indices: Vec<usize>,
+        format: DiagramFormat,
+    ) -> Result<Vec<RenderedDiagram>, String> {
+        use chromiumoxide::cdp::browser_protocol::page::CaptureScreenshotFormat;
+
+        page.set_content(DIAGRAM_PAGE.replacen("{{MERMAID_SCRIPT}}", &inline_mermaid_runtime(), 1))
+            .await
+            .map_err(|e| format!("Failed to set HTML content: {:?}", e))?;
+
+        let mut rendered = Vec::with_capacity(indices.len());
+        for index in indices {
+            let script = RENDER_DIAGRAM_JS
+                .replace("{{ID}}", &format!("'diagram-{}'", index))
+                .replace("{{PNG}}", if format == DiagramFormat::Png { "true" } else { "false" })
+                .replace("{{SOURCE}}", &serde_json::Value::String(sources[index].clone()).to_string());
+            let outcome: RenderOutcome = page.evaluate(script)
+                .await
+                .map_err(|e| format!("Failed to render diagram {}: {:?}", index, e))?
+                .into_value()
+                .map_err(|e| format!("Failed to read diagram {}: {}", index, e))?;
+
+            let image = match (outcome.svg, outcome.error) {
+                (Some(svg), _) if format == DiagramFormat::Svg => Ok(svg.into_bytes()),
+                (Some(_), _) => page.find_element("#stage > svg")
+                    .await
+                    .map_err(|e| format!("Failed to find diagram {}: {:?}", index, e))?
+                    .screenshot(CaptureScreenshotFormat::Png)
+                    .await
+                    .map(Ok)
+                    .map_err(|e| format!("Failed to capture diagram {}: {:?}", index, e))?,
+                (None, error) => Err(error.unwrap_or_else(|| "Unknown Mermaid error".to_string())),
+            };
+            rendered.push(RenderedDiagram { index, image });
+        }
+        Ok(rendered)
+    }
+
+    /// Print an HTML document to a PDF laid out as `layout` once it has
+    /// finished rendering.
+    /// Fails with `StorageError::RenderTimeout` if that takes longer than
+    /// `timeout`.
+    pub async fn html_to_pdf(&self, html: &str, layout: &PdfOptions, timeout: Duration) -> Result<Vec<u8>, String> {
+        let page = self.page().await?;
+        let pdf = Self::print_page(&page, html, layout, timeout).await;
+        self.release_page(page, pdf.is_ok()).await;
+        pdf
+    }
+
+    async fn print_page(page: &Page, html: &str, layout: &PdfOptions, timeout: Duration) -> Result<Vec<u8>, String> {
+        // Set HTML content
+        page.set_content(html)
+            .await
+            .map_err(|e| format!("Failed to set HTML content: {:?}", e))?;
+
+        // Wait for fonts, images and Mermaid.js diagrams
+        Self::wait_until_ready(page, timeout).await?;
+
+        page.pdf(layout.print_params())
+            .await
+            .map_err(|e| format!("Failed to generate PDF: {:?}", e))
+    }
+
+    /// Wait for `PAGE_READY_JS`, listing what never finished on timeout.
+    async fn wait_until_ready(page: &Page, timeout: Duration) -> Result<(), String> {
+        match tokio::time::timeout(timeout, page.evaluate(PAGE_READY_JS)).await {
+            Ok(ready) => ready
+                .map(|_| ())
+                .map_err(|e| format!("Failed to wait for the page to render: {:?}", e)),
+            Err(_) => {
+                let pending: Vec<String> = match page.evaluate(PAGE_PENDING_JS).await {
+                    Ok(result) => result.into_value().unwrap_or_default(),
+                    Err(_) => Vec::new(),
+                };
+                Err(StorageError::RenderTimeout {
+                    timeout_secs: timeout.as_secs(),
+                    pending,
+                }
+                .into())
+            }
+        }
+    }
+}
+
+impl Drop for HeadlessBrowser {
+    fn drop(&mut self) {
+        self.handler.abort();
+    }
 }
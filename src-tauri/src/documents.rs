@@ -0,0 +1,120 @@
+//! Which files count as documents.
+//!
+//! A file is a document when its extension is one of `DocumentTypes::extensions`
+//! or its whole name is one of `DocumentTypes::file_names` (for `README` and
+//! friends without an extension); both compare case-insensitively. The set is
+//! configured globally in `StorageConfig::document_types` and can be
+//! overridden per workspace.
+//!
+//! Documents keep their real extension: only `.md` is hidden from display
+//! names, and a name given without a recognized extension gets the
+//! document's current one (or, for new documents and documents without an
+//! extension, the first configured one). Saving a document under its own
+//! display name never renames it.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Extension hidden from display names and used when none is configured.
+pub const DEFAULT_EXTENSION: &str = "md";
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DocumentTypes {
+    /// Extensions without the leading dot, e.g. `md`. The first one is used
+    /// for new documents.
+    pub extensions: Vec<String>,
+    /// File names recognized as documents without an extension, e.g. `README`.
+    #[serde(default)]
+    pub file_names: Vec<String>,
+}
+
+impl Default for DocumentTypes {
+    fn default() -> Self {
+        DocumentTypes {
+            extensions: ["md", "markdown", "mdown", "mdx", "txt"].map(String::from).to_vec(),
+            file_names: vec!["README".to_string()],
+        }
+    }
+}
+
+impl DocumentTypes {
+    /// Trim, lowercase extensions and drop their leading dot, and remove
+    /// empty entries and duplicates.
+    pub fn normalized(self) -> Self {
+        fn clean(values: Vec<String>, extension: bool) -> Vec<String> {
+            let mut out: Vec<String> = Vec::new();
+            for value in values {
+                let value = value.trim();
+                let value = if extension {
+                    value.trim_start_matches('.').to_lowercase()
+                } else {
+                    value.to_string()
+                };
+                if !value.is_empty() && !out.iter().any(|v| v.eq_ignore_ascii_case(&value)) {
+                    out.push(value);
+                }
+            }
+            out
+        }
+        DocumentTypes {
+            extensions: clean(self.extensions, true),
+            file_names: clean(self.file_names, false),
+        }
+    }
+
+    pub fn is_document(&self, path: &Path) -> bool {
+        match path.extension() {
+            Some(ext) => {
+                let ext = ext.to_string_lossy();
+                self.extensions.iter().any(|e| e.eq_ignore_ascii_case(&ext))
+            }
+            None => path.file_name().is_some_and(|name| {
+                let name = name.to_string_lossy();
+                self.file_names.iter().any(|n| n.eq_ignore_ascii_case(&name))
+            }),
+        }
+    }
+
+    /// Extension given to new documents named without one.
+    pub fn default_extension(&self) -> &str {
+        self.extensions.first().map(String::as_str).unwrap_or(DEFAULT_EXTENSION)
+    }
+
+    /// File name for a document called `name`. Renaming to the document's
+    /// own display name keeps its file name. Otherwise the name is kept as is
+    /// when it already has a recognized extension (or is a recognized file
+    /// name), and is given the extension of `current` (when renaming) or the
+    /// default one.
+    pub fn file_name(&self, name: &str, current: Option<&Path>) -> String {
+        let current_name = current
+            .and_then(Path::file_name)
+            .map(|n| n.to_string_lossy().to_string());
+        if let Some(current_name) = current_name {
+            if display_name(&current_name) == name {
+                return current_name;
+            }
+        }
+        if self.is_document(Path::new(name)) {
+            return name.to_string();
+        }
+        let extension = current
+            .and_then(Path::extension)
+            .map(|ext| ext.to_string_lossy().to_string())
+            .unwrap_or_else(|| self.default_extension().to_string());
+        format!("{}.{}", name, extension)
+    }
+}
+
+/// Name shown for a document file: `.md` (in any case) is hidden, other
+/// extensions stay.
+pub fn display_name(file_name: &str) -> String {
+    let suffix_len = DEFAULT_EXTENSION.len() + 1;
+    if file_name.len() > suffix_len && file_name.is_char_boundary(file_name.len() - suffix_len) {
+        let (stem, suffix) = file_name.split_at(file_name.len() - suffix_len);
+        if suffix.eq_ignore_ascii_case(&format!(".{}", DEFAULT_EXTENSION)) {
+            return stem.to_string();
+        }
+    }
+    file_name.to_string()
+}
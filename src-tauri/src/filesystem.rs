@@ -1,19 +1,52 @@
-use std::fs;
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::ErrorKind;
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, RwLock};
 use std::time::SystemTime;
 
+use crate::atomic::write_atomic;
+use crate::attachments;
 use crate::config::StorageConfig;
-use crate::storage::{SearchResult, TreeNode, TreeNodeKind};
+use crate::documents::{self, DocumentTypes};
+use crate::front_matter::{self, Metadata};
+use crate::history::{EditOrigin, HistoryRevision, HistoryStore};
+use crate::quick_open::{self, RecentFiles};
+use crate::search_index::{self, SearchIndex};
+use crate::sandbox;
+use crate::search_query;
+use crate::link_check;
+use crate::links::{self, LinkGraph, PathMove, Resolver};
+use crate::storage::{
+    Backlink, CollisionPolicy, ImportedAttachment, LinkReport, MovedEntry, QuickOpenResult, SearchPage, SearchResult,
+    StorageError, TagCount, TagRenameResult, TreeNode, TreeNodeKind, UpdateFailure,
+};
+use crate::tags;
+use crate::transfer;
+use crate::trash::{self, TrashEntry};
+use crate::watcher::{self, FsChange, FsChangeKind};
+use crate::workspace_filter::{FilterSettings, WorkspaceFilter};
 
 pub struct FilesystemStorage {
     config: Arc<RwLock<StorageConfig>>,
     config_dir: PathBuf,
+    history: HistoryStore,
+    index: SearchIndex,
+    recent: RecentFiles,
 }
 
 impl FilesystemStorage {
     pub fn new(config: Arc<RwLock<StorageConfig>>, config_dir: PathBuf) -> Self {
-        FilesystemStorage { config, config_dir }
+        let history = HistoryStore::new(config_dir.join("history"));
+        let index = SearchIndex::load(config_dir.join("search_index.json.gz"));
+        let recent = RecentFiles::load(config_dir.join("recent_files.json"));
+        FilesystemStorage {
+            config,
+            config_dir,
+            history,
+            index,
+            recent,
+        }
     }
 
     fn workspaces(&self) -> Vec<(String, PathBuf)> {
@@ -28,12 +61,20 @@ impl FilesystemStorage {
             .unwrap_or_default()
     }
 
-    fn is_hidden(entry: &fs::DirEntry) -> bool {
-        entry
-            .file_name()
-            .to_str()
-            .map(|n| n.starts_with('.'))
-            .unwrap_or(false)
+    /// Paths of all registered workspace roots (used to keep the watcher in sync).
+    pub fn root_paths(&self) -> Vec<PathBuf> {
+        self.workspaces().into_iter().map(|(_, path)| path).collect()
+    }
+
+    /// Tree kind of an existing entry.
+    fn entry_kind(meta: &fs::Metadata, path: &Path) -> TreeNodeKind {
+        if meta.is_dir() {
+            TreeNodeKind::Folder
+        } else if attachments::is_attachment(path) {
+            TreeNodeKind::Attachment
+        } else {
+            TreeNodeKind::Document
+        }
     }
 
     fn fmt_timestamp(t: SystemTime) -> String {
@@ -64,9 +105,26 @@ impl FilesystemStorage {
         (created, modified)
     }
 
+    /// Revision token for a document: modification time (ns) and size, hex-encoded.
+    /// Cheap enough to compute for every listed entry, and any write through this
+    /// storage or an outside editor changes at least one of the two.
+    fn document_revision(meta: &fs::Metadata) -> Option<String> {
+        if !meta.is_file() {
+            return None;
+        }
+        let mtime = meta
+            .modified()
+            .ok()?
+            .duration_since(std::time::UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_nanos();
+        Some(format!("{:x}-{:x}", mtime, meta.len()))
+    }
+
     fn entry_to_treenode(
         entry: &fs::DirEntry,
         parent_id: &str,
+        filter: &WorkspaceFilter,
     ) -> Result<Option<TreeNode>, String> {
         let file_type = entry.file_type().map_err(|e| format!("I/O error: {}", e))?;
         let path = entry.path();
@@ -83,11 +141,13 @@ impl FilesystemStorage {
                 content: None,
                 created_at,
                 updated_at,
+                revision: Self::document_revision(&meta),
+                metadata: None,
             }))
         } else if file_type.is_file() {
             let raw_name = entry.file_name().to_string_lossy().to_string();
-            if raw_name.ends_with(".md") {
-                let name = strip_md_suffix(&raw_name);
+            if filter.is_document(&path) {
+                let name = documents::display_name(&raw_name);
                 Ok(Some(TreeNode {
                     id: path.to_string_lossy().to_string(),
                     parent_id: Some(parent_id.to_string()),
@@ -96,6 +156,20 @@ impl FilesystemStorage {
                     content: None,
                     created_at,
                     updated_at,
+                    revision: Self::document_revision(&meta),
+                    metadata: front_matter::read_metadata(&path),
+                }))
+            } else if attachments::is_attachment(&path) {
+                Ok(Some(TreeNode {
+                    id: path.to_string_lossy().to_string(),
+                    parent_id: Some(parent_id.to_string()),
+                    name: raw_name,
+                    kind: TreeNodeKind::Attachment,
+                    content: None,
+                    created_at,
+                    updated_at,
+                    revision: Self::document_revision(&meta),
+                    metadata: None,
                 }))
             } else {
                 Ok(None)
@@ -119,9 +193,31 @@ impl FilesystemStorage {
             content: None,
             created_at,
             updated_at,
+            revision: None,
+            metadata: None,
         }
     }
 
+    /// TreeNode (without content) for a document path, or None if it is gone.
+    fn document_node(path: &Path) -> Option<TreeNode> {
+        let meta = fs::metadata(path).ok().filter(|m| m.is_file())?;
+        let (created_at, updated_at) = Self::metadata_to_timestamps(&meta);
+        Some(TreeNode {
+            id: path.to_string_lossy().to_string(),
+            parent_id: path.parent().map(|p| p.to_string_lossy().to_string()),
+            name: path
+                .file_name()
+                .map(|n| documents::display_name(&n.to_string_lossy()))
+                .unwrap_or_default(),
+            kind: TreeNodeKind::Document,
+            content: None,
+            created_at,
+            updated_at,
+            revision: Self::document_revision(&meta),
+            metadata: front_matter::read_metadata(path),
+        })
+    }
+
     pub fn list_roots(&self) -> Result<Vec<TreeNode>, String> {
         Ok(self
             .workspaces()
@@ -161,11 +257,100 @@ impl FilesystemStorage {
             return Err(format!("Workspace root not found: {}", id));
         }
         cfg.save(&self.config_dir)?;
+        drop(cfg);
+
+        // Documents stay indexed if the folder is still inside another root.
+        if self.root_for(&path).is_none() {
+            self.index.remove_prefix(&path);
+        }
         Ok(true)
     }
 
+    /// Document types in effect for the root `root_id`, or the global
+    /// setting when no root is given.
+    pub fn get_document_types(&self, root_id: Option<&str>) -> Result<DocumentTypes, String> {
+        let cfg = self.config.read().map_err(|e| e.to_string())?;
+        match root_id {
+            Some(id) => {
+                let root = PathBuf::from(id);
+                if !cfg.workspaces.iter().any(|w| w.path == root) {
+                    return Err(format!("Workspace root not found: {}", id));
+                }
+                Ok(cfg.document_types_for(&root))
+            }
+            None => Ok(cfg.document_types.clone()),
+        }
+    }
+
+    /// Set the document types of the root `root_id` (`None` falls back to the
+    /// global setting) or, without a root, the global setting (`None` restores
+    /// the defaults). Call `sync_search_index` afterwards to pick up documents
+    /// that appeared or disappeared.
+    pub fn set_document_types(
+        &self,
+        root_id: Option<&str>,
+        types: Option<DocumentTypes>,
+    ) -> Result<DocumentTypes, String> {
+        let types = types.map(DocumentTypes::normalized);
+        if types.as_ref().is_some_and(|t| t.extensions.is_empty() && t.file_names.is_empty()) {
+            return Err("At least one document extension or file name is required".into());
+        }
+        let mut cfg = self.config.write().map_err(|e| e.to_string())?;
+        match root_id {
+            Some(id) => {
+                let root = PathBuf::from(id);
+                let workspace = cfg
+                    .workspaces
+                    .iter_mut()
+                    .find(|w| w.path == root)
+                    .ok_or_else(|| format!("Workspace root not found: {}", id))?;
+                workspace.document_types = types;
+            }
+            None => cfg.document_types = types.unwrap_or_default(),
+        }
+        cfg.save(&self.config_dir)?;
+        Ok(match root_id {
+            Some(id) => cfg.document_types_for(Path::new(id)),
+            None => cfg.document_types.clone(),
+        })
+    }
+
+    /// Include/exclude globs and hidden-entry setting of the root `root_id`.
+    pub fn get_workspace_filter(&self, root_id: &str) -> Result<FilterSettings, String> {
+        let cfg = self.config.read().map_err(|e| e.to_string())?;
+        cfg.workspaces
+            .iter()
+            .find(|w| w.path == Path::new(root_id))
+            .map(|w| w.filter.clone())
+            .ok_or_else(|| format!("Workspace root not found: {}", root_id))
+    }
+
+    /// Replace the listing settings of the root `root_id`. Invalid globs are
+    /// rejected. Call `sync_search_index` afterwards so newly hidden documents
+    /// leave the index and newly shown ones join it.
+    pub fn set_workspace_filter(&self, root_id: &str, settings: FilterSettings) -> Result<FilterSettings, String> {
+        let clean = |globs: Vec<String>| -> Vec<String> {
+            globs.into_iter().map(|g| g.trim().to_string()).filter(|g| !g.is_empty()).collect()
+        };
+        let settings = FilterSettings {
+            include: clean(settings.include),
+            exclude: clean(settings.exclude),
+            show_hidden: settings.show_hidden,
+        };
+        settings.validate()?;
+        let mut cfg = self.config.write().map_err(|e| e.to_string())?;
+        let workspace = cfg
+            .workspaces
+            .iter_mut()
+            .find(|w| w.path == Path::new(root_id))
+            .ok_or_else(|| format!("Workspace root not found: {}", root_id))?;
+        workspace.filter = settings.clone();
+        cfg.save(&self.config_dir)?;
+        Ok(settings)
+    }
+
     pub fn get_entry(&self, id: &str) -> Result<Option<TreeNode>, String> {
-        let path = PathBuf::from(id);
+        let path = self.confine(id)?;
         let meta = match fs::metadata(&path) {
             Ok(m) => m,
             Err(_) => return Ok(None),
@@ -196,15 +381,26 @@ impl FilesystemStorage {
                 content: None,
                 created_at,
                 updated_at,
+                revision: Self::document_revision(&meta),
+                metadata: None,
+            }))
+        } else if attachments::is_attachment(&path) {
+            Ok(Some(TreeNode {
+                id: path.to_string_lossy().to_string(),
+                parent_id,
+                name,
+                kind: TreeNodeKind::Attachment,
+                content: None,
+                created_at,
+                updated_at,
+                revision: Self::document_revision(&meta),
+                metadata: None,
             }))
         } else {
             let content = fs::read_to_string(&path)
                 .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
-            let display_name = if name.ends_with(".md") {
-                strip_md_suffix(&name)
-            } else {
-                name
-            };
+            let metadata = front_matter::metadata(&content);
+            let display_name = documents::display_name(&name);
             Ok(Some(TreeNode {
                 id: path.to_string_lossy().to_string(),
                 parent_id,
@@ -213,6 +409,8 @@ impl FilesystemStorage {
                 content: Some(content),
                 created_at,
                 updated_at,
+                revision: Self::document_revision(&meta),
+                metadata,
             }))
         }
     }
@@ -225,23 +423,25 @@ impl FilesystemStorage {
     }
 
     pub fn list_children(&self, parent_id: &str) -> Result<Vec<TreeNode>, String> {
-        let dir_path = PathBuf::from(parent_id);
+        let dir_path = self.confine(parent_id)?;
 
         if !dir_path.is_dir() {
             return Err(format!("Not a directory: {}", dir_path.display()));
         }
 
         let mut children: Vec<TreeNode> = Vec::new();
+        let mut filter = self.workspace_filter(&dir_path);
 
         let entries = fs::read_dir(&dir_path)
             .map_err(|e| format!("Failed to read directory {}: {}", dir_path.display(), e))?;
 
         for entry in entries {
             let entry = entry.map_err(|e| format!("I/O error: {}", e))?;
-            if Self::is_hidden(&entry) {
+            let is_dir = entry.file_type().map(|t| t.is_dir()).unwrap_or(false);
+            if !filter.shows(&entry.path(), is_dir) {
                 continue;
             }
-            if let Some(node) = Self::entry_to_treenode(&entry, parent_id)? {
+            if let Some(node) = Self::entry_to_treenode(&entry, parent_id, &filter)? {
                 children.push(node);
             }
         }
@@ -255,12 +455,16 @@ impl FilesystemStorage {
         Ok(children)
     }
 
-    pub fn create_folder(&self, parent_id: &str, name: &str) -> Result<TreeNode, String> {
-        let parent_path = PathBuf::from(parent_id);
-        let new_path = parent_path.join(name);
+    /// Create a folder; `on_collision` decides what happens when `name` is taken.
+    pub fn create_folder(&self, parent_id: &str, name: &str, on_collision: CollisionPolicy) -> Result<TreeNode, String> {
+        sandbox::check_name(name)?;
+        let parent_path = self.confine(parent_id)?;
+        let new_path = self.resolve_collision(parent_path.join(name), None, true, on_collision)?;
 
-        fs::create_dir(&new_path)
-            .map_err(|e| format!("Failed to create directory {}: {}", new_path.display(), e))?;
+        fs::create_dir(&new_path).map_err(|e| match e.kind() {
+            ErrorKind::AlreadyExists => already_exists(&new_path),
+            _ => format!("Failed to create directory {}: {}", new_path.display(), e),
+        })?;
 
         let meta = fs::metadata(&new_path)
             .map_err(|e| format!("Failed to read metadata: {}", e))?;
@@ -269,31 +473,49 @@ impl FilesystemStorage {
         Ok(TreeNode {
             id: new_path.to_string_lossy().to_string(),
             parent_id: Some(parent_id.to_string()),
-            name: name.to_string(),
+            name: file_name(&new_path),
             kind: TreeNodeKind::Folder,
             content: None,
             created_at,
             updated_at,
+            revision: Self::document_revision(&meta),
+            metadata: None,
         })
     }
 
+    /// Create a document; `on_collision` decides what happens when `name` is
+    /// taken. An existing file is never truncated in place.
     pub fn create_document(
         &self,
         parent_id: &str,
         name: &str,
         content: &str,
+        on_collision: CollisionPolicy,
     ) -> Result<TreeNode, String> {
-        let parent_path = PathBuf::from(parent_id);
-        let filename = ensure_md_extension(name);
-        let new_path = parent_path.join(&filename);
-
-        fs::write(&new_path, content)
-            .map_err(|e| format!("Failed to write {}: {}", new_path.display(), e))?;
+        sandbox::check_name(name)?;
+        let parent_path = self.confine(parent_id)?;
+        let filename = self.document_types(&parent_path).file_name(name, None);
+        let new_path = self.resolve_collision(parent_path.join(&filename), None, false, on_collision)?;
+
+        // Claim the name first, so a file that appeared in the meantime isn't replaced.
+        File::options()
+            .write(true)
+            .create_new(true)
+            .open(&new_path)
+            .map_err(|e| match e.kind() {
+                ErrorKind::AlreadyExists => already_exists(&new_path),
+                _ => format!("Failed to create {}: {}", new_path.display(), e),
+            })?;
+        write_atomic(&new_path, content.as_bytes()).map_err(|e| {
+            let _ = fs::remove_file(&new_path);
+            format!("Failed to write {}: {}", new_path.display(), e)
+        })?;
+        self.reindex(&new_path);
 
         let meta = fs::metadata(&new_path)
             .map_err(|e| format!("Failed to read metadata: {}", e))?;
         let (created_at, updated_at) = Self::metadata_to_timestamps(&meta);
-        let display_name = strip_md_suffix(&filename);
+        let display_name = documents::display_name(&file_name(&new_path));
 
         Ok(TreeNode {
             id: new_path.to_string_lossy().to_string(),
@@ -303,46 +525,60 @@ impl FilesystemStorage {
             content: Some(content.to_string()),
             created_at,
             updated_at,
+            revision: Self::document_revision(&meta),
+            metadata: front_matter::metadata(content),
         })
     }
 
+    /// Overwrite a document (renaming it first if `name` changed). When
+    /// `expected_revision` is given and the file on disk no longer has that
+    /// revision, nothing is written and a `StorageError::Conflict` carrying the
+    /// current content is returned instead. Successful writes are snapshotted
+    /// into the version history, tagged with `origin`. `on_collision` applies
+    /// when the new name is taken by another entry.
     pub fn update_document(
         &self,
         id: &str,
         name: &str,
         content: &str,
+        expected_revision: Option<&str>,
+        on_collision: CollisionPolicy,
+        origin: EditOrigin,
     ) -> Result<TreeNode, String> {
-        let old_path = PathBuf::from(id);
-        if !old_path.is_file() {
-            return Err(format!("Not a file: {}", old_path.display()));
+        sandbox::check_name(name)?;
+        let old_path = self.confine_document(id)?;
+
+        if let Some(expected) = expected_revision {
+            Self::check_revision(&old_path, expected)?;
         }
 
-        let new_filename = ensure_md_extension(name);
+        let new_filename = self.document_types(&old_path).file_name(name, Some(&old_path));
 
-        let new_path = if let Some(parent) = old_path.parent() {
+        let mut new_path = if let Some(parent) = old_path.parent() {
             parent.join(&new_filename)
         } else {
             PathBuf::from(&new_filename)
         };
 
+        let previous = fs::read_to_string(&old_path).ok();
+
         if new_path != old_path {
-            fs::rename(&old_path, &new_path).map_err(|e| {
-                format!(
-                    "Failed to rename {} to {}: {}",
-                    old_path.display(),
-                    new_path.display(),
-                    e
-                )
-            })?;
+            new_path = self.resolve_collision(new_path, Some(&old_path), false, on_collision)?;
+            rename_new(&old_path, &new_path)?;
+            let _ = self.history.rename_path(&old_path, &new_path);
+            self.index.remove_prefix(&old_path);
         }
 
-        fs::write(&new_path, content)
+        write_atomic(&new_path, content.as_bytes())
             .map_err(|e| format!("Failed to write {}: {}", new_path.display(), e))?;
+        // History is a safety net; failing to record it must not fail the save.
+        let _ = self.history.record(&new_path, previous.as_deref(), content, origin);
+        self.reindex(&new_path);
 
         let meta = fs::metadata(&new_path)
             .map_err(|e| format!("Failed to read metadata: {}", e))?;
         let (created_at, updated_at) = Self::metadata_to_timestamps(&meta);
-        let display_name = name.to_string();
+        let display_name = documents::display_name(&file_name(&new_path));
 
         Ok(TreeNode {
             id: new_path.to_string_lossy().to_string(),
@@ -354,281 +590,901 @@ impl FilesystemStorage {
             content: Some(content.to_string()),
             created_at,
             updated_at,
+            revision: Self::document_revision(&meta),
+            metadata: front_matter::metadata(content),
         })
     }
 
-    pub fn rename_entry(&self, id: &str, new_name: &str) -> Result<TreeNode, String> {
-        let old_path = PathBuf::from(id);
+    /// Fail with `StorageError::Conflict` if the file at `path` is no longer at `expected`.
+    fn check_revision(path: &Path, expected: &str) -> Result<(), String> {
+        let meta = fs::metadata(path)
+            .map_err(|e| format!("Failed to read metadata: {}", e))?;
+        let current = Self::document_revision(&meta).unwrap_or_default();
+        if current == expected {
+            return Ok(());
+        }
+        let current_content = fs::read_to_string(path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Err(StorageError::Conflict {
+            id: path.to_string_lossy().to_string(),
+            expected_revision: expected.to_string(),
+            current_revision: current,
+            current_content,
+        }
+        .into())
+    }
+
+    /// Apply `on_collision` to `target`, where a create, rename or move is
+    /// about to put an entry (`source`, unless it's new). Returns `target`
+    /// when it is free or already is `source` (a rename that only changes
+    /// case), a free `Name (2)` variant, or `target` after its current entry
+    /// was moved to the trash.
+    fn resolve_collision(
+        &self,
+        target: PathBuf,
+        source: Option<&Path>,
+        is_dir: bool,
+        on_collision: CollisionPolicy,
+    ) -> Result<PathBuf, String> {
+        if fs::symlink_metadata(&target).is_err() || source.is_some_and(|s| same_entry(s, &target)) {
+            return Ok(target);
+        }
+        match on_collision {
+            CollisionPolicy::Fail => Err(already_exists(&target)),
+            CollisionPolicy::AutoSuffix => {
+                let dir = target
+                    .parent()
+                    .ok_or_else(|| format!("Invalid path: {}", target.display()))?;
+                Ok(transfer::free_numbered_path(dir, &file_name(&target), is_dir))
+            }
+            CollisionPolicy::Overwrite => {
+                if let Some(source) = source.filter(|s| s.starts_with(&target)) {
+                    return Err(format!("Cannot overwrite {}: it contains {}", target.display(), source.display()));
+                }
+                self.delete_entry(&target.to_string_lossy())?;
+                Ok(target)
+            }
+        }
+    }
 
-        let (_is_dir, is_file) = if let Ok(meta) = fs::metadata(&old_path) {
+    /// Rename a file or folder in place. With `update_links`, documents
+    /// linking to it (or into it) are rewritten to follow, recorded in
+    /// history as `origin`. `on_collision` applies when the new name is taken.
+    pub fn rename_entry(
+        &self,
+        id: &str,
+        new_name: &str,
+        update_links: bool,
+        on_collision: CollisionPolicy,
+        origin: EditOrigin,
+    ) -> Result<MovedEntry, String> {
+        sandbox::check_name(new_name)?;
+        let old_path = self.confine_entry(id)?;
+
+        let (is_dir, is_file) = if let Ok(meta) = fs::metadata(&old_path) {
             (meta.is_dir(), meta.is_file())
         } else {
             return Err(format!("Entry not found: {}", old_path.display()));
         };
 
         let new_path = if let Some(parent) = old_path.parent() {
-            if is_file {
-                let filename = ensure_md_extension(new_name);
-                parent.join(&filename)
+            if is_file && attachments::is_attachment(&old_path) {
+                parent.join(attachments::rename_target(new_name, &old_path))
+            } else if is_file {
+                parent.join(self.document_types(&old_path).file_name(new_name, Some(&old_path)))
             } else {
                 parent.join(new_name)
             }
         } else {
             return Err("Cannot rename root entry".into());
         };
-
-        fs::rename(&old_path, &new_path).map_err(|e| {
-            format!(
-                "Failed to rename {} to {}: {}",
-                old_path.display(),
-                new_path.display(),
-                e
-            )
-        })?;
+        let new_path = self.resolve_collision(new_path, Some(&old_path), is_dir, on_collision)?;
+
+        let linking = update_links.then(|| self.linking_documents(&old_path));
+        rename_new(&old_path, &new_path)?;
+        let _ = self.history.rename_path(&old_path, &new_path);
+        self.index.remove_prefix(&old_path);
+        self.reindex(&new_path);
+        let (updated_links, link_failures) = match linking {
+            Some((before, sources)) => self.follow_move(&before, &sources, &old_path, &new_path, origin),
+            None => (Vec::new(), Vec::new()),
+        };
 
         let meta = fs::metadata(&new_path)
             .map_err(|e| format!("Failed to read metadata: {}", e))?;
         let (created_at, updated_at) = Self::metadata_to_timestamps(&meta);
+        let kind = Self::entry_kind(&meta, &new_path);
+        let file_name = file_name(&new_path);
 
-        Ok(TreeNode {
+        let entry = TreeNode {
             id: new_path.to_string_lossy().to_string(),
             parent_id: new_path
                 .parent()
                 .map(|p| p.to_string_lossy().to_string()),
-            name: new_name.to_string(),
-            kind: if meta.is_dir() {
-                TreeNodeKind::Folder
-            } else {
-                TreeNodeKind::Document
+            name: match kind {
+                TreeNodeKind::Document => documents::display_name(&file_name),
+                _ => file_name,
             },
+            kind,
             content: None,
             created_at,
             updated_at,
-        })
+            revision: Self::document_revision(&meta),
+            metadata: if meta.is_file() {
+                front_matter::read_metadata(&new_path)
+            } else {
+                None
+            },
+        };
+        Ok(MovedEntry { entry, updated_links, link_failures })
     }
 
+    /// Move an entry into the trash of its workspace root (see `trash.rs`).
+    /// Returns false if nothing exists at `id`.
     pub fn delete_entry(&self, id: &str) -> Result<bool, String> {
-        let path = PathBuf::from(id);
+        let path = self.confine_entry(id)?;
 
         let meta = match fs::metadata(&path) {
             Ok(m) => m,
             Err(_) => return Ok(false),
         };
 
-        if meta.is_dir() {
-            fs::remove_dir_all(&path)
-                .map_err(|e| format!("Failed to remove directory: {}", e))?;
-        } else {
-            fs::remove_file(&path)
-                .map_err(|e| format!("Failed to remove file: {}", e))?;
+        if self.is_registered_root(&path) {
+            return Err("Cannot delete a workspace root; remove it from the workspace list instead".into());
         }
+        let root = self
+            .root_for(&path)
+            .ok_or_else(|| format!("Not inside a workspace root: {}", path.display()))?;
+
+        let kind = Self::entry_kind(&meta, &path);
+        self.purge_expired_trash(&root);
+        trash::move_to_trash(&root, &path, kind, Self::fmt_timestamp(SystemTime::now()))?;
+        self.index.remove_prefix(&path);
 
         Ok(true)
     }
 
-    /// Move an entry to a new parent directory. Uses `fs::rename`, which only works
-    /// within the same volume/filesystem. Cross-volume moves will surface the OS error.
-    pub fn move_entry(&self, id: &str, new_parent_id: &str) -> Result<TreeNode, String> {
-        let old_path = PathBuf::from(id);
+    /// The registered root containing `path` (the innermost one if roots are nested).
+    fn root_for(&self, path: &Path) -> Option<PathBuf> {
+        self.workspaces()
+            .into_iter()
+            .map(|(_, root)| root)
+            .filter(|root| path.starts_with(root))
+            .max_by_key(|root| root.components().count())
+    }
 
-        let is_dir = fs::metadata(&old_path)
-            .map(|m| m.is_dir())
-            .unwrap_or(false);
+    /// Document types of the workspace containing `path`; the global setting
+    /// outside of any root.
+    fn document_types(&self, path: &Path) -> DocumentTypes {
+        let root = self.root_for(path);
+        self.config
+            .read()
+            .map(|cfg| match root {
+                Some(root) => cfg.document_types_for(&root),
+                None => cfg.document_types.clone(),
+            })
+            .unwrap_or_default()
+    }
 
-        let dest_dir = PathBuf::from(new_parent_id);
+    /// Listing rules of the workspace containing `path` (of `path` itself
+    /// outside of any root).
+    fn workspace_filter(&self, path: &Path) -> WorkspaceFilter {
+        let root = self.root_for(path).unwrap_or_else(|| path.to_path_buf());
+        match self.config.read() {
+            Ok(cfg) => cfg.workspace_filter(&root),
+            Err(_) => StorageConfig::default().workspace_filter(&root),
+        }
+    }
+
+    /// Listing rules of every registered root.
+    pub fn workspace_filters(&self) -> Vec<WorkspaceFilter> {
+        self.config
+            .read()
+            .map(|cfg| cfg.workspaces.iter().map(|w| cfg.workspace_filter(&w.path)).collect())
+            .unwrap_or_default()
+    }
+
+    /// `id` checked against the registered roots (see `sandbox.rs`).
+    fn confine(&self, id: &str) -> Result<PathBuf, String> {
+        sandbox::confine(id, &self.root_paths())
+    }
+
+    /// `id` checked against the registered roots, which it must not be itself.
+    fn confine_entry(&self, id: &str) -> Result<PathBuf, String> {
+        sandbox::confine_entry(id, &self.root_paths())
+    }
+
+    /// `id` checked like `confine_entry`, which must also be an existing
+    /// document; other files (attachments, configuration) are never written.
+    fn confine_document(&self, id: &str) -> Result<PathBuf, String> {
+        let path = self.confine_entry(id)?;
+        if !path.is_file() {
+            return Err(format!("Not a file: {}", path.display()));
+        }
+        if !self.document_types(&path).is_document(&path) {
+            return Err(StorageError::PermissionDenied {
+                path: path.to_string_lossy().to_string(),
+                reason: "is not a document".to_string(),
+            }
+            .into());
+        }
+        Ok(path)
+    }
+
+    /// Trash directories to look at: the given root, or every registered root.
+    fn trash_roots(&self, root_id: Option<&str>) -> Result<Vec<PathBuf>, String> {
+        let roots = self.root_paths();
+        match root_id {
+            Some(id) => Ok(vec![sandbox::registered_root(id, &roots)?]),
+            None => Ok(roots),
+        }
+    }
+
+    fn purge_expired_trash(&self, root: &Path) {
+        let days = self.config.read().ok().and_then(|cfg| cfg.trash_retention_days);
+        if let Some(days) = days {
+            trash::purge_older_than(root, Some(trash::retention_cutoff(days)));
+        }
+    }
+
+    /// Trashed entries of one root (or all roots), newest first. Entries older
+    /// than the configured retention period are purged first.
+    pub fn list_trash(&self, root_id: Option<&str>) -> Result<Vec<TrashEntry>, String> {
+        let mut entries: Vec<TrashEntry> = Vec::new();
+        for root in self.trash_roots(root_id)? {
+            self.purge_expired_trash(&root);
+            entries.extend(trash::list(&root));
+        }
+        entries.sort_by_key(|e| std::cmp::Reverse(e.deleted_at_unix));
+        Ok(entries)
+    }
+
+    /// Find which root's trash holds `trash_id`.
+    fn locate_trash_entry(&self, root_id: Option<&str>, trash_id: &str) -> Result<PathBuf, String> {
+        sandbox::check_name(trash_id)?;
+        self.trash_roots(root_id)?
+            .into_iter()
+            .find(|root| trash::list(root).iter().any(|e| e.id == trash_id))
+            .ok_or_else(|| format!("Trash entry not found: {}", trash_id))
+    }
+
+    /// Move a trashed entry back to its original path.
+    pub fn restore_trash_entry(
+        &self,
+        root_id: Option<&str>,
+        trash_id: &str,
+    ) -> Result<TreeNode, String> {
+        let root = self.locate_trash_entry(root_id, trash_id)?;
+        let restored = trash::restore(&root, trash_id)?;
+        self.reindex(&restored);
+        let id = restored.to_string_lossy().to_string();
+        self.get_entry(&id)?
+            .ok_or_else(|| format!("Entry not found after restore: {}", id))
+    }
+
+    /// Permanently delete one trashed entry, or the whole trash of one root (or
+    /// every root) when `trash_id` is None. Returns the number of entries purged.
+    pub fn purge_trash(&self, root_id: Option<&str>, trash_id: Option<&str>) -> Result<usize, String> {
+        match trash_id {
+            Some(trash_id) => {
+                let root = self.locate_trash_entry(root_id, trash_id)?;
+                trash::purge(&root, trash_id)?;
+                Ok(1)
+            }
+            None => Ok(self
+                .trash_roots(root_id)?
+                .iter()
+                .map(|root| trash::purge_older_than(root, None))
+                .sum()),
+        }
+    }
+
+    /// Move an entry to a new parent directory. Across filesystems the entry
+    /// is copied and the original deleted (see `transfer.rs`). `update_links`
+    /// and `on_collision` work as for `rename_entry`.
+    pub fn move_entry(
+        &self,
+        id: &str,
+        new_parent_id: &str,
+        update_links: bool,
+        on_collision: CollisionPolicy,
+        origin: EditOrigin,
+    ) -> Result<MovedEntry, String> {
+        let old_path = self.confine_entry(id)?;
+
+        let dest_dir = self.confine(new_parent_id)?;
         let file_name = old_path
             .file_name()
             .ok_or_else(|| format!("Invalid source path: {}", old_path.display()))?;
-        let new_path = dest_dir.join(file_name);
-
-        fs::rename(&old_path, &new_path).map_err(|e| {
-            format!(
-                "Failed to move {} to {}: {}",
-                old_path.display(),
-                new_path.display(),
-                e
-            )
-        })?;
+        let new_path = self.resolve_collision(dest_dir.join(file_name), Some(&old_path), old_path.is_dir(), on_collision)?;
+
+        let linking = update_links.then(|| self.linking_documents(&old_path));
+        if same_entry(&old_path, &new_path) {
+            rename_new(&old_path, &new_path)?;
+        } else {
+            transfer::move_path(&old_path, &new_path)?;
+        }
+        let _ = self.history.rename_path(&old_path, &new_path);
+        self.index.remove_prefix(&old_path);
+        self.reindex(&new_path);
+        let (updated_links, link_failures) = match linking {
+            Some((before, sources)) => self.follow_move(&before, &sources, &old_path, &new_path, origin),
+            None => (Vec::new(), Vec::new()),
+        };
 
         let meta = fs::metadata(&new_path)
             .map_err(|e| format!("Failed to read metadata: {}", e))?;
         let (created_at, updated_at) = Self::metadata_to_timestamps(&meta);
         let name = new_path
             .file_name()
-            .map(|n| strip_md_suffix(&n.to_string_lossy()))
+            .map(|n| documents::display_name(&n.to_string_lossy()))
             .unwrap_or_default();
 
-        Ok(TreeNode {
+        let entry = TreeNode {
             id: new_path.to_string_lossy().to_string(),
             parent_id: Some(new_parent_id.to_string()),
             name,
-            kind: if is_dir {
-                TreeNodeKind::Folder
+            kind: Self::entry_kind(&meta, &new_path),
+            content: None,
+            created_at,
+            updated_at,
+            revision: Self::document_revision(&meta),
+            metadata: if meta.is_file() {
+                front_matter::read_metadata(&new_path)
             } else {
-                TreeNodeKind::Document
+                None
             },
+        };
+        Ok(MovedEntry { entry, updated_links, link_failures })
+    }
+
+    /// Copy a file or folder into `new_parent_id`. It keeps its name unless
+    /// that is taken there, in which case it becomes `Note (copy)`,
+    /// `Note (copy 2)`, ...
+    pub fn copy_entry(&self, id: &str, new_parent_id: &str) -> Result<TreeNode, String> {
+        let source = self.confine(id)?;
+        let dest_dir = self.confine(new_parent_id)?;
+        self.copy_into(&source, &dest_dir)
+    }
+
+    /// Copy a file or folder next to itself, as `Note (copy)`.
+    pub fn duplicate_entry(&self, id: &str) -> Result<TreeNode, String> {
+        let source = self.confine_entry(id)?;
+        let parent = source
+            .parent()
+            .ok_or_else(|| format!("Invalid source path: {}", source.display()))?;
+        self.copy_into(&source, parent)
+    }
+
+    fn copy_into(&self, source: &Path, dest_dir: &Path) -> Result<TreeNode, String> {
+        let meta = fs::metadata(source)
+            .map_err(|_| format!("Entry not found: {}", source.display()))?;
+        if !dest_dir.is_dir() {
+            return Err(format!("Not a directory: {}", dest_dir.display()));
+        }
+        let name = source
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| format!("Invalid source path: {}", source.display()))?;
+        let target = transfer::free_copy_path(dest_dir, &name, meta.is_dir());
+        transfer::copy_path(source, &target)?;
+        self.reindex(&target);
+
+        let id = target.to_string_lossy().to_string();
+        let entry = self
+            .get_entry(&id)?
+            .ok_or_else(|| format!("Entry not found after copy: {}", id))?;
+        Ok(TreeNode { content: None, ..entry })
+    }
+
+    /// Save `data` (a pasted or dropped file) in the assets folder for the
+    /// document `document_id` under a free name based on `file_name`, and
+    /// return the new entry with the markdown that links it from the document.
+    pub fn import_attachment(
+        &self,
+        document_id: &str,
+        file_name: &str,
+        data: &[u8],
+    ) -> Result<ImportedAttachment, String> {
+        let document = self.confine(document_id)?;
+        if !document.is_file() {
+            return Err(format!("Not a file: {}", document.display()));
+        }
+        let file_name = attachments::sanitize_file_name(file_name)?;
+        if !attachments::is_attachment(Path::new(&file_name)) {
+            return Err(format!(
+                "Unsupported attachment type: {} (supported: {})",
+                file_name,
+                attachments::EXTENSIONS.join(", ")
+            ));
+        }
+        let root = self
+            .root_for(&document)
+            .ok_or_else(|| format!("Not inside a workspace root: {}", document.display()))?;
+        let setting = self
+            .config
+            .read()
+            .map(|cfg| cfg.assets_folder.clone())
+            .map_err(|e| e.to_string())?;
+        let dir = attachments::assets_dir(&root, &document, &setting)?;
+        // The assets folder may be a symlink leading out of the workspace.
+        self.confine(&dir.to_string_lossy())?;
+        let path = attachments::write_unique(&dir, &file_name, data)?;
+
+        let meta = fs::metadata(&path)
+            .map_err(|e| format!("Failed to read metadata: {}", e))?;
+        let (created_at, updated_at) = Self::metadata_to_timestamps(&meta);
+        let entry = TreeNode {
+            id: path.to_string_lossy().to_string(),
+            parent_id: Some(dir.to_string_lossy().to_string()),
+            name: path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default(),
+            kind: TreeNodeKind::Attachment,
             content: None,
             created_at,
             updated_at,
+            revision: Self::document_revision(&meta),
+            metadata: None,
+        };
+        Ok(ImportedAttachment {
+            markdown: attachments::markdown_link(&document, &path),
+            entry,
         })
     }
 
-    pub fn search(&self, query: &str) -> Result<Vec<SearchResult>, String> {
-        let lower_query = query.to_lowercase();
-        let mut results: Vec<SearchResult> = Vec::new();
+    /// Recorded revisions of a document, newest first.
+    pub fn list_history(&self, id: &str) -> Result<Vec<HistoryRevision>, String> {
+        Ok(self.history.list(&self.confine(id)?))
+    }
 
-        for (_name, root_path) in self.workspaces().into_iter() {
-            if !root_path.is_dir() {
-                continue;
-            }
-            let root_id = root_path.to_string_lossy().to_string();
-            walk_for_search(&root_path, &root_id, &lower_query, &mut results)?;
-        }
+    /// Content of one recorded revision.
+    pub fn get_history_revision(&self, id: &str, revision: u32) -> Result<String, String> {
+        self.history.get(&self.confine(id)?, revision)
+    }
 
-        Ok(results)
+    /// Unified diff between two revisions, or from `from` to the file on disk
+    /// when `to` is None.
+    pub fn diff_history(&self, id: &str, from: u32, to: Option<u32>) -> Result<String, String> {
+        let path = self.confine(id)?;
+        let current = match to {
+            Some(_) => String::new(),
+            None => fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?,
+        };
+        self.history.diff(&path, from, to, &current)
     }
-}
 
-// ── Helpers ────────────────────────────────────────────────────────────────────
+    /// Write an old revision back to the document. The restore itself becomes
+    /// a new revision, so it can be undone like any other edit.
+    pub fn restore_history(&self, id: &str, revision: u32) -> Result<TreeNode, String> {
+        let path = self.confine_entry(id)?;
+        let content = self.history.get(&path, revision)?;
+        let name = path
+            .file_name()
+            .map(|n| documents::display_name(&n.to_string_lossy()))
+            .ok_or_else(|| format!("Invalid path: {}", path.display()))?;
+        self.update_document(id, &name, &content, None, CollisionPolicy::Fail, EditOrigin::Restore)
+    }
 
-/// Strip the `.md` suffix from a filename, returning the bare name.
-fn strip_md_suffix(name: &str) -> String {
-    name.strip_suffix(".md").unwrap_or(name).to_string()
-}
+    /// Front matter of a document as a JSON object (empty if it has none).
+    /// Fails if the front matter block is not valid YAML/TOML.
+    pub fn get_metadata(&self, id: &str) -> Result<Metadata, String> {
+        let path = self.confine(id)?;
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        Ok(front_matter::parse(&content)?.unwrap_or_default())
+    }
 
-/// If `name` doesn't already end with `.md`, append the extension.
-fn ensure_md_extension(name: &str) -> String {
-    if name.ends_with(".md") {
-        name.to_string()
-    } else {
-        format!("{}.md", name)
+    /// Set front matter keys of a document, removing those whose value is
+    /// null, without touching the body. The write goes through
+    /// `update_document`, so `expected_revision` and history apply as usual.
+    pub fn update_metadata(
+        &self,
+        id: &str,
+        updates: &Metadata,
+        expected_revision: Option<&str>,
+        origin: EditOrigin,
+    ) -> Result<TreeNode, String> {
+        let path = self.confine_document(id)?;
+        if let Some(expected) = expected_revision {
+            Self::check_revision(&path, expected)?;
+        }
+        let content = fs::read_to_string(&path)
+            .map_err(|e| format!("Failed to read {}: {}", path.display(), e))?;
+        let updated = front_matter::update(&content, updates)?;
+        let name = path
+            .file_name()
+            .map(|n| documents::display_name(&n.to_string_lossy()))
+            .ok_or_else(|| format!("Invalid path: {}", path.display()))?;
+        self.update_document(id, &name, &updated, expected_revision, CollisionPolicy::Fail, origin)
     }
-}
 
-fn civil_from_days(days: i64) -> (i64, u32, u32) {
-    let z = days + 719468;
-    let era = if z >= 0 { z } else { z - 146096 } / 146097;
-    let doe = (z - era * 146097) as u32;
-    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
-    let y = yoe as i64 + era * 400;
-    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
-    let mp = (5 * doy + 2) / 153;
-    let d = doy - (153 * mp + 2) / 5 + 1;
-    let m = if mp < 10 { mp + 3 } else { mp - 9 };
-    let y = y + if m <= 2 { 1 } else { 0 };
-    (y, m, d)
-}
+    /// Run a query (see `search_query.rs` for the syntax) against the
+    /// persistent index and return the page starting at `offset` (at most
+    /// `limit` results, default 50). Each result lists its first 20 matching
+    /// lines with `context_lines` (default 1) lines around each. Syntax errors
+    /// are returned as `StorageError::InvalidQuery`.
+    pub fn search(
+        &self,
+        query: &str,
+        offset: Option<usize>,
+        limit: Option<usize>,
+        context_lines: Option<usize>,
+    ) -> Result<SearchPage, String> {
+        let offset = offset.unwrap_or(0);
+        let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).min(MAX_SEARCH_LIMIT);
+        let context = context_lines.unwrap_or(1).min(MAX_CONTEXT_LINES);
+        let parsed = search_query::parse(query).map_err(|e| StorageError::InvalidQuery {
+            query: query.to_string(),
+            position: e.position,
+            reason: e.message,
+        })?;
+        let parsed = match parsed {
+            Some(q) => q,
+            None => return Ok(SearchPage { total: 0, offset, results: Vec::new() }),
+        };
+        let (total, hits) = self.index.search(&parsed, &self.workspaces(), offset, limit);
 
-fn extract_matched_line(content: &str, query: &str) -> String {
-    for line in content.lines() {
-        if line.to_lowercase().contains(query) {
-            let trimmed = line.trim();
-            if trimmed.len() > 120 {
-                let mut end = 120;
-                while !trimmed.is_char_boundary(end) {
-                    end -= 1;
+        let results = hits
+            .into_iter()
+            .filter_map(|hit| {
+                // The index may briefly lag behind the disk; skip vanished files.
+                let meta = fs::metadata(&hit.path).ok()?;
+                let content = fs::read_to_string(&hit.path).unwrap_or_default();
+                let (created_at, updated_at) = Self::metadata_to_timestamps(&meta);
+                let name = hit
+                    .path
+                    .file_name()
+                    .map(|n| documents::display_name(&n.to_string_lossy()))
+                    .unwrap_or_default();
+                let (name_match, matches, total_matches) =
+                    search_query::find_matches(&parsed, &name, &content, context, MAX_MATCHES_PER_RESULT);
+                Some(SearchResult {
+                    id: hit.path.to_string_lossy().to_string(),
+                    name,
+                    parent_id: hit.path.parent().map(|p| p.to_string_lossy().to_string()),
+                    kind: TreeNodeKind::Document,
+                    created_at,
+                    updated_at,
+                    score: hit.score,
+                    name_match,
+                    matches,
+                    total_matches,
+                })
+            })
+            .collect();
+
+        Ok(SearchPage { total, offset, results })
+    }
+
+    /// Fuzzy-match `query` against the workspace-relative paths of all
+    /// documents, favoring recently opened ones. An empty query lists recent
+    /// documents. Returns at most `limit` results (default 50), best first.
+    pub fn quick_open(&self, query: &str, limit: Option<usize>) -> Result<Vec<QuickOpenResult>, String> {
+        let limit = limit.unwrap_or(DEFAULT_SEARCH_LIMIT).min(MAX_SEARCH_LIMIT);
+        let workspaces = self.workspaces();
+        let recent = self.recent.list();
+        let recent_rank: HashMap<&Path, usize> = recent
+            .iter()
+            .enumerate()
+            .map(|(rank, p)| (Path::new(p.as_str()), rank))
+            .collect();
+        let empty_query = query.trim().is_empty();
+
+        let mut results: Vec<QuickOpenResult> = self
+            .index
+            .documents()
+            .into_iter()
+            .filter_map(|(path, root)| {
+                let rank = recent_rank.get(path.as_path()).copied();
+                if empty_query && rank.is_none() {
+                    return None;
                 }
-                return trimmed[..end].to_string();
+                let root_name = workspaces
+                    .iter()
+                    .find(|(_, p)| *p == root)
+                    .map(|(name, _)| name.clone())
+                    .unwrap_or_else(|| root.to_string_lossy().to_string());
+                let relative = path.strip_prefix(&root).unwrap_or(&path);
+                let display_path = std::iter::once(root_name)
+                    .chain(relative.iter().map(|c| c.to_string_lossy().to_string()))
+                    .collect::<Vec<_>>()
+                    .join("/");
+                let (score, positions) = quick_open::fuzzy_match(query, &display_path)?;
+                let bonus = rank
+                    .map(|r| quick_open::recency_bonus(r, recent.len()))
+                    .unwrap_or(0);
+                Some(QuickOpenResult {
+                    id: path.to_string_lossy().to_string(),
+                    name: path
+                        .file_name()
+                        .map(|n| documents::display_name(&n.to_string_lossy()))
+                        .unwrap_or_default(),
+                    root_id: root.to_string_lossy().to_string(),
+                    display_path,
+                    score: score + bonus,
+                    positions,
+                })
+            })
+            .collect();
+
+        results.sort_by(|a, b| b.score.cmp(&a.score).then_with(|| a.display_path.cmp(&b.display_path)));
+        results.truncate(limit);
+        Ok(results)
+    }
+
+    /// Remember that a document was opened, for `quick_open` ranking.
+    pub fn record_opened(&self, id: &str) -> Result<(), String> {
+        self.confine(id)?;
+        self.recent.record(id)
+    }
+
+    /// Every tag used in any workspace, with its document count.
+    pub fn list_tags(&self) -> Result<Vec<TagCount>, String> {
+        Ok(self
+            .index
+            .tag_counts()
+            .into_iter()
+            .map(|(tag, count)| TagCount { tag, count })
+            .collect())
+    }
+
+    /// Documents tagged `tag`, including tags nested below it (`tag/...`).
+    pub fn documents_for_tag(&self, tag: &str) -> Result<Vec<TreeNode>, String> {
+        let tag = tags::normalize(tag)?.to_lowercase();
+        Ok(self
+            .index
+            .tagged_documents(&tag)
+            .iter()
+            .filter_map(|path| Self::document_node(path))
+            .collect())
+    }
+
+    /// Rename tag `from` to `to` in every document using it, front matter and
+    /// inline `#tags` alike. Nested tags move along (`from/x` becomes `to/x`),
+    /// and renaming onto an existing tag merges the two. Each rewrite goes
+    /// through `update_document`, so it is recorded in history.
+    pub fn rename_tag(&self, from: &str, to: &str, origin: EditOrigin) -> Result<TagRenameResult, String> {
+        let from = tags::normalize(from)?.to_lowercase();
+        let to = tags::normalize(to)?;
+        let mut changed = Vec::new();
+        let mut failed = Vec::new();
+        for path in self.index.tagged_documents(&from) {
+            let id = path.to_string_lossy().to_string();
+            let result = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+                .and_then(|content| tags::rename_in_document(&content, &from, &to))
+                .and_then(|updated| match updated {
+                    Some(content) => {
+                        let name = path
+                            .file_name()
+                            .map(|n| documents::display_name(&n.to_string_lossy()))
+                            .unwrap_or_default();
+                        self.update_document(&id, &name, &content, None, CollisionPolicy::Fail, origin).map(|_| true)
+                    }
+                    None => Ok(false),
+                });
+            match result {
+                Ok(true) => changed.push(id),
+                Ok(false) => {}
+                Err(error) => failed.push(UpdateFailure { id, error }),
             }
-            return trimmed.to_string();
         }
+        Ok(TagRenameResult { from, to, changed, failed })
     }
-    // Fallback: first non-empty line
-    for line in content.lines() {
-        let trimmed = line.trim();
-        if !trimmed.is_empty() {
-            if trimmed.len() > 120 {
-                let mut end = 120;
-                while !trimmed.is_char_boundary(end) {
-                    end -= 1;
+
+    /// Resolver for links between the documents of all workspaces.
+    pub fn link_resolver(&self) -> Resolver {
+        Resolver::new(self.index.documents())
+    }
+
+    /// Every link to the document `id` from other documents, with its
+    /// position, ordered by linking document.
+    pub fn get_backlinks(&self, id: &str) -> Result<Vec<Backlink>, String> {
+        let target = self.confine(id)?;
+        let resolver = self.link_resolver();
+        let graph = LinkGraph::build(&resolver, &self.index.links());
+        let mut backlinks = Vec::new();
+        for source in graph.backlinks.get(&target).into_iter().flatten() {
+            // The index may briefly lag behind the disk; skip vanished files.
+            let content = match fs::read_to_string(source) {
+                Ok(c) => c,
+                Err(_) => continue,
+            };
+            let lines: Vec<&str> = content.lines().collect();
+            let name = source
+                .file_name()
+                .map(|n| documents::display_name(&n.to_string_lossy()))
+                .unwrap_or_default();
+            for link in links::parse_links(&content) {
+                if link.target.is_empty() || resolver.resolve(source, &link).as_ref() != Some(&target) {
+                    continue;
                 }
-                return trimmed[..end].to_string();
+                backlinks.push(Backlink {
+                    id: source.to_string_lossy().to_string(),
+                    name: name.clone(),
+                    line: link.line,
+                    column: link.column,
+                    text: lines.get(link.line - 1).copied().unwrap_or_default().to_string(),
+                    heading: link.heading,
+                });
             }
-            return trimmed.to_string();
         }
+        Ok(backlinks)
     }
-    "(empty)".to_string()
-}
-
-const MAX_SEARCH_RESULTS: usize = 50;
 
-fn walk_for_search(
-    dir: &Path,
-    parent_id: &str,
-    query: &str,
-    results: &mut Vec<SearchResult>,
-) -> Result<(), String> {
-    if results.len() >= MAX_SEARCH_RESULTS {
-        return Ok(());
+    /// Check the links of every document below the folder `id` (a root or a
+    /// folder inside one): missing targets, missing heading anchors and
+    /// documents nothing links to.
+    pub fn check_links(&self, id: &str) -> Result<LinkReport, String> {
+        let scope = self.confine(id)?;
+        if !scope.is_dir() {
+            return Err(format!("Not a folder: {}", scope.display()));
+        }
+        let root = self
+            .root_for(&scope)
+            .ok_or_else(|| format!("Not inside a workspace root: {}", scope.display()))?;
+        let mut filter = self.workspace_filter(&root);
+        let mut documents = search_index::collect_documents(&scope, &mut filter);
+        documents.sort();
+        let resolver = self.link_resolver();
+        let graph = LinkGraph::build(&resolver, &self.index.links());
+        Ok(link_check::check(&scope, &documents, &mut filter, &resolver, &graph))
     }
 
-    let entries = match fs::read_dir(dir) {
-        Ok(e) => e,
-        Err(_) => return Ok(()),
-    };
+    /// Link resolution as it is now, and the documents whose links may have
+    /// to follow `path` when it moves: those linking to or into it, and the
+    /// documents inside it. Taken before the entry is moved.
+    fn linking_documents(&self, path: &Path) -> (Resolver, Vec<PathBuf>) {
+        let resolver = self.link_resolver();
+        let probe = PathMove { from: path, to: path, before: &resolver, after: &resolver };
+        let sources = self
+            .index
+            .links()
+            .into_iter()
+            .filter(|(source, refs)| probe.affects(source, refs))
+            .map(|(source, _)| source)
+            .collect();
+        (resolver, sources)
+    }
 
-    for entry in entries {
-        if results.len() >= MAX_SEARCH_RESULTS {
-            return Ok(());
+    /// Rewrite the links of `sources` (paths from before the move) now that
+    /// `from` has moved to `to`. Returns the rewritten documents and the ones
+    /// that could not be updated.
+    fn follow_move(
+        &self,
+        before: &Resolver,
+        sources: &[PathBuf],
+        from: &Path,
+        to: &Path,
+        origin: EditOrigin,
+    ) -> (Vec<String>, Vec<UpdateFailure>) {
+        let after = self.link_resolver();
+        let moved = PathMove { from, to, before, after: &after };
+        let mut updated = Vec::new();
+        let mut failed = Vec::new();
+        for source in sources {
+            let path = moved.new_path(source).unwrap_or_else(|| source.clone());
+            let id = path.to_string_lossy().to_string();
+            let result = fs::read_to_string(&path)
+                .map_err(|e| format!("Failed to read {}: {}", path.display(), e))
+                .and_then(|content| match moved.rewrite(source, &content) {
+                    Some(content) => {
+                        let name = path
+                            .file_name()
+                            .map(|n| documents::display_name(&n.to_string_lossy()))
+                            .unwrap_or_default();
+                        self.update_document(&id, &name, &content, None, CollisionPolicy::Fail, origin).map(|_| true)
+                    }
+                    None => Ok(false),
+                });
+            match result {
+                Ok(true) => updated.push(id),
+                Ok(false) => {}
+                Err(error) => failed.push(UpdateFailure { id, error }),
+            }
         }
+        (updated, failed)
+    }
 
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_) => continue,
-        };
+    /// Re-read the index entries for `path` (a document or a whole folder).
+    fn reindex(&self, path: &Path) {
+        match self.root_for(path) {
+            Some(root) => self.index.index_tree(path, &mut self.workspace_filter(&root)),
+            None => self.index.remove_prefix(path),
+        }
+    }
 
-        let file_name = entry.file_name().to_string_lossy().to_string();
-        if file_name.starts_with('.') {
-            continue;
+    /// Update the search index from a batch of watcher changes, so edits made
+    /// outside the app are searchable without a rescan.
+    pub fn apply_fs_changes(&self, changes: &[FsChange]) {
+        // One set of filters for the batch, so ignore files are read once
+        let mut filters = self.workspace_filters();
+        let mut rules_changed = false;
+        for change in changes {
+            let path = Path::new(&change.id);
+            let filter = filters.iter_mut().find(|f| path.starts_with(f.root()));
+            rules_changed |= filter.as_ref().is_some_and(|f| watcher::is_rules_file(path, f.root()));
+            if let (FsChangeKind::Renamed, Some(old)) = (change.kind, &change.old_id) {
+                self.index.remove_prefix(Path::new(old));
+            }
+            match (change.kind, filter) {
+                (FsChangeKind::Removed, _) | (_, None) => self.index.remove_prefix(path),
+                (_, Some(filter)) => self.index.index_tree(path, filter),
+            }
+        }
+        // An edited ignore file can hide or reveal documents anywhere below it.
+        if rules_changed {
+            self.index.reconcile(&mut self.workspace_filters());
         }
+    }
 
-        let path = entry.path();
-        let file_type = match entry.file_type() {
-            Ok(ft) => ft,
-            Err(_) => continue,
-        };
+    /// Reconcile the index with every registered root (new, changed and
+    /// deleted files) and persist it. Run in the background at startup and
+    /// after a root is added.
+    pub fn sync_search_index(&self) {
+        self.index.reconcile(&mut self.workspace_filters());
+        let _ = self.index.save_if_dirty();
+    }
 
-        if file_type.is_dir() {
-            let child_id = path.to_string_lossy().to_string();
-            walk_for_search(&path, &child_id, query, results)?;
-        } else if file_type.is_file() {
-            let lower_name = file_name.to_lowercase();
-            let name_match = lower_name.contains(query);
+    /// Persist pending index changes, if any.
+    pub fn flush_search_index(&self) -> Result<(), String> {
+        self.index.save_if_dirty()
+    }
+}
 
-            let content = match fs::read_to_string(&path) {
-                Ok(c) => c,
-                Err(_) => continue,
-            };
+// ── Helpers ────────────────────────────────────────────────────────────────────
 
-            let content_match = !name_match && content.to_lowercase().contains(query);
-
-            if name_match || content_match {
-                if file_name.ends_with(".md") {
-                    let meta = match entry.metadata() {
-                        Ok(m) => m,
-                        Err(_) => continue,
-                    };
-                    let (created_at, updated_at) =
-                        FilesystemStorage::metadata_to_timestamps(&meta);
-                    let name = strip_md_suffix(&file_name);
-                    let matched_line = if name_match && !content_match {
-                        extract_matched_line(&content, query)
-                    } else {
-                        extract_matched_line(&content, query)
-                    };
-                    results.push(SearchResult {
-                        id: path.to_string_lossy().to_string(),
-                        parent_id: Some(parent_id.to_string()),
-                        name,
-                        kind: TreeNodeKind::Document,
-                        created_at,
-                        updated_at,
-                        matched_line,
-                    });
-                }
-            }
-        }
+/// Last component of `path`, or "" if it has none.
+fn file_name(path: &Path) -> String {
+    path.file_name().map(|n| n.to_string_lossy().to_string()).unwrap_or_default()
+}
+
+/// Whether `a` and `b` are the same entry, e.g. on a case-insensitive filesystem.
+fn same_entry(a: &Path, b: &Path) -> bool {
+    match (fs::canonicalize(a), fs::canonicalize(b)) {
+        (Ok(a), Ok(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Rename `from` to the name `resolve_collision` picked, failing with
+/// `StorageError::AlreadyExists` if something has taken it since. Renaming
+/// an entry to itself (e.g. only changing case) goes through as is.
+fn rename_new(from: &Path, to: &Path) -> Result<(), String> {
+    let renamed = if same_entry(from, to) {
+        fs::rename(from, to)
+    } else {
+        transfer::rename_no_replace(from, to)
+    };
+    renamed.map_err(|e| match e.kind() {
+        ErrorKind::AlreadyExists => already_exists(to),
+        _ => format!("Failed to rename {} to {}: {}", from.display(), to.display(), e),
+    })
+}
+
+fn already_exists(path: &Path) -> String {
+    StorageError::AlreadyExists {
+        path: path.to_string_lossy().to_string(),
     }
+    .into()
+}
 
-    Ok(())
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = (z - era * 146097) as u32;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let y = yoe as i64 + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let y = y + if m <= 2 { 1 } else { 0 };
+    (y, m, d)
 }
+
+const DEFAULT_SEARCH_LIMIT: usize = 50;
+const MAX_SEARCH_LIMIT: usize = 500;
+const MAX_CONTEXT_LINES: usize = 10;
+/// Matching lines returned per search result; `total_matches` counts all.
+const MAX_MATCHES_PER_RESULT: usize = 20;
@@ -0,0 +1,292 @@
+//! Front matter at the top of a document: YAML between `---` lines or TOML
+//! between `+++` lines.
+//!
+//! ```text
+//! ---
+//! title: Onboarding
+//! tags: [guide, team]
+//! status: draft
+//! ---
+//! # Onboarding
+//! ```
+//!
+//! Metadata is exposed as a JSON object so the tree, search and MCP clients
+//! see the same shape regardless of the source format. TOML dates become
+//! strings. Updating keys re-serializes the block, so comments inside the
+//! front matter are not kept; the body below it is never touched.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::Path;
+
+use serde_json::{Map, Number, Value};
+
+/// Parsed front matter keys.
+pub type Metadata = Map<String, Value>;
+
+/// Front matter longer than this is not read when listing directories.
+const MAX_HEAD_BYTES: usize = 64 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Yaml,
+    Toml,
+}
+
+impl Format {
+    fn fence(self) -> &'static str {
+        match self {
+            Format::Yaml => "---",
+            Format::Toml => "+++",
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Format::Yaml => "YAML",
+            Format::Toml => "TOML",
+        }
+    }
+}
+
+/// A document split at its front matter.
+pub struct FrontMatter<'a> {
+    pub format: Format,
+    /// Text between the fences.
+    pub raw: &'a str,
+    /// Everything after the closing fence line.
+    pub body: &'a str,
+}
+
+/// Split off the front matter block, if the document starts with one. A
+/// block that is never closed is treated as ordinary content.
+pub fn split(content: &str) -> Option<FrontMatter<'_>> {
+    let text = content.strip_prefix('\u{feff}').unwrap_or(content);
+    let first_end = text.find('\n')?;
+    let format = match text[..first_end].trim_end() {
+        "---" => Format::Yaml,
+        "+++" => Format::Toml,
+        _ => return None,
+    };
+    let raw_start = first_end + 1;
+    let mut pos = raw_start;
+    while pos <= text.len() {
+        let line_end = text[pos..].find('\n').map(|i| pos + i);
+        let line = &text[pos..line_end.unwrap_or(text.len())];
+        let closing = line.trim_end();
+        if closing == format.fence() || (format == Format::Yaml && closing == "...") {
+            let body_start = line_end.map(|i| i + 1).unwrap_or(text.len());
+            return Some(FrontMatter {
+                format,
+                raw: &text[raw_start..pos],
+                body: &text[body_start..],
+            });
+        }
+        pos = line_end? + 1;
+    }
+    None
+}
+
+/// Content without its front matter block.
+pub fn body(content: &str) -> &str {
+    split(content).map(|fm| fm.body).unwrap_or(content)
+}
+
+/// Parse the front matter of `content`. Ok(None) when there is none; an
+/// error when the block is not valid YAML/TOML or not a key/value mapping.
+pub fn parse(content: &str) -> Result<Option<Metadata>, String> {
+    match split(content) {
+        Some(fm) => parse_block(fm.format, fm.raw).map(Some),
+        None => Ok(None),
+    }
+}
+
+/// Front matter of `content`, ignoring blocks that don't parse.
+pub fn metadata(content: &str) -> Option<Metadata> {
+    parse(content).ok().flatten()
+}
+
+/// Front matter of the file at `path`, reading only as far as the closing
+/// fence so directory listings stay cheap.
+pub fn read_metadata(path: &Path) -> Option<Metadata> {
+    let mut reader = BufReader::new(File::open(path).ok()?);
+    let mut head = String::new();
+    reader.read_line(&mut head).ok()?;
+    let fence = match head.trim_start_matches('\u{feff}').trim_end() {
+        "---" => "---",
+        "+++" => "+++",
+        _ => return None,
+    };
+    loop {
+        let start = head.len();
+        if reader.read_line(&mut head).ok()? == 0 || head.len() > MAX_HEAD_BYTES {
+            return None;
+        }
+        let line = head[start..].trim_end();
+        if line == fence || (fence == "---" && line == "...") {
+            return metadata(&head);
+        }
+    }
+}
+
+/// `title` from the front matter, if it is a non-empty string.
+pub fn title(meta: &Metadata) -> Option<&str> {
+    meta.get("title")
+        .and_then(Value::as_str)
+        .map(str::trim)
+        .filter(|t| !t.is_empty())
+}
+
+/// Values of `key` as strings: a list, or a single comma-separated string.
+pub fn string_list(meta: &Metadata, key: &str) -> Vec<String> {
+    let scalar = |v: &Value| match v {
+        Value::String(s) => Some(s.trim().to_string()),
+        Value::Number(n) => Some(n.to_string()),
+        _ => None,
+    };
+    let values: Vec<String> = match meta.get(key) {
+        Some(Value::Array(items)) => items.iter().filter_map(scalar).collect(),
+        Some(Value::String(s)) => s.split(',').map(|t| t.trim().to_string()).collect(),
+        Some(other) => scalar(other).into_iter().collect(),
+        None => Vec::new(),
+    };
+    values.into_iter().filter(|v| !v.is_empty()).collect()
+}
+
+/// Set or remove front matter keys and return the new document. A `null`
+/// value removes the key. Documents without front matter get a YAML block;
+/// the block is dropped when its last key is removed.
+pub fn update(content: &str, updates: &Metadata) -> Result<String, String> {
+    let (format, raw, body) = match split(content) {
+        Some(fm) => (fm.format, fm.raw, fm.body),
+        None => (Format::Yaml, "", content),
+    };
+    let block = match format {
+        Format::Yaml => update_yaml(raw, updates)?,
+        Format::Toml => update_toml(raw, updates)?,
+    };
+    if block.trim().is_empty() {
+        return Ok(body.to_string());
+    }
+    let fence = format.fence();
+    let newline = if block.ends_with('\n') { "" } else { "\n" };
+    Ok(format!("{}\n{}{}{}\n{}", fence, block, newline, fence, body))
+}
+
+fn parse_block(format: Format, raw: &str) -> Result<Metadata, String> {
+    let value = match format {
+        Format::Yaml => {
+            if raw.trim().is_empty() {
+                return Ok(Metadata::new());
+            }
+            let yaml: serde_yaml::Value = serde_yaml::from_str(raw)
+                .map_err(|e| format!("Invalid YAML front matter: {}", e))?;
+            yaml_to_json(yaml)
+        }
+        Format::Toml => {
+            let table: toml::Table = raw
+                .parse()
+                .map_err(|e| format!("Invalid TOML front matter: {}", e))?;
+            toml_to_json(toml::Value::Table(table))
+        }
+    };
+    match value {
+        Value::Object(map) => Ok(map),
+        Value::Null => Ok(Metadata::new()),
+        _ => Err(format!("{} front matter must be a mapping of keys to values", format.name())),
+    }
+}
+
+fn update_yaml(raw: &str, updates: &Metadata) -> Result<String, String> {
+    let mut mapping = if raw.trim().is_empty() {
+        serde_yaml::Mapping::new()
+    } else {
+        match serde_yaml::from_str(raw) {
+            Ok(serde_yaml::Value::Mapping(m)) => m,
+            Ok(_) => return Err("YAML front matter must be a mapping of keys to values".into()),
+            Err(e) => return Err(format!("Invalid YAML front matter: {}", e)),
+        }
+    };
+    for (key, value) in updates {
+        let key = serde_yaml::Value::String(key.clone());
+        if value.is_null() {
+            mapping.shift_remove(&key);
+        } else {
+            let value = serde_yaml::to_value(value)
+                .map_err(|e| format!("Failed to convert metadata value: {}", e))?;
+            mapping.insert(key, value);
+        }
+    }
+    if mapping.is_empty() {
+        return Ok(String::new());
+    }
+    serde_yaml::to_string(&mapping).map_err(|e| format!("Failed to write YAML front matter: {}", e))
+}
+
+fn update_toml(raw: &str, updates: &Metadata) -> Result<String, String> {
+    let mut table: toml::Table = raw
+        .parse()
+        .map_err(|e| format!("Invalid TOML front matter: {}", e))?;
+    for (key, value) in updates {
+        if value.is_null() {
+            table.remove(key);
+        } else {
+            let value = toml::Value::try_from(value)
+                .map_err(|e| format!("Cannot store {} in TOML front matter: {}", key, e))?;
+            table.insert(key.clone(), value);
+        }
+    }
+    toml::to_string(&table).map_err(|e| format!("Failed to write TOML front matter: {}", e))
+}
+
+fn yaml_to_json(value: serde_yaml::Value) -> Value {
+    use serde_yaml::Value as Yaml;
+    match value {
+        Yaml::Null => Value::Null,
+        Yaml::Bool(b) => Value::Bool(b),
+        Yaml::Number(n) => {
+            if let Some(i) = n.as_i64() {
+                Value::from(i)
+            } else if let Some(u) = n.as_u64() {
+                Value::from(u)
+            } else {
+                n.as_f64().and_then(Number::from_f64).map(Value::Number).unwrap_or(Value::Null)
+            }
+        }
+        Yaml::String(s) => Value::String(s),
+        Yaml::Sequence(items) => Value::Array(items.into_iter().map(yaml_to_json).collect()),
+        Yaml::Mapping(mapping) => Value::Object(
+            mapping
+                .into_iter()
+                .filter_map(|(k, v)| {
+                    // JSON keys are strings; scalar keys like `2024:` are kept as text.
+                    let key = match yaml_to_json(k) {
+                        Value::String(s) => s,
+                        Value::Number(n) => n.to_string(),
+                        Value::Bool(b) => b.to_string(),
+                        _ => return None,
+                    };
+                    Some((key, yaml_to_json(v)))
+                })
+                .collect(),
+        ),
+        Yaml::Tagged(tagged) => yaml_to_json(tagged.value),
+    }
+}
+
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Number::from_f64(f).map(Value::Number).unwrap_or(Value::Null),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(d) => Value::String(d.to_string()),
+        toml::Value::Array(items) => Value::Array(items.into_iter().map(toml_to_json).collect()),
+        toml::Value::Table(table) => Value::Object(
+            table
+                .into_iter()
+                .map(|(k, v)| (k, toml_to_json(v)))
+                .collect(),
+        ),
+    }
+}
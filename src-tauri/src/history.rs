@@ -0,0 +1,285 @@
+//! Local version history for documents.
+//!
+//! Every successful `update_document` records a gzip-compressed snapshot in
+//! the app data directory. Snapshots belong to a document id rather than to a
+//! path, so history survives renames and moves as long as they go through the
+//! storage layer:
+//!
+//! ```text
+//! <app data>/history/index.json              path → document id
+//! <app data>/history/<doc id>/revisions.json Vec<HistoryRevision>
+//! <app data>/history/<doc id>/<n>.md.gz      snapshot of revision n
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use similar::TextDiff;
+
+use crate::atomic::write_atomic;
+
+/// Oldest revisions beyond this count are pruned per document.
+const MAX_REVISIONS: usize = 200;
+
+/// Who produced a revision, kept so agent edits can be audited.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum EditOrigin {
+    /// Content that was on disk before the first tracked edit.
+    Baseline,
+    App,
+    Mcp,
+    Restore,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryRevision {
+    pub revision: u32,
+    pub created_at_unix: u64,
+    pub size: usize,
+    pub origin: EditOrigin,
+    /// Path the document had when this revision was recorded.
+    pub path: String,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct HistoryIndex {
+    paths: HashMap<String, String>,
+}
+
+pub struct HistoryStore {
+    dir: PathBuf,
+    index: Mutex<HistoryIndex>,
+}
+
+impl HistoryStore {
+    pub fn new(dir: PathBuf) -> Self {
+        let index = fs::read_to_string(dir.join("index.json"))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        HistoryStore {
+            dir,
+            index: Mutex::new(index),
+        }
+    }
+
+    fn save_index(&self, index: &HistoryIndex) -> Result<(), String> {
+        fs::create_dir_all(&self.dir)
+            .map_err(|e| format!("Failed to create history directory: {}", e))?;
+        let json = serde_json::to_string(index)
+            .map_err(|e| format!("Failed to serialize history index: {}", e))?;
+        write_atomic(&self.dir.join("index.json"), json.as_bytes())
+            .map_err(|e| format!("Failed to write history index: {}", e))
+    }
+
+    fn doc_dir(&self, doc_id: &str) -> PathBuf {
+        self.dir.join(doc_id)
+    }
+
+    fn doc_id(&self, path: &Path) -> Option<String> {
+        let index = self.index.lock().ok()?;
+        index.paths.get(&path_key(path)).cloned()
+    }
+
+    fn load_revisions(&self, doc_id: &str) -> Vec<HistoryRevision> {
+        fs::read_to_string(self.doc_dir(doc_id).join("revisions.json"))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    fn save_revisions(&self, doc_id: &str, revisions: &[HistoryRevision]) -> Result<(), String> {
+        let json = serde_json::to_string_pretty(revisions)
+            .map_err(|e| format!("Failed to serialize history: {}", e))?;
+        write_atomic(&self.doc_dir(doc_id).join("revisions.json"), json.as_bytes())
+            .map_err(|e| format!("Failed to write history: {}", e))
+    }
+
+    /// Record `content` as the newest revision of the document at `path`.
+    /// `previous` is the content that was on disk before the write; it is
+    /// stored as a baseline revision the first time a document is tracked so
+    /// the pre-edit state can always be restored. Identical consecutive
+    /// snapshots are skipped.
+    pub fn record(
+        &self,
+        path: &Path,
+        previous: Option<&str>,
+        content: &str,
+        origin: EditOrigin,
+    ) -> Result<(), String> {
+        // Held for the whole write so concurrent saves can't interleave revisions.
+        let mut index = self.index.lock().map_err(|e| e.to_string())?;
+        let key = path_key(path);
+        let doc_id = match index.paths.get(&key) {
+            Some(id) => id.clone(),
+            None => {
+                let id = new_doc_id(&self.dir, &index);
+                index.paths.insert(key, id.clone());
+                self.save_index(&index)?;
+                id
+            }
+        };
+
+        let dir = self.doc_dir(&doc_id);
+        fs::create_dir_all(&dir)
+            .map_err(|e| format!("Failed to create history directory: {}", e))?;
+        let mut revisions = self.load_revisions(&doc_id);
+
+        if revisions.is_empty() {
+            if let Some(previous) = previous.filter(|p| *p != content) {
+                self.push_snapshot(&doc_id, &mut revisions, path, previous, EditOrigin::Baseline)?;
+            }
+        } else if let Some(last) = revisions.last() {
+            if self.read_snapshot(&doc_id, last.revision).ok().as_deref() == Some(content) {
+                return Ok(());
+            }
+        }
+        self.push_snapshot(&doc_id, &mut revisions, path, content, origin)?;
+
+        while revisions.len() > MAX_REVISIONS {
+            let pruned = revisions.remove(0);
+            let _ = fs::remove_file(snapshot_path(&dir, pruned.revision));
+        }
+        self.save_revisions(&doc_id, &revisions)
+    }
+
+    fn push_snapshot(
+        &self,
+        doc_id: &str,
+        revisions: &mut Vec<HistoryRevision>,
+        path: &Path,
+        content: &str,
+        origin: EditOrigin,
+    ) -> Result<(), String> {
+        let revision = revisions.last().map(|r| r.revision + 1).unwrap_or(1);
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::default());
+        encoder
+            .write_all(content.as_bytes())
+            .and_then(|_| encoder.finish())
+            .and_then(|bytes| write_atomic(&snapshot_path(&self.doc_dir(doc_id), revision), &bytes))
+            .map_err(|e| format!("Failed to write history snapshot: {}", e))?;
+        revisions.push(HistoryRevision {
+            revision,
+            created_at_unix: now_unix(),
+            size: content.len(),
+            origin,
+            path: path.to_string_lossy().to_string(),
+        });
+        Ok(())
+    }
+
+    fn read_snapshot(&self, doc_id: &str, revision: u32) -> Result<String, String> {
+        let bytes = fs::read(snapshot_path(&self.doc_dir(doc_id), revision))
+            .map_err(|_| format!("Revision {} not found", revision))?;
+        let mut content = String::new();
+        GzDecoder::new(bytes.as_slice())
+            .read_to_string(&mut content)
+            .map_err(|e| format!("Failed to read revision {}: {}", revision, e))?;
+        Ok(content)
+    }
+
+    /// Revisions of the document at `path`, newest first.
+    pub fn list(&self, path: &Path) -> Vec<HistoryRevision> {
+        let mut revisions = match self.doc_id(path) {
+            Some(id) => self.load_revisions(&id),
+            None => Vec::new(),
+        };
+        revisions.reverse();
+        revisions
+    }
+
+    /// Content of one revision of the document at `path`.
+    pub fn get(&self, path: &Path, revision: u32) -> Result<String, String> {
+        let doc_id = self
+            .doc_id(path)
+            .ok_or_else(|| format!("No history for {}", path.display()))?;
+        self.read_snapshot(&doc_id, revision)
+    }
+
+    /// Unified diff from revision `from` to revision `to`, or to `current`
+    /// (the content on disk) when `to` is None.
+    pub fn diff(
+        &self,
+        path: &Path,
+        from: u32,
+        to: Option<u32>,
+        current: &str,
+    ) -> Result<String, String> {
+        let old = self.get(path, from)?;
+        let (new, new_label) = match to {
+            Some(rev) => (self.get(path, rev)?, format!("revision {}", rev)),
+            None => (current.to_string(), "current".to_string()),
+        };
+        Ok(TextDiff::from_lines(&old, &new)
+            .unified_diff()
+            .context_radius(3)
+            .header(&format!("revision {}", from), &new_label)
+            .to_string())
+    }
+
+    /// Keep history attached when an entry moves from `old` to `new`. For
+    /// folders every tracked document below `old` is re-keyed.
+    pub fn rename_path(&self, old: &Path, new: &Path) -> Result<(), String> {
+        let mut index = self.index.lock().map_err(|e| e.to_string())?;
+        let moved: Vec<(String, String)> = index
+            .paths
+            .keys()
+            .filter_map(|key| {
+                let rel = Path::new(key).strip_prefix(old).ok()?;
+                let target = if rel.as_os_str().is_empty() {
+                    new.to_path_buf()
+                } else {
+                    new.join(rel)
+                };
+                Some((key.clone(), path_key(&target)))
+            })
+            .collect();
+        if moved.is_empty() {
+            return Ok(());
+        }
+        for (key, target) in moved {
+            if let Some(id) = index.paths.remove(&key) {
+                index.paths.insert(target, id);
+            }
+        }
+        self.save_index(&index)
+    }
+}
+
+fn path_key(path: &Path) -> String {
+    path.to_string_lossy().to_string()
+}
+
+fn snapshot_path(doc_dir: &Path, revision: u32) -> PathBuf {
+    doc_dir.join(format!("{}.md.gz", revision))
+}
+
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+fn new_doc_id(dir: &Path, index: &HistoryIndex) -> String {
+    let mut nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    loop {
+        let id = format!("{:x}", nanos);
+        if !dir.join(&id).exists() && !index.paths.values().any(|v| *v == id) {
+            return id;
+        }
+        nanos += 1;
+    }
+}
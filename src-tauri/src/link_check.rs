@@ -0,0 +1,165 @@
+//! Workspace link checking.
+//!
+//! Every document below a folder is parsed for wiki links, relative markdown
+//! links and images (see `links.rs`). A link is reported when its target
+//! doesn't exist, or when it names a heading (`#section`, `[[note#Heading]]`)
+//! that the target document doesn't have. Documents that no other document
+//! links to, from any workspace, are listed as orphans.
+//!
+//! Wiki links naming a file that isn't a document (`![[diagram.png]]`) are looked up
+//! next to the linking document, at that path inside the workspace root, and
+//! finally by file name anywhere in the root.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::links::{self, Link, LinkGraph, LinkKind, Resolver};
+use crate::search_index::collect_files;
+use crate::storage::{LinkProblem, LinkProblemKind, LinkReport};
+use crate::workspace_filter::WorkspaceFilter;
+
+/// Check the links of `documents` (the documents below `scope`, which lies in
+/// the workspace `filter` is for). `graph` covers all workspaces, so links
+/// from outside `scope` still count when looking for orphans.
+pub fn check(
+    scope: &Path,
+    documents: &[PathBuf],
+    filter: &mut WorkspaceFilter,
+    resolver: &Resolver,
+    graph: &LinkGraph,
+) -> LinkReport {
+    let mut checker = Checker {
+        filter,
+        resolver,
+        anchors: HashMap::new(),
+        file_names: None,
+    };
+    let mut links_checked = 0;
+    let mut problems = Vec::new();
+    for source in documents {
+        // The index may briefly lag behind the disk; skip vanished files.
+        let content = match fs::read_to_string(source) {
+            Ok(c) => c,
+            Err(_) => continue,
+        };
+        checker.anchors.insert(source.clone(), links::heading_anchors(&content));
+        for link in links::parse_links(&content) {
+            links_checked += 1;
+            if let Some(kind) = checker.problem(source, &link) {
+                problems.push(LinkProblem {
+                    id: source.to_string_lossy().to_string(),
+                    line: link.line,
+                    column: link.column,
+                    kind,
+                    link: content[link.start..link.end].to_string(),
+                    embed: link.embed,
+                });
+            }
+        }
+    }
+    let orphans = documents
+        .iter()
+        .filter(|d| graph.backlinks.get(*d).is_none_or(Vec::is_empty))
+        .map(|d| d.to_string_lossy().to_string())
+        .collect();
+    LinkReport {
+        id: scope.to_string_lossy().to_string(),
+        documents: documents.len(),
+        links: links_checked,
+        problems,
+        orphans,
+    }
+}
+
+struct Checker<'a> {
+    filter: &'a mut WorkspaceFilter,
+    resolver: &'a Resolver,
+    /// Heading anchors of documents read so far.
+    anchors: HashMap<PathBuf, HashSet<String>>,
+    /// Lowercased names of every shown file in the root, read on first use.
+    file_names: Option<HashSet<String>>,
+}
+
+impl Checker<'_> {
+    fn problem(&mut self, source: &Path, link: &Link) -> Option<LinkProblemKind> {
+        match link.kind {
+            LinkKind::Wiki => {
+                let target = match self.resolver.resolve(source, link) {
+                    Some(t) => t,
+                    None if self.is_attachment(&link.target) && self.wiki_file_exists(source, &link.target) => {
+                        return None
+                    }
+                    None => return Some(LinkProblemKind::MissingTarget),
+                };
+                // `[[note#^block]]` points at a block id, not a heading.
+                let heading = link.heading.as_deref().filter(|h| !h.is_empty() && !h.starts_with('^'));
+                match heading {
+                    Some(h) if !self.has_anchor(&target, &links::slugify(h)) => {
+                        Some(LinkProblemKind::MissingAnchor)
+                    }
+                    _ => None,
+                }
+            }
+            LinkKind::Markdown => {
+                let target = if link.target.is_empty() {
+                    source.to_path_buf()
+                } else {
+                    let dir = source.parent().unwrap_or(Path::new(""));
+                    links::normalize(&dir.join(&link.target))
+                };
+                if !target.exists() {
+                    return Some(LinkProblemKind::MissingTarget);
+                }
+                let anchor = link.heading.as_deref().map(links::percent_decode).filter(|a| !a.is_empty());
+                match anchor {
+                    Some(a) if self.filter.is_document(&target) && target.is_file() => {
+                        let found = self.has_anchor(&target, &a) || self.has_anchor(&target, &links::slugify(&a));
+                        if found {
+                            None
+                        } else {
+                            Some(LinkProblemKind::MissingAnchor)
+                        }
+                    }
+                    _ => None,
+                }
+            }
+        }
+    }
+
+    /// A wiki target naming a file other than a document (`diagram.png`).
+    fn is_attachment(&self, target: &str) -> bool {
+        let path = Path::new(target);
+        path.extension().is_some() && !self.filter.is_document(path)
+    }
+
+    fn has_anchor(&mut self, document: &Path, anchor: &str) -> bool {
+        self.anchors
+            .entry(document.to_path_buf())
+            .or_insert_with(|| {
+                fs::read_to_string(document)
+                    .map(|c| links::heading_anchors(&c))
+                    .unwrap_or_default()
+            })
+            .contains(anchor)
+    }
+
+    fn wiki_file_exists(&mut self, source: &Path, target: &str) -> bool {
+        let dir = source.parent().unwrap_or(Path::new(""));
+        let root = self.filter.root().to_path_buf();
+        if dir.join(target).exists() || root.join(target).exists() {
+            return true;
+        }
+        let filter = &mut *self.filter;
+        let names = self.file_names.get_or_insert_with(|| {
+            collect_files(&root, filter)
+                .iter()
+                .filter_map(|p| p.file_name())
+                .map(|n| n.to_string_lossy().to_lowercase())
+                .collect()
+        });
+        let name = target.rsplit('/').next().unwrap_or(target);
+        names.contains(&name.to_lowercase())
+    }
+}
+
@@ -0,0 +1,606 @@
+//! Links between documents: `[[wiki links]]` and relative markdown links.
+//!
+//! Wiki links name a document rather than a path:
+//!
+//! ```text
+//! [[Note Name]]                   any document named `Note Name.md` (or `.txt`, ...)
+//! [[folder/note#Heading|alias]]   a document whose path ends in folder/note
+//! [[#Heading]]                    a heading in the same document
+//! ```
+//!
+//! Names are matched case-insensitively across every workspace root. When
+//! several documents match, the one in the same root and closest to the
+//! linking document wins. Relative markdown links (`[text](../note.md)`)
+//! are resolved against the linking document's folder.
+//!
+//! The search index stores each document's outgoing links as `LinkRef`s;
+//! they are resolved when a `LinkGraph` is built, so creating or renaming a
+//! document immediately changes where links point. `PathMove` rewrites the
+//! links that would otherwise break when a file or folder is renamed or moved.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Component, Path, PathBuf};
+use std::sync::OnceLock;
+
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+use crate::search_index::{heading_text, line_kinds, LineKind};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkKind {
+    Wiki,
+    Markdown,
+}
+
+/// A link as written in a document.
+#[derive(Debug, Clone)]
+pub struct Link {
+    pub kind: LinkKind,
+    /// Wiki: the name or path (`folder/note`). Markdown: the destination
+    /// without its `#anchor`, percent-decoded. Empty for same-document links.
+    pub target: String,
+    /// Heading (wiki) or anchor (markdown) after the `#`.
+    pub heading: Option<String>,
+    /// Wiki alias after the `|`, or the text of a markdown link.
+    pub alias: Option<String>,
+    /// `![[...]]` / `![...](...)`.
+    pub embed: bool,
+    /// 1-based line and column (in characters) of the opening bracket.
+    pub line: usize,
+    pub column: usize,
+    /// Byte range of the whole link in the document.
+    pub start: usize,
+    pub end: usize,
+    /// Byte range of the target as written: the wiki name or path before any
+    /// `#`/`|`, or the whole markdown destination including its anchor.
+    pub target_range: (usize, usize),
+}
+
+/// An outgoing link as stored in the search index.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", content = "target", rename_all = "lowercase")]
+pub enum LinkRef {
+    /// A wiki link target, resolved by name.
+    Wiki(String),
+    /// Absolute path of a relative markdown link or image. Only links to
+    /// documents take part in the link graph; the rest are kept so renames
+    /// can find references to images and other files.
+    File(String),
+}
+
+fn wiki_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| Regex::new(r"(!?)\[\[([^\[\]\n]+?)\]\]").unwrap())
+}
+
+fn markdown_re() -> &'static Regex {
+    static RE: OnceLock<Regex> = OnceLock::new();
+    RE.get_or_init(|| {
+        Regex::new(r#"(!?)\[([^\]\n]*)\]\(\s*(?:<([^>\n]*)>|([^)\s]+))(?:\s+"[^"\n]*")?\s*\)"#)
+            .unwrap()
+    })
+}
+
+/// Every wiki and relative markdown link in `content`, in document order.
+/// Links inside fenced code, inline code spans and front matter are ignored,
+/// as are markdown links to URLs and absolute paths.
+pub fn parse_links(content: &str) -> Vec<Link> {
+    let mut links = Vec::new();
+    let mut offset = 0;
+    for (index, (line, kind)) in content.split_inclusive('\n').zip(line_kinds(content)).enumerate() {
+        let line_start = offset;
+        offset += line.len();
+        if !matches!(kind, LineKind::Body | LineKind::Heading | LineKind::Title) {
+            continue;
+        }
+        let code = code_spans(line);
+        let in_code = |pos: usize| code.iter().any(|(s, e)| pos >= *s && pos < *e);
+        let mut found: Vec<Link> = Vec::new();
+
+        for caps in wiki_re().captures_iter(line) {
+            let whole = caps.get(0).unwrap();
+            if in_code(whole.start()) {
+                continue;
+            }
+            let inner = &caps[2];
+            let inner_start = line_start + caps.get(2).unwrap().start();
+            let target_len = inner.find(['#', '|']).unwrap_or(inner.len());
+            let (target, alias) = match inner.split_once('|') {
+                Some((t, a)) => (t, Some(a.trim().to_string())),
+                None => (inner, None),
+            };
+            let (target, heading) = match target.split_once('#') {
+                Some((t, h)) => (t, Some(h.trim().to_string())),
+                None => (target, None),
+            };
+            found.push(Link {
+                kind: LinkKind::Wiki,
+                target: target.trim().to_string(),
+                heading,
+                alias,
+                embed: !caps[1].is_empty(),
+                line: index + 1,
+                column: line[..whole.start()].chars().count() + 1,
+                start: line_start + whole.start(),
+                end: line_start + whole.end(),
+                target_range: (inner_start, inner_start + target_len),
+            });
+        }
+
+        for caps in markdown_re().captures_iter(line) {
+            let whole = caps.get(0).unwrap();
+            let inside_wiki = found
+                .iter()
+                .any(|l| (l.start..l.end).contains(&(line_start + whole.start())));
+            if in_code(whole.start()) || inside_wiki {
+                continue;
+            }
+            let destination_match = caps.get(3).or_else(|| caps.get(4));
+            let destination = destination_match.map(|m| m.as_str()).unwrap_or("");
+            let destination_start = line_start + destination_match.map(|m| m.start()).unwrap_or(0);
+            if !is_relative(destination) {
+                continue;
+            }
+            let (target, heading) = match destination.split_once('#') {
+                Some((t, h)) => (t, Some(h.to_string())),
+                None => (destination, None),
+            };
+            found.push(Link {
+                kind: LinkKind::Markdown,
+                target: percent_decode(target),
+                heading,
+                alias: Some(caps[2].to_string()),
+                embed: !caps[1].is_empty(),
+                line: index + 1,
+                column: line[..whole.start()].chars().count() + 1,
+                start: line_start + whole.start(),
+                end: line_start + whole.end(),
+                target_range: (destination_start, destination_start + destination.len()),
+            });
+        }
+
+        found.sort_by_key(|l| l.start);
+        links.extend(found);
+    }
+    links
+}
+
+/// The outgoing links of the document at `path` that point at another file,
+/// for the index.
+pub fn link_refs(path: &Path, content: &str) -> Vec<LinkRef> {
+    let mut refs: Vec<LinkRef> = Vec::new();
+    for link in parse_links(content) {
+        let link_ref = match link.kind {
+            LinkKind::Wiki if !link.target.is_empty() => LinkRef::Wiki(link.target),
+            LinkKind::Markdown if !link.target.is_empty() => {
+                let dir = path.parent().unwrap_or(Path::new(""));
+                LinkRef::File(normalize(&dir.join(&link.target)).to_string_lossy().to_string())
+            }
+            _ => continue,
+        };
+        if !refs.contains(&link_ref) {
+            refs.push(link_ref);
+        }
+    }
+    refs
+}
+
+/// Replace every non-embed wiki link in `content` with the markdown that
+/// `render` returns for it. Code is left alone.
+pub fn replace_wiki_links(content: &str, mut render: impl FnMut(&Link) -> String) -> String {
+    let mut out = String::with_capacity(content.len());
+    let mut pos = 0;
+    for link in parse_links(content) {
+        if link.kind != LinkKind::Wiki || link.embed {
+            continue;
+        }
+        out.push_str(&content[pos..link.start]);
+        out.push_str(&render(&link));
+        pos = link.end;
+    }
+    out.push_str(&content[pos..]);
+    out
+}
+
+/// Text shown for a wiki link: its alias, or the target and heading.
+pub fn display_text(link: &Link) -> String {
+    if let Some(alias) = link.alias.as_ref().filter(|a| !a.is_empty()) {
+        return alias.clone();
+    }
+    match (&link.heading, link.target.is_empty()) {
+        (Some(heading), true) => heading.clone(),
+        (Some(heading), false) => format!("{} > {}", link.target, heading),
+        (None, _) => link.target.clone(),
+    }
+}
+
+/// GitHub-style heading anchor, matching the frontend's `slugify`.
+pub fn slugify(text: &str) -> String {
+    let kept: String = text
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c.is_whitespace() || *c == '-')
+        .collect();
+    kept.split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-")
+        .trim_matches('-')
+        .to_string()
+}
+
+/// Anchors of every heading in `content`, as the frontend assigns them:
+/// repeated slugs get `-1`, `-2`, ... suffixes.
+pub fn heading_anchors(content: &str) -> HashSet<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    let mut anchors = HashSet::new();
+    for (line, kind) in content.lines().zip(line_kinds(content)) {
+        if !matches!(kind, LineKind::Title | LineKind::Heading) {
+            continue;
+        }
+        let text = heading_text(line.trim_start()).map(|(_, t)| t).unwrap_or("");
+        let slug = slugify(text);
+        let count = counts.entry(slug.clone()).or_default();
+        anchors.insert(if *count == 0 { slug } else { format!("{}-{}", slug, count) });
+        *count += 1;
+    }
+    anchors
+}
+
+/// `to` relative to the directory `from_dir`, with `/` separators.
+pub fn relative_path(from_dir: &Path, to: &Path) -> String {
+    let from: Vec<Component> = from_dir.components().collect();
+    let target: Vec<Component> = to.components().collect();
+    let common = from.iter().zip(&target).take_while(|(a, b)| a == b).count();
+    let mut parts: Vec<String> = vec!["..".to_string(); from.len() - common];
+    parts.extend(target[common..].iter().map(|c| c.as_os_str().to_string_lossy().to_string()));
+    parts.join("/")
+}
+
+/// Percent-encode the characters that would end or break a markdown link
+/// destination.
+pub fn encode_destination(path: &str) -> String {
+    let mut out = String::with_capacity(path.len());
+    for c in path.chars() {
+        match c {
+            ' ' => out.push_str("%20"),
+            '(' => out.push_str("%28"),
+            ')' => out.push_str("%29"),
+            '<' => out.push_str("%3C"),
+            '>' => out.push_str("%3E"),
+            '#' => out.push_str("%23"),
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Resolves link targets to document paths across all workspace roots.
+pub struct Resolver {
+    /// (path, root) of every document.
+    docs: Vec<(PathBuf, PathBuf)>,
+    paths: HashSet<PathBuf>,
+    /// Lowercased file name and file stem → indexes into `docs`.
+    by_name: HashMap<String, Vec<usize>>,
+    /// Path without its extension → indexes into `docs`.
+    by_base: HashMap<PathBuf, Vec<usize>>,
+}
+
+impl Resolver {
+    pub fn new(docs: Vec<(PathBuf, PathBuf)>) -> Self {
+        let mut by_name: HashMap<String, Vec<usize>> = HashMap::new();
+        let mut by_base: HashMap<PathBuf, Vec<usize>> = HashMap::new();
+        for (i, (path, _)) in docs.iter().enumerate() {
+            let name = path.file_name().map(|n| n.to_string_lossy().to_lowercase());
+            let stem = path.file_stem().map(|s| s.to_string_lossy().to_lowercase());
+            if let Some(name) = &name {
+                by_name.entry(name.clone()).or_default().push(i);
+            }
+            if let Some(stem) = stem.filter(|s| Some(s) != name.as_ref()) {
+                by_name.entry(stem).or_default().push(i);
+            }
+            by_base.entry(path.with_extension("")).or_default().push(i);
+        }
+        let paths = docs.iter().map(|(p, _)| p.clone()).collect();
+        Resolver { docs, paths, by_name, by_base }
+    }
+
+    /// The document a stored link points to, if it exists.
+    pub fn resolve_ref(&self, source: &Path, link: &LinkRef) -> Option<PathBuf> {
+        match link {
+            LinkRef::Wiki(target) => self.resolve_wiki(source, target),
+            LinkRef::File(path) => Some(PathBuf::from(path)).filter(|p| self.paths.contains(p)),
+        }
+    }
+
+    /// The document `link` (written in `source`) points to. Same-document
+    /// links resolve to `source`.
+    pub fn resolve(&self, source: &Path, link: &Link) -> Option<PathBuf> {
+        if link.target.is_empty() {
+            return Some(source.to_path_buf());
+        }
+        match link.kind {
+            LinkKind::Wiki => self.resolve_wiki(source, &link.target),
+            LinkKind::Markdown => {
+                let dir = source.parent().unwrap_or(Path::new(""));
+                Some(normalize(&dir.join(&link.target))).filter(|p| self.paths.contains(p))
+            }
+        }
+    }
+
+    /// Markdown link destination for `link` (written in `source`) as seen
+    /// from `base_dir`, with the heading as an anchor. None if the target
+    /// doesn't exist.
+    pub fn href(&self, source: &Path, link: &Link, base_dir: &Path) -> Option<String> {
+        let anchor = link
+            .heading
+            .as_deref()
+            .map(|h| format!("#{}", slugify(h)))
+            .unwrap_or_default();
+        if link.target.is_empty() {
+            return Some(anchor).filter(|a| !a.is_empty());
+        }
+        let target = self.resolve(source, link)?;
+        Some(format!("{}{}", encode_destination(&relative_path(base_dir, &target)), anchor))
+    }
+
+    /// The shortest wiki link target, written in `source`, that resolves to
+    /// the document `target`: its name, or as much of its path as it takes to
+    /// tell it apart. Falls back to a path relative to `source`.
+    pub fn wiki_target(&self, source: &Path, target: &Path) -> String {
+        let root = self.docs.iter().find(|(p, _)| p == target).map(|(_, r)| r.as_path());
+        let relative = root.and_then(|r| target.strip_prefix(r).ok()).unwrap_or(target);
+        // Without the extension where that is unambiguous, with it otherwise.
+        for relative in [relative.with_extension(""), relative.to_path_buf()] {
+            let parts: Vec<String> = relative.iter().map(|c| c.to_string_lossy().to_string()).collect();
+            for n in 1..=parts.len() {
+                let candidate = parts[parts.len() - n..].join("/");
+                if self.resolve_wiki(source, &candidate).as_deref() == Some(target) {
+                    return candidate;
+                }
+            }
+        }
+        let dir = source.parent().unwrap_or(Path::new(""));
+        let bare = relative_path(dir, &target.with_extension(""));
+        let bare = if bare.starts_with("../") { bare } else { format!("./{}", bare) };
+        if self.resolve_wiki(source, &bare).as_deref() == Some(target) {
+            return bare;
+        }
+        let path = relative_path(dir, target);
+        if path.starts_with("../") {
+            path
+        } else {
+            format!("./{}", path)
+        }
+    }
+
+    fn resolve_wiki(&self, source: &Path, target: &str) -> Option<PathBuf> {
+        let target = target.trim().replace('\\', "/");
+        if target.starts_with("./") || target.starts_with("../") {
+            let dir = source.parent().unwrap_or(Path::new(""));
+            let path = normalize(&dir.join(&target));
+            if self.paths.contains(&path) {
+                return Some(path);
+            }
+            return self.by_base.get(&path)?.iter().map(|i| &self.docs[*i].0).min().cloned();
+        }
+        let parts: Vec<String> = target
+            .split('/')
+            .filter(|p| !p.is_empty())
+            .map(str::to_lowercase)
+            .collect();
+        let name = parts.last()?;
+        let source_root = self.docs.iter().find(|(p, _)| p == source).map(|(_, r)| r);
+        let lowercase = |path: &Path| -> Vec<String> {
+            path.iter().map(|c| c.to_string_lossy().to_lowercase()).collect()
+        };
+
+        self.by_name
+            .get(name)?
+            .iter()
+            .map(|i| &self.docs[*i])
+            .filter(|(path, root)| {
+                // `[[note]]` and `[[note.md]]` both name `note.md`.
+                let relative = path.strip_prefix(root).unwrap_or(path);
+                lowercase(&relative.with_extension("")).ends_with(&parts)
+                    || lowercase(relative).ends_with(&parts)
+            })
+            .min_by_key(|(path, root)| {
+                let other_root = Some(root) != source_root;
+                let shared = path
+                    .components()
+                    .zip(source.components())
+                    .take_while(|(a, b)| a == b)
+                    .count();
+                (other_root, usize::MAX - shared, path.components().count(), path.clone())
+            })
+            .map(|(path, _)| path.clone())
+    }
+}
+
+/// Resolved outgoing links and backlinks of every document.
+#[derive(Default)]
+pub struct LinkGraph {
+    pub outgoing: HashMap<PathBuf, Vec<PathBuf>>,
+    pub backlinks: HashMap<PathBuf, Vec<PathBuf>>,
+}
+
+impl LinkGraph {
+    /// Resolve the stored links of every document. Links from a document to
+    /// itself are left out.
+    pub fn build(resolver: &Resolver, links: &[(PathBuf, Vec<LinkRef>)]) -> Self {
+        let mut graph = LinkGraph::default();
+        for (source, refs) in links {
+            let mut targets: Vec<PathBuf> = refs
+                .iter()
+                .filter_map(|r| resolver.resolve_ref(source, r))
+                .filter(|t| t != source)
+                .collect();
+            targets.sort();
+            targets.dedup();
+            for target in &targets {
+                graph.backlinks.entry(target.clone()).or_default().push(source.clone());
+            }
+            graph.outgoing.insert(source.clone(), targets);
+        }
+        for sources in graph.backlinks.values_mut() {
+            sources.sort();
+        }
+        graph
+    }
+}
+
+/// A file or folder moving from `from` to `to`, with link resolution as the
+/// workspace was before the move and as it is after.
+pub struct PathMove<'a> {
+    pub from: &'a Path,
+    pub to: &'a Path,
+    pub before: &'a Resolver,
+    pub after: &'a Resolver,
+}
+
+impl PathMove<'_> {
+    /// Where `path` ends up, if it is the moved entry or inside it.
+    pub fn new_path(&self, path: &Path) -> Option<PathBuf> {
+        let rest = path.strip_prefix(self.from).ok()?;
+        Some(if rest.as_os_str().is_empty() {
+            self.to.to_path_buf()
+        } else {
+            self.to.join(rest)
+        })
+    }
+
+    /// Whether the document at `source` (its path before the move), with the
+    /// stored links `refs`, may need its links rewritten: it links into the
+    /// moved entry or moves along with it.
+    pub fn affects(&self, source: &Path, refs: &[LinkRef]) -> bool {
+        source.starts_with(self.from)
+            || refs.iter().any(|r| match r {
+                LinkRef::File(path) => Path::new(path).starts_with(self.from),
+                LinkRef::Wiki(_) => self
+                    .before
+                    .resolve_ref(source, r)
+                    .is_some_and(|p| p.starts_with(self.from)),
+            })
+    }
+
+    /// Rewrite the links of the document that was at `old_source` so they
+    /// keep pointing where they did. Markdown links and images get a new
+    /// relative destination (the anchor is kept); wiki links that no longer
+    /// reach their document get the shortest target that does, keeping the
+    /// heading and alias. Wiki links that didn't resolve are left alone.
+    /// Returns None when nothing had to change.
+    pub fn rewrite(&self, old_source: &Path, content: &str) -> Option<String> {
+        let new_source = self.new_path(old_source).unwrap_or_else(|| old_source.to_path_buf());
+        let old_dir = old_source.parent().unwrap_or(Path::new(""));
+        let new_dir = new_source.parent().unwrap_or(Path::new(""));
+        let mut out = String::with_capacity(content.len());
+        let mut pos = 0;
+        let mut changed = false;
+        for link in parse_links(content) {
+            if link.target.is_empty() {
+                continue;
+            }
+            let replacement = match link.kind {
+                LinkKind::Markdown => {
+                    let old_target = normalize(&old_dir.join(&link.target));
+                    let new_target = self.new_path(&old_target).unwrap_or(old_target);
+                    if normalize(&new_dir.join(&link.target)) == new_target {
+                        continue;
+                    }
+                    let anchor = link.heading.as_deref().map(|h| format!("#{}", h)).unwrap_or_default();
+                    format!("{}{}", encode_destination(&relative_path(new_dir, &new_target)), anchor)
+                }
+                LinkKind::Wiki => {
+                    let old_target = match self.before.resolve(old_source, &link) {
+                        Some(t) => t,
+                        None => continue,
+                    };
+                    let new_target = self.new_path(&old_target).unwrap_or(old_target);
+                    if self.after.resolve(&new_source, &link).as_ref() == Some(&new_target) {
+                        continue;
+                    }
+                    self.after.wiki_target(&new_source, &new_target)
+                }
+            };
+            out.push_str(&content[pos..link.target_range.0]);
+            out.push_str(&replacement);
+            pos = link.target_range.1;
+            changed = true;
+        }
+        out.push_str(&content[pos..]);
+        if changed {
+            Some(out)
+        } else {
+            None
+        }
+    }
+}
+
+/// Byte ranges of inline code spans (`` `code` ``) in a line.
+fn code_spans(line: &str) -> Vec<(usize, usize)> {
+    let mut spans = Vec::new();
+    let bytes = line.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] != b'`' {
+            i += 1;
+            continue;
+        }
+        let ticks = bytes[i..].iter().take_while(|b| **b == b'`').count();
+        let fence = &line[i..i + ticks];
+        match line[i + ticks..].find(fence) {
+            Some(close) => {
+                let end = i + ticks + close + ticks;
+                spans.push((i, end));
+                i = end;
+            }
+            None => i += ticks,
+        }
+    }
+    spans
+}
+
+fn is_relative(destination: &str) -> bool {
+    let has_scheme = destination
+        .split_once(':')
+        .map(|(scheme, _)| {
+            !scheme.is_empty()
+                && scheme.chars().all(|c| c.is_ascii_alphanumeric() || "+.-".contains(c))
+        })
+        .unwrap_or(false);
+    !destination.is_empty() && !has_scheme && !destination.starts_with('/') && !destination.starts_with('\\')
+}
+
+/// Resolve `.` and `..` components without touching the filesystem.
+pub fn normalize(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                out.pop();
+            }
+            other => out.push(other.as_os_str()),
+        }
+    }
+    out
+}
+
+pub fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        if bytes[i] == b'%' {
+            if let Some(byte) = text.get(i + 1..i + 3).and_then(|h| u8::from_str_radix(h, 16).ok()) {
+                out.push(byte);
+                i += 3;
+                continue;
+            }
+        }
+        out.push(bytes[i]);
+        i += 1;
+    }
+    String::from_utf8(out).unwrap_or_else(|_| text.to_string())
+}
@@ -6,21 +6,71 @@ mod mcp_server;
 mod storage;
 mod filesystem;
 mod config;
-
-use converter::{ExportFormat, convert_markdown, check_chrome_available, convert_html_to_pdf};
+mod atomic;
+mod watcher;
+mod trash;
+mod history;
+mod search_index;
+mod search_query;
+mod quick_open;
+mod front_matter;
+mod tags;
+mod links;
+mod link_check;
+mod attachments;
+mod documents;
+mod workspace_filter;
+mod sandbox;
+mod transfer;
+mod pdf_layout;
+mod browser_pool;
+
+use converter::{
+    DiagramFormat, ExportFormat, ExportOptions, ExportReport, check_chrome_available, convert_markdown,
+    has_mermaid_runtime, mermaid_sources, require_mermaid_runtime,
+};
 use storage::TreeNode;
-use storage::SearchResult;
+use storage::{Backlink, CollisionPolicy, ImportedAttachment, LinkReport, MovedEntry, QuickOpenResult, SearchPage, TagCount, TagRenameResult};
+use trash::TrashEntry;
+use history::{EditOrigin, HistoryRevision};
 use filesystem::FilesystemStorage;
+use front_matter::Metadata;
+use links::Link;
+use browser_pool::{BrowserPool, BrowserPoolArc, DEFAULT_IDLE_TIMEOUT, DEFAULT_MAX_CONCURRENT};
 use config::StorageConfig;
+use pdf_layout::PdfOptions;
+use documents::DocumentTypes;
+use workspace_filter::FilterSettings;
+use watcher::{WorkspaceWatcher, FS_CHANGES_EVENT};
+use std::collections::BTreeMap;
 use std::sync::{Arc, Mutex, RwLock};
 use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 use tauri::{State, Manager};
 use tauri::api::path::app_data_dir;
 use tokio::task::JoinHandle;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 
 /// Holds the join-handle of the running axum server, or None when stopped.
 struct McpServerState(Mutex<Option<JoinHandle<()>>>);
 
+/// Recursive watcher over all workspace roots, or None if it failed to start.
+struct WatcherState(Option<WorkspaceWatcher>);
+
+impl WatcherState {
+    /// Re-align the watched directories with the configured roots (best effort).
+    fn sync(&self, backend: &FilesystemStorage) {
+        if let Some(watcher) = &self.0 {
+            let _ = watcher.sync_roots(&backend.root_paths());
+        }
+    }
+}
+
+/// How often pending search index changes are written to disk.
+const SEARCH_INDEX_FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
 /// The single filesystem-backed storage backend.
 type FsArc = Arc<FilesystemStorage>;
 type FsState<'a> = State<'a, FsArc>;
@@ -28,6 +78,9 @@ type FsState<'a> = State<'a, FsArc>;
 /// Storage config state for getting/setting MCP port.
 type ConfigArc = Arc<RwLock<StorageConfig>>;
 
+/// Problem found while loading the config at startup, reported once to the UI.
+struct ConfigWarning(Mutex<Option<String>>);
+
 // ── Unified storage commands ─────────────────────────────────────────────────
 
 #[tauri::command]
@@ -36,13 +89,67 @@ fn storage_list_roots(backend: FsState) -> Result<Vec<TreeNode>, String> {
 }
 
 #[tauri::command]
-fn storage_add_root(backend: FsState, name: String, extra: Option<String>) -> Result<TreeNode, String> {
-    backend.add_root(&name, extra.as_deref())
+fn storage_add_root(
+    backend: FsState,
+    watcher: State<'_, WatcherState>,
+    name: String,
+    extra: Option<String>,
+) -> Result<TreeNode, String> {
+    let root = backend.add_root(&name, extra.as_deref())?;
+    watcher.sync(&backend);
+    // Index the new root in the background; large folders take a while.
+    let indexer = Arc::clone(&backend);
+    std::thread::spawn(move || indexer.sync_search_index());
+    Ok(root)
 }
 
 #[tauri::command]
-fn storage_remove_root(backend: FsState, id: String) -> Result<bool, String> {
-    backend.remove_root(&id)
+fn storage_get_document_types(
+    backend: FsState,
+    root_id: Option<String>,
+) -> Result<DocumentTypes, String> {
+    backend.get_document_types(root_id.as_deref())
+}
+
+#[tauri::command]
+fn storage_set_document_types(
+    backend: FsState,
+    root_id: Option<String>,
+    types: Option<DocumentTypes>,
+) -> Result<DocumentTypes, String> {
+    let types = backend.set_document_types(root_id.as_deref(), types)?;
+    // Documents may have appeared or disappeared; re-sync in the background.
+    let indexer = Arc::clone(&backend);
+    std::thread::spawn(move || indexer.sync_search_index());
+    Ok(types)
+}
+
+#[tauri::command]
+fn storage_get_workspace_filter(backend: FsState, root_id: String) -> Result<FilterSettings, String> {
+    backend.get_workspace_filter(&root_id)
+}
+
+#[tauri::command]
+fn storage_set_workspace_filter(
+    backend: FsState,
+    root_id: String,
+    settings: FilterSettings,
+) -> Result<FilterSettings, String> {
+    let settings = backend.set_workspace_filter(&root_id, settings)?;
+    let indexer = Arc::clone(&backend);
+    std::thread::spawn(move || indexer.sync_search_index());
+    Ok(settings)
+}
+
+#[tauri::command]
+fn storage_remove_root(
+    backend: FsState,
+    watcher: State<'_, WatcherState>,
+    id: String,
+) -> Result<bool, String> {
+    let removed = backend.remove_root(&id)?;
+    watcher.sync(&backend);
+    Ok(removed)
 }
 
 #[tauri::command]
@@ -60,8 +167,9 @@ fn storage_create_folder(
     backend: FsState,
     parent_id: String,
     name: String,
+    on_collision: Option<CollisionPolicy>,
 ) -> Result<TreeNode, String> {
-    backend.create_folder(&parent_id, &name)
+    backend.create_folder(&parent_id, &name, on_collision.unwrap_or_default())
 }
 
 #[tauri::command]
@@ -70,8 +178,9 @@ fn storage_create_document(
     parent_id: String,
     name: String,
     content: String,
+    on_collision: Option<CollisionPolicy>,
 ) -> Result<TreeNode, String> {
-    backend.create_document(&parent_id, &name, &content)
+    backend.create_document(&parent_id, &name, &content, on_collision.unwrap_or_default())
 }
 
 #[tauri::command]
@@ -80,13 +189,49 @@ fn storage_update_document(
     id: String,
     name: String,
     content: String,
+    expected_revision: Option<String>,
+    on_collision: Option<CollisionPolicy>,
 ) -> Result<TreeNode, String> {
-    backend.update_document(&id, &name, &content)
+    backend.update_document(
+        &id,
+        &name,
+        &content,
+        expected_revision.as_deref(),
+        on_collision.unwrap_or_default(),
+        EditOrigin::App,
+    )
+}
+
+#[tauri::command]
+fn storage_get_metadata(backend: FsState, id: String) -> Result<Metadata, String> {
+    backend.get_metadata(&id)
 }
 
 #[tauri::command]
-fn storage_rename_entry(backend: FsState, id: String, new_name: String) -> Result<TreeNode, String> {
-    backend.rename_entry(&id, &new_name)
+fn storage_update_metadata(
+    backend: FsState,
+    id: String,
+    updates: Metadata,
+    expected_revision: Option<String>,
+) -> Result<TreeNode, String> {
+    backend.update_metadata(&id, &updates, expected_revision.as_deref(), EditOrigin::App)
+}
+
+#[tauri::command]
+fn storage_rename_entry(
+    backend: FsState,
+    id: String,
+    new_name: String,
+    update_links: Option<bool>,
+    on_collision: Option<CollisionPolicy>,
+) -> Result<MovedEntry, String> {
+    backend.rename_entry(
+        &id,
+        &new_name,
+        update_links.unwrap_or(false),
+        on_collision.unwrap_or_default(),
+        EditOrigin::App,
+    )
 }
 
 #[tauri::command]
@@ -95,44 +240,244 @@ fn storage_delete_entry(backend: FsState, id: String) -> Result<bool, String> {
 }
 
 #[tauri::command]
-fn storage_move_entry(backend: FsState, id: String, new_parent_id: String) -> Result<TreeNode, String> {
-    backend.move_entry(&id, &new_parent_id)
+fn storage_move_entry(
+    backend: FsState,
+    id: String,
+    new_parent_id: String,
+    update_links: Option<bool>,
+    on_collision: Option<CollisionPolicy>,
+) -> Result<MovedEntry, String> {
+    backend.move_entry(
+        &id,
+        &new_parent_id,
+        update_links.unwrap_or(false),
+        on_collision.unwrap_or_default(),
+        EditOrigin::App,
+    )
+}
+
+#[tauri::command]
+fn storage_copy_entry(backend: FsState, id: String, new_parent_id: String) -> Result<TreeNode, String> {
+    backend.copy_entry(&id, &new_parent_id)
 }
 
 #[tauri::command]
-fn storage_search(backend: FsState, query: String) -> Result<Vec<SearchResult>, String> {
-    backend.search(&query)
+fn storage_duplicate_entry(backend: FsState, id: String) -> Result<TreeNode, String> {
+    backend.duplicate_entry(&id)
 }
 
+/// `data_base64` is the file content, base64-encoded.
+#[tauri::command]
+fn storage_import_attachment(
+    backend: FsState,
+    document_id: String,
+    file_name: String,
+    data_base64: String,
+) -> Result<ImportedAttachment, String> {
+    let data = BASE64
+        .decode(data_base64.as_bytes())
+        .map_err(|e| format!("Invalid base64 data: {}", e))?;
+    backend.import_attachment(&document_id, &file_name, &data)
+}
+
+#[tauri::command]
+fn storage_list_trash(backend: FsState, root_id: Option<String>) -> Result<Vec<TrashEntry>, String> {
+    backend.list_trash(root_id.as_deref())
+}
+
+#[tauri::command]
+fn storage_restore_trash_entry(
+    backend: FsState,
+    root_id: Option<String>,
+    trash_id: String,
+) -> Result<TreeNode, String> {
+    backend.restore_trash_entry(root_id.as_deref(), &trash_id)
+}
+
+#[tauri::command]
+fn storage_purge_trash(
+    backend: FsState,
+    root_id: Option<String>,
+    trash_id: Option<String>,
+) -> Result<usize, String> {
+    backend.purge_trash(root_id.as_deref(), trash_id.as_deref())
+}
+
+#[tauri::command]
+fn storage_list_history(backend: FsState, id: String) -> Result<Vec<HistoryRevision>, String> {
+    backend.list_history(&id)
+}
+
+#[tauri::command]
+fn storage_get_history_revision(backend: FsState, id: String, revision: u32) -> Result<String, String> {
+    backend.get_history_revision(&id, revision)
+}
+
+#[tauri::command]
+fn storage_diff_history(
+    backend: FsState,
+    id: String,
+    from: u32,
+    to: Option<u32>,
+) -> Result<String, String> {
+    backend.diff_history(&id, from, to)
+}
+
+#[tauri::command]
+fn storage_restore_history(backend: FsState, id: String, revision: u32) -> Result<TreeNode, String> {
+    backend.restore_history(&id, revision)
+}
+
+#[tauri::command]
+fn storage_search(
+    backend: FsState,
+    query: String,
+    offset: Option<usize>,
+    limit: Option<usize>,
+    context_lines: Option<usize>,
+) -> Result<SearchPage, String> {
+    backend.search(&query, offset, limit, context_lines)
+}
+
+#[tauri::command]
+fn storage_quick_open(
+    backend: FsState,
+    query: String,
+    limit: Option<usize>,
+) -> Result<Vec<QuickOpenResult>, String> {
+    backend.quick_open(&query, limit)
+}
+
+#[tauri::command]
+fn storage_record_opened(backend: FsState, id: String) -> Result<(), String> {
+    backend.record_opened(&id)
+}
+
+#[tauri::command]
+fn storage_get_backlinks(backend: FsState, id: String) -> Result<Vec<Backlink>, String> {
+    backend.get_backlinks(&id)
+}
+
+#[tauri::command]
+fn storage_check_links(backend: FsState, id: String) -> Result<LinkReport, String> {
+    backend.check_links(&id)
+}
+
+#[tauri::command]
+fn storage_list_tags(backend: FsState) -> Result<Vec<TagCount>, String> {
+    backend.list_tags()
+}
+
+#[tauri::command]
+fn storage_documents_for_tag(backend: FsState, tag: String) -> Result<Vec<TreeNode>, String> {
+    backend.documents_for_tag(&tag)
+}
+
+#[tauri::command]
+fn storage_rename_tag(backend: FsState, from: String, to: String) -> Result<TagRenameResult, String> {
+    backend.rename_tag(&from, &to, EditOrigin::App)
+}
+
+/// PDFs are always rendered self-contained with static diagrams, so Chrome
+/// needs no network. HTML diagrams are pre-rendered when asked and Chrome is
+/// available; diagrams Mermaid rejects are listed in the report.
 #[tauri::command]
 fn storage_export_document(
     backend: FsState,
+    browsers: State<'_, BrowserPoolArc>,
     id: String,
     format: String,
     output_path: String,
-) -> Result<(), String> {
+    options: Option<ExportOptions>,
+) -> Result<ExportReport, String> {
     let entry = backend.get_entry(&id)?
         .ok_or_else(|| format!("Entry not found: {}", id))?;
     let content = entry.content.ok_or_else(|| "Entry is not a document".to_string())?;
 
+    // Wiki links become links relative to where the export is written
+    let resolver = backend.link_resolver();
+    let source = PathBuf::from(&entry.id);
+    let base_dir = Path::new(&output_path).parent().map(Path::to_path_buf).unwrap_or_default();
+    let wiki_link = |link: &Link| resolver.href(&source, link, &base_dir);
+
     let export_format = ExportFormat::from_str(&format)?;
-    match export_format {
-        ExportFormat::Html => {
-            let output_bytes = convert_markdown(&content, &export_format)?;
-            fs::write(&output_path, output_bytes)
-                .map_err(|e| format!("Failed to write file: {}", e))?;
-        }
-        ExportFormat::Pdf => {
-            let html_bytes = convert_markdown(&content, &ExportFormat::Html)?;
-            let html = String::from_utf8_lossy(&html_bytes).to_string();
-            // We need an async context for chromiumoxide; spawn a blocking task
-            let pdf_bytes = tokio::runtime::Handle::current()
-                .block_on(convert_html_to_pdf(&html))?;
-            fs::write(&output_path, pdf_bytes)
-                .map_err(|e| format!("Failed to write file: {}", e))?;
-        }
+    let is_pdf = matches!(export_format, ExportFormat::Pdf);
+    let options = if is_pdf {
+        ExportOptions { self_contained: true, static_diagrams: true, ..options.unwrap_or_default() }
+    } else {
+        options.unwrap_or_default()
+    };
+    if is_pdf {
+        options.pdf.validate()?;
     }
-    Ok(())
+    let sources = mermaid_sources(&content);
+    // Self-contained exports (PDFs included) must not fall back to showing
+    // diagrams as text
+    if options.self_contained && !sources.is_empty() {
+        require_mermaid_runtime()?;
+    }
+    let prerender = options.static_diagrams
+        && !sources.is_empty()
+        && has_mermaid_runtime()
+        && (is_pdf || check_chrome_available().is_ok());
+
+    let (output_bytes, diagrams) = if is_pdf || prerender {
+        // We need an async context for chromiumoxide; the shared browser
+        // serves both the diagrams and the PDF
+        let (content, options, sources, wiki_link) = (&content, &options, &sources, &wiki_link);
+        tokio::runtime::Handle::current().block_on(browsers.run(|browser| async move {
+            let diagrams = if prerender {
+                browser.render_diagrams(sources, None, DiagramFormat::Svg).await?
+            } else {
+                Vec::new()
+            };
+            let html = convert_markdown(content, &ExportFormat::Html, options, &diagrams, wiki_link)?;
+            let bytes = if is_pdf {
+                browser.html_to_pdf(&String::from_utf8_lossy(&html), &options.pdf, options.ready_timeout()).await?
+            } else {
+                html
+            };
+            Ok((bytes, diagrams))
+        }))?
+    } else {
+        (convert_markdown(&content, &export_format, &options, &[], &wiki_link)?, Vec::new())
+    };
+    fs::write(&output_path, output_bytes)
+        .map_err(|e| format!("Failed to write file: {}", e))?;
+
+    Ok(ExportReport {
+        diagram_errors: diagrams.iter().filter_map(|d| d.error()).collect(),
+    })
+}
+
+/// Render Mermaid diagram `index` (0-based) of a document to an SVG or PNG
+/// file. A diagram Mermaid rejects fails with `StorageError::InvalidDiagram`.
+#[tauri::command]
+fn storage_export_diagram(
+    backend: FsState,
+    browsers: State<'_, BrowserPoolArc>,
+    id: String,
+    index: usize,
+    format: String,
+    output_path: String,
+) -> Result<(), String> {
+    let entry = backend.get_entry(&id)?
+        .ok_or_else(|| format!("Entry not found: {}", id))?;
+    let content = entry.content.ok_or_else(|| "Entry is not a document".to_string())?;
+    let format = DiagramFormat::from_str(&format)?;
+    check_chrome_available()?;
+
+    let sources = mermaid_sources(&content);
+    let sources = &sources;
+    let mut rendered = tokio::runtime::Handle::current().block_on(browsers.run(|browser| async move {
+        browser.render_diagrams(sources, Some(index), format).await
+    }))?;
+    let image = rendered
+        .pop()
+        .ok_or_else(|| format!("No Mermaid diagram {}", index))?
+        .into_image()?;
+    fs::write(&output_path, image)
+        .map_err(|e| format!("Failed to write file: {}", e))
 }
 
 /// Check if PDF export is available (Chrome installed)
@@ -149,6 +494,7 @@ async fn start_mcp_server(
     mcp_state: State<'_, McpServerState>,
     fs_arc: State<'_, FsArc>,
     config: State<'_, ConfigArc>,
+    browsers: State<'_, BrowserPoolArc>,
 ) -> Result<(), String> {
     // Check if already running — drop the guard before any .await
     {
@@ -158,7 +504,7 @@ async fn start_mcp_server(
         }
     } // guard dropped here
 
-    let router = mcp_server::build_router(Arc::clone(&fs_arc), app_handle);
+    let router = mcp_server::build_router(Arc::clone(&fs_arc), Arc::clone(&browsers), app_handle);
 
     // Get the configured port
     let port = {
@@ -198,6 +544,13 @@ fn get_mcp_server_status(mcp_state: State<'_, McpServerState>) -> Result<bool, S
     Ok(handle_guard.is_some())
 }
 
+/// Returns (and clears) the message explaining why the config could not be loaded.
+#[tauri::command]
+fn take_config_warning(warning: State<'_, ConfigWarning>) -> Result<Option<String>, String> {
+    let mut guard = warning.0.lock().map_err(|e| e.to_string())?;
+    Ok(guard.take())
+}
+
 #[tauri::command]
 fn get_mcp_port(config: State<'_, ConfigArc>) -> Result<u16, String> {
     let config_guard = config.read().map_err(|e| e.to_string())?;
@@ -229,6 +582,121 @@ fn set_mcp_port(
     Ok(())
 }
 
+#[tauri::command]
+fn get_trash_retention(config: State<'_, ConfigArc>) -> Result<Option<u32>, String> {
+    let config_guard = config.read().map_err(|e| e.to_string())?;
+    Ok(config_guard.trash_retention_days)
+}
+
+#[tauri::command]
+fn set_trash_retention(
+    days: Option<u32>,
+    config: State<'_, ConfigArc>,
+    app_data_dir: tauri::State<'_, std::sync::Mutex<Option<std::path::PathBuf>>>,
+) -> Result<(), String> {
+    {
+        let mut config_guard = config.write().map_err(|e| e.to_string())?;
+        config_guard.trash_retention_days = days;
+    }
+    let dir = {
+        let dir_guard = app_data_dir.lock().map_err(|e| e.to_string())?;
+        dir_guard.clone()
+    };
+    if let Some(dir) = dir {
+        let config_guard = config.read().map_err(|e| e.to_string())?;
+        config_guard.save(&dir)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn get_assets_folder(config: State<'_, ConfigArc>) -> Result<String, String> {
+    let config_guard = config.read().map_err(|e| e.to_string())?;
+    Ok(config_guard.assets_folder.clone())
+}
+
+#[tauri::command]
+fn set_assets_folder(
+    folder: String,
+    config: State<'_, ConfigArc>,
+    app_data_dir: tauri::State<'_, std::sync::Mutex<Option<std::path::PathBuf>>>,
+) -> Result<(), String> {
+    let folder = folder.trim();
+    if folder.is_empty() {
+        return Err("Assets folder must not be empty".to_string());
+    }
+    {
+        let mut config_guard = config.write().map_err(|e| e.to_string())?;
+        config_guard.assets_folder = folder.to_string();
+    }
+    let dir = {
+        let dir_guard = app_data_dir.lock().map_err(|e| e.to_string())?;
+        dir_guard.clone()
+    };
+    if let Some(dir) = dir {
+        let config_guard = config.read().map_err(|e| e.to_string())?;
+        config_guard.save(&dir)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn get_pdf_presets(config: State<'_, ConfigArc>) -> Result<BTreeMap<String, PdfOptions>, String> {
+    let config_guard = config.read().map_err(|e| e.to_string())?;
+    Ok(config_guard.pdf_presets.clone())
+}
+
+/// Save `options` as the PDF preset `name`, replacing any preset of that name.
+#[tauri::command]
+fn save_pdf_preset(
+    name: String,
+    options: PdfOptions,
+    config: State<'_, ConfigArc>,
+    app_data_dir: tauri::State<'_, std::sync::Mutex<Option<std::path::PathBuf>>>,
+) -> Result<(), String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err("Preset name must not be empty".to_string());
+    }
+    options.validate()?;
+    {
+        let mut config_guard = config.write().map_err(|e| e.to_string())?;
+        config_guard.pdf_presets.insert(name.to_string(), options);
+    }
+    let dir = {
+        let dir_guard = app_data_dir.lock().map_err(|e| e.to_string())?;
+        dir_guard.clone()
+    };
+    if let Some(dir) = dir {
+        let config_guard = config.read().map_err(|e| e.to_string())?;
+        config_guard.save(&dir)?;
+    }
+    Ok(())
+}
+
+#[tauri::command]
+fn delete_pdf_preset(
+    name: String,
+    config: State<'_, ConfigArc>,
+    app_data_dir: tauri::State<'_, std::sync::Mutex<Option<std::path::PathBuf>>>,
+) -> Result<(), String> {
+    {
+        let mut config_guard = config.write().map_err(|e| e.to_string())?;
+        if config_guard.pdf_presets.remove(&name).is_none() {
+            return Err(format!("No PDF preset named {:?}", name));
+        }
+    }
+    let dir = {
+        let dir_guard = app_data_dir.lock().map_err(|e| e.to_string())?;
+        dir_guard.clone()
+    };
+    if let Some(dir) = dir {
+        let config_guard = config.read().map_err(|e| e.to_string())?;
+        config_guard.save(&dir)?;
+    }
+    Ok(())
+}
+
 fn main() {
     tauri::Builder::default()
         .setup(|app| {
@@ -237,7 +705,7 @@ fn main() {
                 .ok_or_else(|| "Failed to get app data directory")?;
 
             // Load or create storage config
-            let storage_config = StorageConfig::load(&app_data_dir);
+            let (storage_config, config_warning) = StorageConfig::load(&app_data_dir);
             let config_arc = Arc::new(RwLock::new(storage_config));
 
             // Single filesystem-backed storage backend
@@ -246,10 +714,43 @@ fn main() {
                 app_data_dir.clone(),
             ));
 
+            // Watch every workspace root; keep the search index current and
+            // forward coalesced changes to the UI
+            let handle = app.handle();
+            let watched = Arc::clone(&backend);
+            let rules = Arc::clone(&backend);
+            let watcher = WorkspaceWatcher::new(move || rules.workspace_filters(), move |changes| {
+                watched.apply_fs_changes(&changes);
+                if let Some(window) = handle.get_window("main") {
+                    let _ = window.emit(FS_CHANGES_EVENT, &changes);
+                }
+            })
+            .ok();
+            let watcher_state = WatcherState(watcher);
+            watcher_state.sync(&backend);
+
+            // Catch the search index up with edits made while the app was closed,
+            // then persist it periodically
+            let indexer = Arc::clone(&backend);
+            std::thread::spawn(move || {
+                indexer.sync_search_index();
+                loop {
+                    std::thread::sleep(SEARCH_INDEX_FLUSH_INTERVAL);
+                    let _ = indexer.flush_search_index();
+                }
+            });
+
+            // One headless Chrome, launched on the first export, serves
+            // both the UI and MCP
+            let browsers = BrowserPool::new(DEFAULT_MAX_CONCURRENT, DEFAULT_IDLE_TIMEOUT);
+
             // Manage state
             app.manage(backend);                               // FilesystemStorage
+            app.manage(watcher_state);                         // Workspace watcher
+            app.manage(browsers);                              // Headless Chrome for exports
             app.manage(McpServerState(Mutex::new(None)));      // MCP server handle
             app.manage(config_arc);                             // StorageConfig (for MCP port)
+            app.manage(ConfigWarning(Mutex::new(config_warning))); // config load problem, if any
             app.manage(std::sync::Mutex::new(Some(app_data_dir))); // app data dir for config saving
 
             Ok(())
@@ -258,22 +759,54 @@ fn main() {
             storage_list_roots,
             storage_add_root,
             storage_remove_root,
+            storage_get_document_types,
+            storage_set_document_types,
+            storage_get_workspace_filter,
+            storage_set_workspace_filter,
             storage_get_entry,
             storage_list_children,
             storage_create_folder,
             storage_create_document,
             storage_update_document,
+            storage_get_metadata,
+            storage_update_metadata,
             storage_rename_entry,
             storage_delete_entry,
             storage_move_entry,
+            storage_copy_entry,
+            storage_duplicate_entry,
+            storage_import_attachment,
+            storage_list_trash,
+            storage_restore_trash_entry,
+            storage_purge_trash,
+            storage_list_history,
+            storage_get_history_revision,
+            storage_diff_history,
+            storage_restore_history,
             storage_search,
+            storage_quick_open,
+            storage_record_opened,
+            storage_get_backlinks,
+            storage_check_links,
+            storage_list_tags,
+            storage_documents_for_tag,
+            storage_rename_tag,
             storage_export_document,
+            storage_export_diagram,
             check_pdf_available,
             start_mcp_server,
             stop_mcp_server,
             get_mcp_server_status,
             get_mcp_port,
             set_mcp_port,
+            take_config_warning,
+            get_trash_retention,
+            set_trash_retention,
+            get_assets_folder,
+            set_assets_folder,
+            get_pdf_presets,
+            save_pdf_preset,
+            delete_pdf_preset,
         ])
         .run(tauri::generate_context!())
         .expect("error while running tauri application");
@@ -7,8 +7,16 @@
 //!
 //! Tools exposed (path/file-centric):
 //!   list_roots, list_directory, get_entry, read_file,
-//!   create_file, update_file, create_directory,
-//!   rename_entry, delete_entry, move_entry, search
+//!   create_file, update_file, get_metadata, update_metadata, create_directory,
+//!   rename_entry, delete_entry, move_entry, copy_entry, duplicate_entry,
+//!   import_attachment, export_diagram, search, quick_open,
+//!   get_backlinks, check_links, list_tags, list_tag_documents, rename_tag,
+//!   list_trash, restore_trash_entry, purge_trash,
+//!   list_history, get_revision, diff_revisions, restore_revision
+//!
+//! Creating, renaming and moving never replace an existing entry unless the
+//! client passes `on_collision: "overwrite"`; by default they fail with an
+//! `already_exists` error naming the conflicting path.
 //!
 //! After each write operation, a Tauri event (`mcp-operation`) is emitted
 //! so the frontend can animate the changes in real time.
@@ -22,18 +30,26 @@ use axum::{
     routing::post,
     Json, Router,
 };
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use tauri::{AppHandle, Manager};
 use tower_http::cors::{Any, CorsLayer};
 
+use crate::browser_pool::BrowserPool;
+use crate::converter::{check_chrome_available, mermaid_sources, DiagramFormat};
 use crate::filesystem::FilesystemStorage;
+use crate::history::EditOrigin;
+use crate::storage::{structured_error, CollisionPolicy};
 
 // ── Shared state ─────────────────────────────────────────────────────────────
 
 /// State shared between the MCP HTTP server and the Tauri event system.
 pub struct McpState {
     pub fs: Arc<FilesystemStorage>,
+    /// Headless Chrome shared with the app's own exports.
+    pub browsers: Arc<BrowserPool>,
     pub app_handle: AppHandle,
 }
 
@@ -61,6 +77,8 @@ struct JsonRpcResponse {
 struct JsonRpcError {
     code: i32,
     message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    data: Option<Value>,
 }
 
 impl JsonRpcResponse {
@@ -72,15 +90,35 @@ impl JsonRpcResponse {
             jsonrpc: "2.0".into(),
             id,
             result: None,
-            error: Some(JsonRpcError { code, message: message.into() }),
+            error: Some(JsonRpcError { code, message: message.into(), data: None }),
+        }
+    }
+    /// Error response for a failed tool call. Structured storage errors (e.g. an
+    /// edit conflict) keep their JSON payload in `error.data`.
+    fn tool_err(id: Option<Value>, message: String) -> Self {
+        match structured_error(&message) {
+            Some(data) => {
+                let text = data
+                    .get("message")
+                    .and_then(Value::as_str)
+                    .unwrap_or("Storage error")
+                    .to_string();
+                Self {
+                    jsonrpc: "2.0".into(),
+                    id,
+                    result: None,
+                    error: Some(JsonRpcError { code: -32000, message: text, data: Some(data) }),
+                }
+            }
+            None => Self::err(id, -32000, message),
         }
     }
 }
 
 // ── Router ───────────────────────────────────────────────────────────────────
 
-pub fn build_router(fs: Arc<FilesystemStorage>, app_handle: AppHandle) -> Router {
-    let state = Arc::new(McpState { fs, app_handle });
+pub fn build_router(fs: Arc<FilesystemStorage>, browsers: Arc<BrowserPool>, app_handle: AppHandle) -> Router {
+    let state = Arc::new(McpState { fs, browsers, app_handle });
 
     let cors = CorsLayer::new()
         .allow_methods([Method::POST, Method::OPTIONS])
@@ -158,7 +196,7 @@ fn tools_manifest() -> Value {
         },
         {
             "name": "list_directory",
-            "description": "List the children (folders and .md documents) of a directory",
+            "description": "List the children (folders, documents such as .md or .txt files, and attachments such as images and PDFs) of a directory",
             "inputSchema": {
                 "type": "object",
                 "properties": { "path": { "type": "string", "description": "Absolute path of the directory" } },
@@ -176,7 +214,7 @@ fn tools_manifest() -> Value {
         },
         {
             "name": "read_file",
-            "description": "Read the markdown content of a .md file",
+            "description": "Read the content of a document (.md, .markdown, .txt, ...)",
             "inputSchema": {
                 "type": "object",
                 "properties": { "path": { "type": "string" } },
@@ -185,30 +223,55 @@ fn tools_manifest() -> Value {
         },
         {
             "name": "create_file",
-            "description": "Create a new .md document (extension appended automatically if missing)",
+            "description": "Create a new document (.md is appended unless the name has a recognized document extension such as .markdown or .txt)",
             "inputSchema": {
                 "type": "object",
                 "properties": {
                     "parent_path": { "type": "string" },
                     "name": { "type": "string" },
-                    "content": { "type": "string" }
+                    "content": { "type": "string" },
+                    "on_collision": { "type": "string", "enum": ["fail", "auto_suffix", "overwrite"], "description": "If the name is taken: fail with already_exists (default), use a free name like 'Note (2).md', or move the existing entry to the trash" }
                 },
                 "required": ["parent_path", "name", "content"]
             }
         },
         {
             "name": "update_file",
-            "description": "Update a document's content (and rename it if `name` changed)",
+            "description": "Update a document's content (and rename it if `name` changed). Pass the `revision` from a previous read as `expected_revision` to fail with a conflict (including the current content) instead of overwriting someone else's changes.",
             "inputSchema": {
                 "type": "object",
                 "properties": {
                     "path": { "type": "string" },
                     "name": { "type": "string" },
-                    "content": { "type": "string" }
+                    "content": { "type": "string" },
+                    "expected_revision": { "type": "string", "description": "Revision token from get_entry/list_directory" },
+                    "on_collision": { "type": "string", "enum": ["fail", "auto_suffix", "overwrite"], "description": "If the new name is taken: fail with already_exists (default), use a free name like 'Note (2).md', or move the existing entry to the trash" }
                 },
                 "required": ["path", "name", "content"]
             }
         },
+        {
+            "name": "get_metadata",
+            "description": "Read a document's YAML (---) or TOML (+++) front matter as a JSON object; empty if it has none. Directory listings also include it as `metadata`.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "path": { "type": "string" } },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "update_metadata",
+            "description": "Set front matter keys of a document without touching its body. A null value removes the key. Keeps the existing YAML/TOML format; documents without front matter get a YAML block. Comments inside the front matter are not preserved.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "updates": { "type": "object", "description": "Keys to set, e.g. { \"status\": \"done\", \"tags\": [\"a\"] }" },
+                    "expected_revision": { "type": "string", "description": "Revision token from get_entry/list_directory" }
+                },
+                "required": ["path", "updates"]
+            }
+        },
         {
             "name": "create_directory",
             "description": "Create a new subdirectory inside a parent directory",
@@ -216,26 +279,29 @@ fn tools_manifest() -> Value {
                 "type": "object",
                 "properties": {
                     "parent_path": { "type": "string" },
-                    "name": { "type": "string" }
+                    "name": { "type": "string" },
+                    "on_collision": { "type": "string", "enum": ["fail", "auto_suffix", "overwrite"], "description": "If the name is taken: fail with already_exists (default), use a free name like 'Note (2).md', or move the existing entry to the trash" }
                 },
                 "required": ["parent_path", "name"]
             }
         },
         {
             "name": "rename_entry",
-            "description": "Rename a file or folder (kept in place)",
+            "description": "Rename a file or folder (kept in place). With update_links, documents linking to it (relative links, images, wiki links) are rewritten and listed in updated_links.",
             "inputSchema": {
                 "type": "object",
                 "properties": {
                     "path": { "type": "string" },
-                    "new_name": { "type": "string" }
+                    "new_name": { "type": "string" },
+                    "update_links": { "type": "boolean", "description": "Rewrite links to the entry in other documents (default: false)" },
+                    "on_collision": { "type": "string", "enum": ["fail", "auto_suffix", "overwrite"], "description": "If the new name is taken: fail with already_exists (default), use a free name like 'Note (2).md', or move the existing entry to the trash" }
                 },
                 "required": ["path", "new_name"]
             }
         },
         {
             "name": "delete_entry",
-            "description": "Delete a file or folder (recursive for folders)",
+            "description": "Move a file or folder (recursive for folders) to its workspace trash. Use list_trash/restore_trash_entry to undo.",
             "inputSchema": {
                 "type": "object",
                 "properties": { "path": { "type": "string" } },
@@ -244,7 +310,21 @@ fn tools_manifest() -> Value {
         },
         {
             "name": "move_entry",
-            "description": "Move a file or folder into a new parent directory. Only works within the same volume.",
+            "description": "Move a file or folder into a new parent directory (across volumes it is copied, then the original deleted). With update_links, documents linking to it (and its own relative links) are rewritten and listed in updated_links.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "new_parent_path": { "type": "string" },
+                    "update_links": { "type": "boolean", "description": "Rewrite links to the entry in other documents (default: false)" },
+                    "on_collision": { "type": "string", "enum": ["fail", "auto_suffix", "overwrite"], "description": "If the name is taken in the new parent: fail with already_exists (default), use a free name like 'Note (2).md', or move the existing entry to the trash" }
+                },
+                "required": ["path", "new_parent_path"]
+            }
+        },
+        {
+            "name": "copy_entry",
+            "description": "Copy a file or folder into a directory. The copy keeps its name unless it is taken there, then it is named like 'Note (copy).md'. Returns the new entry.",
             "inputSchema": {
                 "type": "object",
                 "properties": {
@@ -254,14 +334,189 @@ fn tools_manifest() -> Value {
                 "required": ["path", "new_parent_path"]
             }
         },
+        {
+            "name": "duplicate_entry",
+            "description": "Copy a file or folder next to itself under a free name like 'Note (copy).md' or 'Note (copy 2).md'. Returns the new entry.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "path": { "type": "string" } },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "import_attachment",
+            "description": "Save a binary file (image, PDF, audio, video) into the assets folder used by a document, under a name that doesn't overwrite anything (image.png, image-1.png, ...). Returns { entry, markdown } where markdown is the relative link to insert into the document, e.g. ![image](assets/image.png).",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "document_path": { "type": "string", "description": "Absolute path of the document that will link the file" },
+                    "file_name": { "type": "string", "description": "Suggested file name including extension, e.g. diagram.png" },
+                    "data_base64": { "type": "string", "description": "File content, base64-encoded" }
+                },
+                "required": ["document_path", "file_name", "data_base64"]
+            }
+        },
+        {
+            "name": "export_diagram",
+            "description": "Render a document's Mermaid diagrams (```mermaid or :::mermaid blocks, numbered from 0) to SVG or PNG in headless Chrome. Returns a list of { index, svg } (SVG markup) or { index, png_base64 }, or { index, error } with Mermaid's message for a diagram with a syntax error.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "index": { "type": "integer", "description": "Render only this diagram (default: all)" },
+                    "format": { "type": "string", "enum": ["svg", "png"], "description": "Image format (default: svg)" }
+                },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "list_trash",
+            "description": "List deleted entries held in the workspace trash, newest first",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "root_path": { "type": "string", "description": "Limit to one workspace root (default: all)" } },
+                "required": []
+            }
+        },
+        {
+            "name": "restore_trash_entry",
+            "description": "Move a trashed entry back to its original path",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "trash_id": { "type": "string", "description": "`id` from list_trash" },
+                    "root_path": { "type": "string" }
+                },
+                "required": ["trash_id"]
+            }
+        },
+        {
+            "name": "purge_trash",
+            "description": "Permanently delete one trashed entry, or the whole trash when `trash_id` is omitted",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "trash_id": { "type": "string" },
+                    "root_path": { "type": "string" }
+                },
+                "required": []
+            }
+        },
+        {
+            "name": "list_history",
+            "description": "List the saved revisions of a document, newest first. `origin` shows whether a revision came from the app, an MCP client, or a restore.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "path": { "type": "string" } },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "get_revision",
+            "description": "Read the content of one saved revision of a document",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "revision": { "type": "integer" }
+                },
+                "required": ["path", "revision"]
+            }
+        },
+        {
+            "name": "diff_revisions",
+            "description": "Unified diff between two revisions of a document, or from one revision to the current file when `to` is omitted",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "from": { "type": "integer" },
+                    "to": { "type": "integer" }
+                },
+                "required": ["path", "from"]
+            }
+        },
+        {
+            "name": "restore_revision",
+            "description": "Write an old revision back to the document (recorded as a new revision)",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "path": { "type": "string" },
+                    "revision": { "type": "integer" }
+                },
+                "required": ["path", "revision"]
+            }
+        },
         {
             "name": "search",
-            "description": "Search documents by filename or content across all root folders",
+            "description": "Full-text search across all root folders, ranked by relevance (title and heading matches weigh more). Query syntax: words (all must match; also match as prefixes), \"exact phrase\", OR, AND, -exclude or NOT, (grouping), /regex/ or /regex/i, and field filters title:, heading:, code: (fenced code), tag:, path:, root: (workspace name or path) and modified:>YYYY-MM-DD (also >=, <, <=, =). Syntax errors return an invalid_query error with the character position. Returns { total, offset, results }; each result has name_match, total_matches and matches (the first 20 matching lines: line, column, byte offset, text, highlight ranges in characters, context_before/context_after with context_lines lines, default 1). Page with offset/limit (default limit 50)",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "query": { "type": "string" },
+                    "offset": { "type": "integer" },
+                    "limit": { "type": "integer" },
+                    "context_lines": { "type": "integer" }
+                },
+                "required": ["query"]
+            }
+        },
+        {
+            "name": "quick_open",
+            "description": "Resolve a vague file reference (e.g. 'onboarding doc') to document paths by fuzzy-matching file and folder names, like a ctrl-P finder. Query characters must appear in order; space-separated parts must each match. Recently opened files rank higher; an empty query lists recent files. Returns [{ id, name, root_id, display_path, score, positions }], best first",
             "inputSchema": {
                 "type": "object",
-                "properties": { "query": { "type": "string" } },
+                "properties": {
+                    "query": { "type": "string" },
+                    "limit": { "type": "integer" }
+                },
                 "required": ["query"]
             }
+        },
+        {
+            "name": "get_backlinks",
+            "description": "List links pointing to a document from other documents: [[wiki links]] (resolved by name across all roots, e.g. [[Note]], [[folder/note#heading|alias]]) and relative markdown links. Returns [{ id, name, line, column, text, heading }], one entry per link.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "path": { "type": "string", "description": "Absolute path of the linked document" } },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "check_links",
+            "description": "Check every document below a workspace root or folder for broken links: relative links, images and [[wiki links]] whose target doesn't exist (missing_target) and #anchors or [[note#Heading]] links that match no heading (missing_anchor). Also lists orphans, documents no other document links to. Returns { id, documents, links, problems: [{ id, line, column, kind, link, embed }], orphans }.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "path": { "type": "string", "description": "Absolute path of a root folder or a folder inside one" } },
+                "required": ["path"]
+            }
+        },
+        {
+            "name": "list_tags",
+            "description": "List every tag used across all root folders (front matter `tags` and inline #tags, lowercased) with the number of documents using it. Returns [{ tag, count }] sorted by tag. Use the search tool with `tag:<name>` to combine a tag with other criteria.",
+            "inputSchema": { "type": "object", "properties": {}, "required": [] }
+        },
+        {
+            "name": "list_tag_documents",
+            "description": "List the documents tagged with a tag, including tags nested below it (`project` also returns `project/alpha`). Entries include their front matter as `metadata`.",
+            "inputSchema": {
+                "type": "object",
+                "properties": { "tag": { "type": "string", "description": "Tag name, with or without the leading #" } },
+                "required": ["tag"]
+            }
+        },
+        {
+            "name": "rename_tag",
+            "description": "Rename a tag in every document that uses it, in front matter and inline #tags; nested tags move along (`from/x` becomes `to/x`). Renaming onto an existing tag merges the two. Returns { from, to, changed: [paths], failed: [{ id, error }] }.",
+            "inputSchema": {
+                "type": "object",
+                "properties": {
+                    "from": { "type": "string" },
+                    "to": { "type": "string" }
+                },
+                "required": ["from", "to"]
+            }
         }
     ])
 }
@@ -295,7 +550,7 @@ async fn handle_tool_call(state: Arc<McpState>, id: Option<Value>, params: Value
             id,
             json!({ "content": [{ "type": "text", "text": content }] }),
         ),
-        Err(msg) => JsonRpcResponse::err(id, -32000, msg),
+        Err(msg) => JsonRpcResponse::tool_err(id, msg),
     }
 }
 
@@ -345,7 +600,8 @@ fn run_tool(state: Arc<McpState>, name: &str, args: Value) -> Result<String, Str
             let parent_path = get_str(&args, "parent_path")?;
             let file_name = get_str(&args, "name")?;
             let content = get_str(&args, "content")?;
-            let doc = fs.create_document(&parent_path, &file_name, &content)?;
+            let on_collision = get_collision_policy(&args)?;
+            let doc = fs.create_document(&parent_path, &file_name, &content, on_collision)?;
             let result = serde_json::to_string_pretty(&doc).unwrap();
             emit_event(McpEvent {
                 operation: "create_file".into(),
@@ -359,7 +615,16 @@ fn run_tool(state: Arc<McpState>, name: &str, args: Value) -> Result<String, Str
             let path = get_str(&args, "path")?;
             let file_name = get_str(&args, "name")?;
             let content = get_str(&args, "content")?;
-            let doc = fs.update_document(&path, &file_name, &content)?;
+            let expected_revision = get_opt_str(&args, "expected_revision");
+            let on_collision = get_collision_policy(&args)?;
+            let doc = fs.update_document(
+                &path,
+                &file_name,
+                &content,
+                expected_revision.as_deref(),
+                on_collision,
+                EditOrigin::Mcp,
+            )?;
             let result = serde_json::to_string_pretty(&doc).unwrap();
             emit_event(McpEvent {
                 operation: "update_file".into(),
@@ -369,10 +634,39 @@ fn run_tool(state: Arc<McpState>, name: &str, args: Value) -> Result<String, Str
             Ok(result)
         }
 
+        "get_metadata" => {
+            let path = get_str(&args, "path")?;
+            let metadata = fs.get_metadata(&path)?;
+            Ok(serde_json::to_string_pretty(&metadata).unwrap())
+        }
+
+        "update_metadata" => {
+            let path = get_str(&args, "path")?;
+            let updates = args
+                .get("updates")
+                .and_then(Value::as_object)
+                .ok_or_else(|| "Missing or invalid argument: updates".to_string())?;
+            let expected_revision = get_opt_str(&args, "expected_revision");
+            let doc = fs.update_metadata(
+                &path,
+                updates,
+                expected_revision.as_deref(),
+                EditOrigin::Mcp,
+            )?;
+            let result = serde_json::to_string_pretty(&doc).unwrap();
+            emit_event(McpEvent {
+                operation: "update_metadata".into(),
+                id: doc.id.clone(),
+                name: doc.name.clone(),
+            });
+            Ok(result)
+        }
+
         "create_directory" => {
             let parent_path = get_str(&args, "parent_path")?;
             let dir_name = get_str(&args, "name")?;
-            let folder = fs.create_folder(&parent_path, &dir_name)?;
+            let on_collision = get_collision_policy(&args)?;
+            let folder = fs.create_folder(&parent_path, &dir_name, on_collision)?;
             let result = serde_json::to_string_pretty(&folder).unwrap();
             emit_event(McpEvent {
                 operation: "create_directory".into(),
@@ -385,13 +679,22 @@ fn run_tool(state: Arc<McpState>, name: &str, args: Value) -> Result<String, Str
         "rename_entry" => {
             let path = get_str(&args, "path")?;
             let new_name = get_str(&args, "new_name")?;
-            let entry = fs.rename_entry(&path, &new_name)?;
-            let result = serde_json::to_string_pretty(&entry).unwrap();
+            let update_links = get_opt_bool(&args, "update_links")?.unwrap_or(false);
+            let on_collision = get_collision_policy(&args)?;
+            let moved = fs.rename_entry(&path, &new_name, update_links, on_collision, EditOrigin::Mcp)?;
+            let result = serde_json::to_string_pretty(&moved).unwrap();
             emit_event(McpEvent {
                 operation: "rename_entry".into(),
-                id: entry.id.clone(),
-                name: entry.name.clone(),
+                id: moved.entry.id.clone(),
+                name: moved.entry.name.clone(),
             });
+            for id in &moved.updated_links {
+                emit_event(McpEvent {
+                    operation: "update_file".into(),
+                    id: id.clone(),
+                    name: String::new(),
+                });
+            }
             Ok(result)
         }
 
@@ -409,22 +712,203 @@ fn run_tool(state: Arc<McpState>, name: &str, args: Value) -> Result<String, Str
         "move_entry" => {
             let path = get_str(&args, "path")?;
             let new_parent_path = get_str(&args, "new_parent_path")?;
-            let entry = fs.move_entry(&path, &new_parent_path)?;
-            let result = serde_json::to_string_pretty(&entry).unwrap();
+            let update_links = get_opt_bool(&args, "update_links")?.unwrap_or(false);
+            let on_collision = get_collision_policy(&args)?;
+            let moved = fs.move_entry(&path, &new_parent_path, update_links, on_collision, EditOrigin::Mcp)?;
+            let result = serde_json::to_string_pretty(&moved).unwrap();
             emit_event(McpEvent {
                 operation: "move_entry".into(),
+                id: moved.entry.id.clone(),
+                name: moved.entry.name.clone(),
+            });
+            for id in &moved.updated_links {
+                emit_event(McpEvent {
+                    operation: "update_file".into(),
+                    id: id.clone(),
+                    name: String::new(),
+                });
+            }
+            Ok(result)
+        }
+
+        "copy_entry" => {
+            let path = get_str(&args, "path")?;
+            let new_parent_path = get_str(&args, "new_parent_path")?;
+            let copied = fs.copy_entry(&path, &new_parent_path)?;
+            emit_event(McpEvent {
+                operation: "copy_entry".into(),
+                id: copied.id.clone(),
+                name: copied.name.clone(),
+            });
+            Ok(serde_json::to_string_pretty(&copied).unwrap())
+        }
+
+        "duplicate_entry" => {
+            let path = get_str(&args, "path")?;
+            let copied = fs.duplicate_entry(&path)?;
+            emit_event(McpEvent {
+                operation: "duplicate_entry".into(),
+                id: copied.id.clone(),
+                name: copied.name.clone(),
+            });
+            Ok(serde_json::to_string_pretty(&copied).unwrap())
+        }
+
+        "import_attachment" => {
+            let document_path = get_str(&args, "document_path")?;
+            let file_name = get_str(&args, "file_name")?;
+            let data = BASE64
+                .decode(get_str(&args, "data_base64")?.as_bytes())
+                .map_err(|e| format!("Invalid base64 data: {e}"))?;
+            let imported = fs.import_attachment(&document_path, &file_name, &data)?;
+            emit_event(McpEvent {
+                operation: "import_attachment".into(),
+                id: imported.entry.id.clone(),
+                name: imported.entry.name.clone(),
+            });
+            Ok(serde_json::to_string_pretty(&imported).unwrap())
+        }
+
+        "export_diagram" => {
+            let path = get_str(&args, "path")?;
+            let index = get_opt_u32(&args, "index")?.map(|i| i as usize);
+            let format = DiagramFormat::from_str(&get_opt_str(&args, "format").unwrap_or_else(|| "svg".into()))?;
+            let content = fs
+                .get_entry(&path)?
+                .and_then(|entry| entry.content)
+                .ok_or_else(|| format!("Not a readable file: {path}"))?;
+            check_chrome_available()?;
+            let sources = mermaid_sources(&content);
+            let sources = &sources;
+            let rendered = tokio::runtime::Handle::current().block_on(state.browsers.run(|browser| async move {
+                browser.render_diagrams(sources, index, format).await
+            }))?;
+            let diagrams: Vec<Value> = rendered
+                .into_iter()
+                .map(|diagram| match (diagram.image, format) {
+                    (Ok(image), DiagramFormat::Svg) => {
+                        json!({ "index": diagram.index, "svg": String::from_utf8_lossy(&image) })
+                    }
+                    (Ok(image), DiagramFormat::Png) => {
+                        json!({ "index": diagram.index, "png_base64": BASE64.encode(image) })
+                    }
+                    (Err(error), _) => json!({ "index": diagram.index, "error": error }),
+                })
+                .collect();
+            Ok(serde_json::to_string_pretty(&diagrams).unwrap())
+        }
+
+        "list_trash" => {
+            let root_path = get_opt_str(&args, "root_path");
+            let entries = fs.list_trash(root_path.as_deref())?;
+            Ok(serde_json::to_string_pretty(&entries).unwrap())
+        }
+
+        "restore_trash_entry" => {
+            let trash_id = get_str(&args, "trash_id")?;
+            let root_path = get_opt_str(&args, "root_path");
+            let entry = fs.restore_trash_entry(root_path.as_deref(), &trash_id)?;
+            let result = serde_json::to_string_pretty(&entry).unwrap();
+            emit_event(McpEvent {
+                operation: "restore_trash_entry".into(),
                 id: entry.id.clone(),
                 name: entry.name.clone(),
             });
             Ok(result)
         }
 
+        "purge_trash" => {
+            let trash_id = get_opt_str(&args, "trash_id");
+            let root_path = get_opt_str(&args, "root_path");
+            let purged = fs.purge_trash(root_path.as_deref(), trash_id.as_deref())?;
+            Ok(format!("{{\"purged\": {purged}}}"))
+        }
+
+        "list_history" => {
+            let path = get_str(&args, "path")?;
+            let revisions = fs.list_history(&path)?;
+            Ok(serde_json::to_string_pretty(&revisions).unwrap())
+        }
+
+        "get_revision" => {
+            let path = get_str(&args, "path")?;
+            let revision = get_u32(&args, "revision")?;
+            fs.get_history_revision(&path, revision)
+        }
+
+        "diff_revisions" => {
+            let path = get_str(&args, "path")?;
+            let from = get_u32(&args, "from")?;
+            let to = get_opt_u32(&args, "to")?;
+            fs.diff_history(&path, from, to)
+        }
+
+        "restore_revision" => {
+            let path = get_str(&args, "path")?;
+            let revision = get_u32(&args, "revision")?;
+            let doc = fs.restore_history(&path, revision)?;
+            let result = serde_json::to_string_pretty(&doc).unwrap();
+            emit_event(McpEvent {
+                operation: "restore_revision".into(),
+                id: doc.id.clone(),
+                name: doc.name.clone(),
+            });
+            Ok(result)
+        }
+
         "search" => {
             let query = get_str(&args, "query")?;
-            let results = fs.search(&query)?;
+            let offset = get_opt_u32(&args, "offset")?.map(|n| n as usize);
+            let limit = get_opt_u32(&args, "limit")?.map(|n| n as usize);
+            let context_lines = get_opt_u32(&args, "context_lines")?.map(|n| n as usize);
+            let page = fs.search(&query, offset, limit, context_lines)?;
+            Ok(serde_json::to_string_pretty(&page).unwrap())
+        }
+
+        "quick_open" => {
+            let query = get_str(&args, "query")?;
+            let limit = get_opt_u32(&args, "limit")?.map(|n| n as usize);
+            let results = fs.quick_open(&query, limit)?;
             Ok(serde_json::to_string_pretty(&results).unwrap())
         }
 
+        "get_backlinks" => {
+            let path = get_str(&args, "path")?;
+            let backlinks = fs.get_backlinks(&path)?;
+            Ok(serde_json::to_string_pretty(&backlinks).unwrap())
+        }
+
+        "check_links" => {
+            let path = get_str(&args, "path")?;
+            let report = fs.check_links(&path)?;
+            Ok(serde_json::to_string_pretty(&report).unwrap())
+        }
+
+        "list_tags" => {
+            let tags = fs.list_tags()?;
+            Ok(serde_json::to_string_pretty(&tags).unwrap())
+        }
+
+        "list_tag_documents" => {
+            let tag = get_str(&args, "tag")?;
+            let docs = fs.documents_for_tag(&tag)?;
+            Ok(serde_json::to_string_pretty(&docs).unwrap())
+        }
+
+        "rename_tag" => {
+            let from = get_str(&args, "from")?;
+            let to = get_str(&args, "to")?;
+            let result = fs.rename_tag(&from, &to, EditOrigin::Mcp)?;
+            for id in &result.changed {
+                emit_event(McpEvent {
+                    operation: "rename_tag".into(),
+                    id: id.clone(),
+                    name: String::new(),
+                });
+            }
+            Ok(serde_json::to_string_pretty(&result).unwrap())
+        }
+
         other => Err(format!("Unknown tool: {other}")),
     }
 }
@@ -437,3 +921,41 @@ fn get_str(args: &Value, key: &str) -> Result<String, String> {
         .map(String::from)
         .ok_or_else(|| format!("Missing or invalid argument: {key}"))
 }
+
+fn get_opt_str(args: &Value, key: &str) -> Option<String> {
+    args.get(key).and_then(Value::as_str).map(String::from)
+}
+
+fn get_opt_bool(args: &Value, key: &str) -> Result<Option<bool>, String> {
+    match args.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => v
+            .as_bool()
+            .map(Some)
+            .ok_or_else(|| format!("Missing or invalid argument: {key}")),
+    }
+}
+
+/// The `on_collision` argument; absent means `fail`.
+fn get_collision_policy(args: &Value) -> Result<CollisionPolicy, String> {
+    match args.get("on_collision") {
+        None | Some(Value::Null) => Ok(CollisionPolicy::default()),
+        Some(v) => serde_json::from_value(v.clone())
+            .map_err(|_| "Invalid argument: on_collision must be fail, auto_suffix or overwrite".to_string()),
+    }
+}
+
+fn get_u32(args: &Value, key: &str) -> Result<u32, String> {
+    get_opt_u32(args, key)?.ok_or_else(|| format!("Missing or invalid argument: {key}"))
+}
+
+fn get_opt_u32(args: &Value, key: &str) -> Result<Option<u32>, String> {
+    match args.get(key) {
+        None | Some(Value::Null) => Ok(None),
+        Some(v) => v
+            .as_u64()
+            .and_then(|n| u32::try_from(n).ok())
+            .map(Some)
+            .ok_or_else(|| format!("Missing or invalid argument: {key}")),
+    }
+}
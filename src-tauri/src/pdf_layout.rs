@@ -0,0 +1,165 @@
+//! Page layout of PDF exports.
+//!
+//! `PdfOptions` maps onto Chrome's `Page.printToPDF`. Sizes and margins are
+//! in inches; the defaults reproduce the original export (A4 portrait, 0.5"
+//! margins, backgrounds printed, no header or footer). Named layouts are kept
+//! in `StorageConfig::pdf_presets`.
+//!
+//! Header and footer templates are HTML printed in the top and bottom margin
+//! of every page. Chrome fills elements with these classes:
+//!
+//! ```text
+//! title        the document title
+//! date         the print date
+//! url          the document location
+//! pageNumber   the current page
+//! totalPages   the page count
+//! ```
+//!
+//! e.g. `<div style="font-size:9px; width:100%; text-align:center">
+//! <span class="title"></span> · <span class="date"></span> · Page
+//! <span class="pageNumber"></span> of <span class="totalPages"></span></div>`.
+//! Templates don't inherit the document's styles, so give them a font size,
+//! and leave a margin tall enough to hold them.
+
+use chromiumoxide::cdp::browser_protocol::page::PrintToPdfParams;
+use serde::{Deserialize, Serialize};
+
+/// Template printed in place of a header or footer that isn't set;
+/// Chrome's own default would be used otherwise.
+const EMPTY_TEMPLATE: &str = "<span></span>";
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PaperSize {
+    A3,
+    A4,
+    A5,
+    Letter,
+    Legal,
+    Tabloid,
+    /// Width and height in inches.
+    Custom { width: f64, height: f64 },
+}
+
+impl PaperSize {
+    /// Width and height in inches, portrait.
+    pub fn dimensions(&self) -> (f64, f64) {
+        match *self {
+            PaperSize::A3 => (11.69, 16.54),
+            PaperSize::A4 => (8.27, 11.69),
+            PaperSize::A5 => (5.83, 8.27),
+            PaperSize::Letter => (8.5, 11.0),
+            PaperSize::Legal => (8.5, 14.0),
+            PaperSize::Tabloid => (11.0, 17.0),
+            PaperSize::Custom { width, height } => (width, height),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Orientation {
+    #[default]
+    Portrait,
+    Landscape,
+}
+
+/// Page margins in inches.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct PdfMargins {
+    pub top: f64,
+    pub bottom: f64,
+    pub left: f64,
+    pub right: f64,
+}
+
+impl Default for PdfMargins {
+    fn default() -> Self {
+        PdfMargins { top: 0.5, bottom: 0.5, left: 0.5, right: 0.5 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PdfOptions {
+    pub paper: PaperSize,
+    pub margins: PdfMargins,
+    pub orientation: Orientation,
+    /// Zoom of the page content, 0.1 to 2.
+    pub scale: f64,
+    /// HTML printed at the top of every page (see the module docs).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub header_template: Option<String>,
+    /// HTML printed at the bottom of every page (see the module docs).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub footer_template: Option<String>,
+    /// Print background colors and images, e.g. of code blocks and tables.
+    pub print_background: bool,
+}
+
+impl Default for PdfOptions {
+    fn default() -> Self {
+        PdfOptions {
+            paper: PaperSize::A4,
+            margins: PdfMargins::default(),
+            orientation: Orientation::Portrait,
+            scale: 1.0,
+            header_template: None,
+            footer_template: None,
+            print_background: true,
+        }
+    }
+}
+
+impl PdfOptions {
+    /// Fail with a message naming the first value Chrome can't print with.
+    pub fn validate(&self) -> Result<(), String> {
+        let (width, height) = self.page_size();
+        if !(width.is_finite() && height.is_finite() && width > 0.0 && height > 0.0) {
+            return Err(format!("Invalid paper size {} x {} in", width, height));
+        }
+        let PdfMargins { top, bottom, left, right } = self.margins;
+        if ![top, bottom, left, right].iter().all(|m| m.is_finite() && *m >= 0.0) {
+            return Err("Margins must not be negative".to_string());
+        }
+        if top + bottom >= height || left + right >= width {
+            return Err(format!("Margins leave no room on a {} x {} in page", width, height));
+        }
+        if !(0.1..=2.0).contains(&self.scale) {
+            return Err(format!("Scale must be between 0.1 and 2, got {}", self.scale));
+        }
+        Ok(())
+    }
+
+    /// Width and height in inches, as printed.
+    fn page_size(&self) -> (f64, f64) {
+        let (width, height) = self.paper.dimensions();
+        match self.orientation {
+            Orientation::Portrait => (width, height),
+            Orientation::Landscape => (height, width),
+        }
+    }
+
+    pub fn print_params(&self) -> PrintToPdfParams {
+        // The paper is given in portrait; Chrome swaps it for `landscape`.
+        let (width, height) = self.paper.dimensions();
+        let mut params = PrintToPdfParams::builder()
+            .paper_width(width)
+            .paper_height(height)
+            .landscape(self.orientation == Orientation::Landscape)
+            .margin_top(self.margins.top)
+            .margin_bottom(self.margins.bottom)
+            .margin_left(self.margins.left)
+            .margin_right(self.margins.right)
+            .scale(self.scale)
+            .print_background(self.print_background);
+        if self.header_template.is_some() || self.footer_template.is_some() {
+            params = params
+                .display_header_footer(true)
+                .header_template(self.header_template.as_deref().unwrap_or(EMPTY_TEMPLATE))
+                .footer_template(self.footer_template.as_deref().unwrap_or(EMPTY_TEMPLATE));
+        }
+        params.build()
+    }
+}
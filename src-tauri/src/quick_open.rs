@@ -0,0 +1,202 @@
+//! Fuzzy "quick open" matching over document paths.
+//!
+//! A query matches a path when its characters appear in order (case-insensitive),
+//! fzf style. Whitespace separates query parts that must each match. Matches
+//! score higher when they are consecutive, start a path segment or word, or
+//! fall inside the file name; shorter paths and recently opened files win ties.
+//!
+//! Recently opened files are kept in `<app data>/recent_files.json`.
+
+use std::fs;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::atomic::write_atomic;
+
+/// How many recently opened files are remembered.
+const MAX_RECENT: usize = 200;
+
+const SCORE_MATCH: i32 = 16;
+const BONUS_SEGMENT_START: i32 = 24;
+const BONUS_WORD_START: i32 = 16;
+const BONUS_CAMEL: i32 = 12;
+const BONUS_CONSECUTIVE: i32 = 12;
+const BONUS_FILE_NAME: i32 = 8;
+/// Added for the most recently opened file, decreasing linearly down the list.
+const BONUS_RECENT: i32 = 80;
+
+pub struct RecentFiles {
+    file: PathBuf,
+    paths: Mutex<Vec<String>>,
+}
+
+impl RecentFiles {
+    pub fn load(file: PathBuf) -> Self {
+        let paths = fs::read_to_string(&file)
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default();
+        RecentFiles {
+            file,
+            paths: Mutex::new(paths),
+        }
+    }
+
+    /// Move `path` to the front of the list and persist it.
+    pub fn record(&self, path: &str) -> Result<(), String> {
+        let mut paths = self.paths.lock().map_err(|e| e.to_string())?;
+        paths.retain(|p| p != path);
+        paths.insert(0, path.to_string());
+        paths.truncate(MAX_RECENT);
+        let json = serde_json::to_string(&*paths)
+            .map_err(|e| format!("Failed to serialize recent files: {}", e))?;
+        if let Some(dir) = self.file.parent() {
+            fs::create_dir_all(dir).map_err(|e| format!("Failed to create {}: {}", dir.display(), e))?;
+        }
+        write_atomic(&self.file, json.as_bytes())
+            .map_err(|e| format!("Failed to write recent files: {}", e))
+    }
+
+    /// Recently opened paths, most recent first.
+    pub fn list(&self) -> Vec<String> {
+        self.paths.lock().map(|p| p.clone()).unwrap_or_default()
+    }
+}
+
+/// Recency bonus for the entry at `rank` (0 = most recent) of `len` entries.
+pub fn recency_bonus(rank: usize, len: usize) -> i32 {
+    if len == 0 {
+        return 0;
+    }
+    BONUS_RECENT * (len - rank) as i32 / len as i32
+}
+
+/// Score `candidate` against `query`. Returns the score and the matched
+/// character positions, or None if some query part does not match.
+pub fn fuzzy_match(query: &str, candidate: &str) -> Option<(i32, Vec<usize>)> {
+    let parts: Vec<Vec<char>> = query
+        .split_whitespace()
+        .map(|part| part.chars().map(fold_case).collect())
+        .collect();
+    // Cheap rejection before scoring: most candidates don't contain the query at all.
+    if !parts.iter().all(|part| is_subsequence(part, candidate)) {
+        return None;
+    }
+    let chars: Vec<char> = candidate.chars().collect();
+    let lower: Vec<char> = chars.iter().copied().map(fold_case).collect();
+    let name_start = chars
+        .iter()
+        .rposition(|c| *c == '/' || *c == '\\')
+        .map(|i| i + 1)
+        .unwrap_or(0);
+
+    let mut total = 0;
+    let mut positions: Vec<usize> = Vec::new();
+    for part in &parts {
+        let (score, matched) = match_part(part, &chars, &lower, name_start)?;
+        total += score;
+        positions.extend(matched);
+    }
+    positions.sort_unstable();
+    positions.dedup();
+    // Prefer shorter paths when everything else is equal.
+    total -= (chars.len() / 16) as i32;
+    Some((total, positions))
+}
+
+/// Lowercase one character, with a fast path for ASCII.
+fn fold_case(c: char) -> char {
+    if c.is_ascii() {
+        c.to_ascii_lowercase()
+    } else {
+        c.to_lowercase().next().unwrap_or(c)
+    }
+}
+
+fn is_subsequence(part: &[char], candidate: &str) -> bool {
+    let mut rest = candidate.chars().map(fold_case);
+    part.iter().all(|c| rest.any(|l| l == *c))
+}
+
+/// Best alignment of `part` as a subsequence of `lower` (Smith-Waterman style:
+/// per-character bonuses, a bonus for consecutive characters, a penalty of one
+/// point per skipped character).
+fn match_part(
+    part: &[char],
+    chars: &[char],
+    lower: &[char],
+    name_start: usize,
+) -> Option<(i32, Vec<usize>)> {
+    let (m, n) = (part.len(), lower.len());
+    if m == 0 {
+        return Some((0, Vec::new()));
+    }
+    if m > n {
+        return None;
+    }
+    const NONE: i32 = i32::MIN / 2;
+    let bonus: Vec<i32> = (0..n).map(|j| char_bonus(chars, j, name_start)).collect();
+    // score[i][j]: best score with part[i] matched at position j.
+    let mut score = vec![NONE; m * n];
+    let mut from = vec![usize::MAX; m * n];
+    let at = |i: usize, j: usize| i * n + j;
+
+    for j in 0..n {
+        if lower[j] == part[0] {
+            score[at(0, j)] = SCORE_MATCH + bonus[j];
+        }
+    }
+    for i in 1..m {
+        // Best score[i-1][k] + k over k < j - 1, for the gap penalty (j - k - 1).
+        let mut best_gap = (NONE, usize::MAX);
+        for j in i..n {
+            if j >= 2 && score[at(i - 1, j - 2)] != NONE {
+                let candidate = score[at(i - 1, j - 2)] + (j - 2) as i32;
+                if candidate > best_gap.0 {
+                    best_gap = (candidate, j - 2);
+                }
+            }
+            if lower[j] != part[i] {
+                continue;
+            }
+            let here = SCORE_MATCH + bonus[j];
+            let consecutive = score[at(i - 1, j - 1)];
+            if consecutive != NONE {
+                score[at(i, j)] = consecutive + here + BONUS_CONSECUTIVE;
+                from[at(i, j)] = j - 1;
+            }
+            if best_gap.0 != NONE {
+                let gapped = best_gap.0 - (j as i32 - 1) + here;
+                if gapped > score[at(i, j)] {
+                    score[at(i, j)] = gapped;
+                    from[at(i, j)] = best_gap.1;
+                }
+            }
+        }
+    }
+
+    let (end, best) = (0..n)
+        .map(|j| (j, score[at(m - 1, j)]))
+        .filter(|(_, s)| *s != NONE)
+        .max_by_key(|(j, s)| (*s, *j))?;
+    let mut positions = vec![0; m];
+    let mut j = end;
+    for i in (0..m).rev() {
+        positions[i] = j;
+        if i > 0 {
+            j = from[at(i, j)];
+        }
+    }
+    Some((best, positions))
+}
+
+fn char_bonus(chars: &[char], j: usize, name_start: usize) -> i32 {
+    let in_name = if j >= name_start { BONUS_FILE_NAME } else { 0 };
+    let boundary = match j.checked_sub(1).map(|p| chars[p]) {
+        None | Some('/') | Some('\\') => BONUS_SEGMENT_START,
+        Some(' ') | Some('-') | Some('_') | Some('.') => BONUS_WORD_START,
+        Some(prev) if prev.is_lowercase() && chars[j].is_uppercase() => BONUS_CAMEL,
+        _ => 0,
+    };
+    boundary + in_name
+}
@@ -0,0 +1,108 @@
+//! Confining storage operations to the registered workspace roots.
+//!
+//! Ids are absolute paths supplied by the frontend and by MCP clients, so
+//! every id is checked before it is touched:
+//!
+//! - it must be absolute and must not contain `..`;
+//! - it is canonicalized (for paths that don't exist yet, the deepest
+//!   existing ancestor is), which resolves symlinks;
+//! - the canonical path must lie inside a canonicalized registered root;
+//! - no folder between the root and the entry may be `.git` or the trash
+//!   (`workspace_filter::ALWAYS_HIDDEN`), which are never listed and hold
+//!   files that must not be edited (git config and hooks, trash metadata).
+//!
+//! A symlink inside a root that points outside of every root is therefore
+//! rejected, while one pointing elsewhere inside a root is fine. Names of new
+//! or renamed entries must be a single path component. Violations are
+//! reported as `StorageError::PermissionDenied`.
+
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+
+use crate::storage::StorageError;
+use crate::workspace_filter::ALWAYS_HIDDEN;
+
+fn denied(path: &Path, reason: &str) -> String {
+    StorageError::PermissionDenied {
+        path: path.to_string_lossy().to_string(),
+        reason: reason.to_string(),
+    }
+    .into()
+}
+
+/// `path` with symlinks resolved. A path that doesn't exist yet is resolved
+/// up to its deepest existing ancestor and the rest appended unchanged.
+fn canonical(path: &Path) -> Option<PathBuf> {
+    let mut existing = path;
+    let mut rest: Vec<&std::ffi::OsStr> = Vec::new();
+    loop {
+        if let Ok(resolved) = fs::canonicalize(existing) {
+            return Some(rest.iter().rev().fold(resolved, |acc, part| acc.join(part)));
+        }
+        rest.push(existing.file_name()?);
+        existing = existing.parent()?;
+    }
+}
+
+/// Check that the entry `id` lies inside one of `roots` (or is one) and
+/// return it as a path. The returned path is `id` itself, not its canonical
+/// form, so ids stay stable for callers.
+pub fn confine(id: &str, roots: &[PathBuf]) -> Result<PathBuf, String> {
+    let path = PathBuf::from(id);
+    if !path.is_absolute() {
+        return Err(denied(&path, "is not an absolute path"));
+    }
+    if path.components().any(|c| c == Component::ParentDir) {
+        return Err(denied(&path, "contains `..`"));
+    }
+    let resolved = canonical(&path).ok_or_else(|| denied(&path, "cannot be resolved"))?;
+    let relative = roots
+        .iter()
+        .filter_map(|root| fs::canonicalize(root).ok())
+        .find_map(|root| resolved.strip_prefix(root).ok().map(Path::to_path_buf))
+        .ok_or_else(|| denied(&path, "is outside every workspace root"))?;
+    let reserved = relative.components().any(|c| match c {
+        Component::Normal(name) => ALWAYS_HIDDEN.iter().any(|hidden| name == *hidden),
+        _ => false,
+    });
+    if reserved {
+        return Err(denied(&path, "is inside a folder reserved for git or the trash"));
+    }
+    Ok(path)
+}
+
+/// Like `confine`, for operations that must not apply to a root itself
+/// (rename, move, delete).
+pub fn confine_entry(id: &str, roots: &[PathBuf]) -> Result<PathBuf, String> {
+    let path = confine(id, roots)?;
+    if roots.iter().any(|root| root == &path) {
+        return Err(denied(&path, "is a workspace root"));
+    }
+    Ok(path)
+}
+
+/// `id` if it is exactly one of the registered `roots`.
+pub fn registered_root(id: &str, roots: &[PathBuf]) -> Result<PathBuf, String> {
+    let path = PathBuf::from(id);
+    if roots.contains(&path) {
+        Ok(path)
+    } else {
+        Err(denied(&path, "is not a registered workspace root"))
+    }
+}
+
+/// Check that `name` is usable as a single file or folder name.
+pub fn check_name(name: &str) -> Result<(), String> {
+    let reason = if name.trim().is_empty() {
+        "is empty"
+    } else if name.contains(['/', '\\']) {
+        "contains a path separator"
+    } else if name == "." || name == ".." {
+        "is not a file name"
+    } else if name.contains('\0') {
+        "contains a NUL character"
+    } else {
+        return Ok(());
+    };
+    Err(denied(Path::new(name), reason))
+}
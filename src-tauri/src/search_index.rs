@@ -0,0 +1,825 @@
+//! Persistent full-text index over every workspace root.
+//!
+//! An inverted index maps each term to the documents containing it, with
+//! separate term frequencies for the title (file name and first `# ` heading),
+//! headings, body and fenced code. Queries (see `search_query.rs`) are
+//! evaluated against the index, reading a file only for phrase and regex
+//! clauses, and ranked with BM25, where title and heading occurrences count
+//! `TITLE_BOOST`/`HEADING_BOOST` times.
+//!
+//! The index lives in `<app data>/search_index.json.gz`. `FilesystemStorage`
+//! updates it whenever it writes through the storage layer and the watcher
+//! feeds it on-disk changes; on startup `reconcile` re-reads only the files
+//! whose size or modification time changed since the index was saved.
+//! Entries the workspace hides (ignore files, exclude globs; see
+//! `workspace_filter.rs`) are never indexed.
+
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs;
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::RwLock;
+use std::time::UNIX_EPOCH;
+
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+
+use crate::atomic::write_atomic;
+use crate::front_matter::{self, Metadata};
+use crate::links::{self, LinkRef};
+use crate::search_query::{Field, Matcher, Query};
+use crate::tags;
+use crate::workspace_filter::WorkspaceFilter;
+
+/// Bumped whenever the stored layout or the analysis changes; older files are rebuilt.
+const INDEX_VERSION: u32 = 6;
+
+const TITLE_BOOST: f64 = 5.0;
+const HEADING_BOOST: f64 = 2.5;
+/// Weight of a query term matched only as a prefix of an indexed term.
+const PREFIX_WEIGHT: f64 = 0.6;
+/// Upper bound on indexed terms a single query prefix expands to.
+const MAX_PREFIX_EXPANSIONS: usize = 64;
+const BM25_K1: f64 = 1.2;
+const BM25_B: f64 = 0.75;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct IndexedDoc {
+    path: String,
+    root: String,
+    /// Size and modification time (ns) at indexing time, to detect stale entries.
+    len: u64,
+    mtime_ns: u128,
+    /// Total number of indexed tokens, for BM25 length normalization.
+    length: u32,
+    /// Distinct terms, so the doc's postings can be removed without a full scan.
+    terms: Vec<String>,
+    /// Lowercased tags from front matter and inline `#tags`.
+    tags: Vec<String>,
+    /// Outgoing links, unresolved (see `links.rs`).
+    links: Vec<LinkRef>,
+}
+
+impl IndexedDoc {
+    fn modified_unix(&self) -> u64 {
+        (self.mtime_ns / 1_000_000_000) as u64
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+struct Posting {
+    doc: u32,
+    title: u32,
+    heading: u32,
+    body: u32,
+    code: u32,
+}
+
+impl Posting {
+    fn weighted_tf(&self) -> f64 {
+        (self.body + self.code) as f64
+            + HEADING_BOOST * self.heading as f64
+            + TITLE_BOOST * self.title as f64
+    }
+
+    fn in_field(&self, field: Field) -> bool {
+        match field {
+            Field::Title => self.title > 0,
+            Field::Heading => self.heading > 0,
+            Field::Code => self.code > 0,
+            _ => true,
+        }
+    }
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct IndexData {
+    version: u32,
+    next_id: u32,
+    total_length: u64,
+    docs: HashMap<u32, IndexedDoc>,
+    paths: HashMap<String, u32>,
+    /// Posting lists stay sorted by doc id: ids only grow and new docs are appended.
+    postings: BTreeMap<String, Vec<Posting>>,
+    /// Tag → ids of the documents using it.
+    tags: BTreeMap<String, Vec<u32>>,
+}
+
+/// A ranked match, before it is turned into a `SearchResult`.
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub path: PathBuf,
+    pub score: f64,
+}
+
+pub struct SearchIndex {
+    file: PathBuf,
+    data: RwLock<IndexData>,
+    dirty: AtomicBool,
+}
+
+impl SearchIndex {
+    /// Load the index stored at `file`, or start empty if it is missing,
+    /// unreadable or was written by an older version.
+    pub fn load(file: PathBuf) -> Self {
+        let data = fs::read(&file)
+            .ok()
+            .and_then(|bytes| {
+                let mut json = String::new();
+                GzDecoder::new(bytes.as_slice()).read_to_string(&mut json).ok()?;
+                serde_json::from_str::<IndexData>(&json).ok()
+            })
+            .filter(|d| d.version == INDEX_VERSION)
+            .unwrap_or_else(|| IndexData {
+                version: INDEX_VERSION,
+                ..Default::default()
+            });
+        SearchIndex {
+            file,
+            data: RwLock::new(data),
+            dirty: AtomicBool::new(false),
+        }
+    }
+
+    /// Write the index to disk if it changed since the last save.
+    pub fn save_if_dirty(&self) -> Result<(), String> {
+        if !self.dirty.swap(false, Ordering::AcqRel) {
+            return Ok(());
+        }
+        let json = {
+            let data = self.data.read().map_err(|e| e.to_string())?;
+            serde_json::to_vec(&*data)
+                .map_err(|e| format!("Failed to serialize search index: {}", e))?
+        };
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::fast());
+        let result = encoder
+            .write_all(&json)
+            .and_then(|_| encoder.finish())
+            .and_then(|bytes| {
+                if let Some(dir) = self.file.parent() {
+                    fs::create_dir_all(dir)?;
+                }
+                write_atomic(&self.file, &bytes)
+            })
+            .map_err(|e| format!("Failed to write search index: {}", e));
+        if result.is_err() {
+            self.dirty.store(true, Ordering::Release);
+        }
+        result
+    }
+
+    /// (Re)index one document. Unreadable or vanished files are dropped from the index.
+    pub fn index_file(&self, root: &Path, path: &Path) {
+        let meta = match fs::metadata(path) {
+            Ok(m) if m.is_file() => m,
+            _ => return self.remove_prefix(path),
+        };
+        let content = match fs::read_to_string(path) {
+            Ok(c) => c,
+            Err(_) => return self.remove_prefix(path),
+        };
+        let analyzed = analyze(path, &content);
+        if let Ok(mut data) = self.data.write() {
+            data.remove_doc(&path.to_string_lossy());
+            data.insert_doc(root, path, &meta, analyzed);
+            self.dirty.store(true, Ordering::Release);
+        }
+    }
+
+    /// Index every document below `dir` (or `dir` itself, if it is a
+    /// document) whose size or mtime changed. Entries `filter` hides are
+    /// skipped, and dropped if `dir` itself is hidden.
+    pub fn index_tree(&self, dir: &Path, filter: &mut WorkspaceFilter) {
+        if !filter.contains(dir) {
+            self.remove_prefix(dir);
+            return;
+        }
+        let root = filter.root().to_path_buf();
+        if dir.is_file() {
+            if filter.is_document(dir) && self.is_stale(dir) {
+                self.index_file(&root, dir);
+            }
+            return;
+        }
+        for path in collect_documents(dir, filter) {
+            if self.is_stale(&path) {
+                self.index_file(&root, &path);
+            }
+        }
+    }
+
+    /// Drop a document, or every document below a folder.
+    pub fn remove_prefix(&self, path: &Path) {
+        if let Ok(mut data) = self.data.write() {
+            let doomed: Vec<String> = data
+                .paths
+                .keys()
+                .filter(|p| Path::new(p).starts_with(path))
+                .cloned()
+                .collect();
+            if doomed.is_empty() {
+                return;
+            }
+            for p in doomed {
+                data.remove_doc(&p);
+            }
+            self.dirty.store(true, Ordering::Release);
+        }
+    }
+
+    /// Bring the index in line with the disk: forget documents that vanished
+    /// or whose root is no longer registered, or that are no longer shown as
+    /// documents, and index new and changed ones.
+    pub fn reconcile(&self, roots: &mut [WorkspaceFilter]) {
+        let mut seen: HashSet<String> = HashSet::new();
+        for filter in roots {
+            let root = filter.root().to_path_buf();
+            for path in collect_documents(&root, filter) {
+                seen.insert(path.to_string_lossy().to_string());
+                if self.is_stale(&path) {
+                    self.index_file(&root, &path);
+                }
+            }
+        }
+        if let Ok(mut data) = self.data.write() {
+            let gone: Vec<String> = data
+                .paths
+                .keys()
+                .filter(|p| !seen.contains(*p))
+                .cloned()
+                .collect();
+            if !gone.is_empty() {
+                for p in gone {
+                    data.remove_doc(&p);
+                }
+                self.dirty.store(true, Ordering::Release);
+            }
+        }
+    }
+
+    /// Every indexed document as (path, root).
+    pub fn documents(&self) -> Vec<(PathBuf, PathBuf)> {
+        self.data
+            .read()
+            .map(|data| {
+                data.docs
+                    .values()
+                    .map(|d| (PathBuf::from(&d.path), PathBuf::from(&d.root)))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Outgoing links of every indexed document.
+    pub fn links(&self) -> Vec<(PathBuf, Vec<LinkRef>)> {
+        self.data
+            .read()
+            .map(|data| {
+                data.docs
+                    .values()
+                    .filter(|d| !d.links.is_empty())
+                    .map(|d| (PathBuf::from(&d.path), d.links.clone()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Every tag with the number of documents using it, sorted by tag.
+    pub fn tag_counts(&self) -> Vec<(String, usize)> {
+        self.data
+            .read()
+            .map(|data| {
+                data.tags
+                    .iter()
+                    .map(|(tag, ids)| (tag.clone(), ids.len()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    /// Paths of the documents tagged `tag` (lowercase) or a tag nested below
+    /// it, sorted.
+    pub fn tagged_documents(&self, tag: &str) -> Vec<PathBuf> {
+        let data = match self.data.read() {
+            Ok(d) => d,
+            Err(_) => return Vec::new(),
+        };
+        let mut ids: Vec<u32> = data
+            .tags
+            .range(tag.to_string()..)
+            .take_while(|(t, _)| t.starts_with(tag))
+            .filter(|(t, _)| t.len() == tag.len() || t[tag.len()..].starts_with('/'))
+            .flat_map(|(_, ids)| ids.iter().copied())
+            .collect();
+        ids.sort_unstable();
+        ids.dedup();
+        let mut paths: Vec<PathBuf> = ids
+            .into_iter()
+            .filter_map(|id| data.docs.get(&id))
+            .map(|doc| PathBuf::from(&doc.path))
+            .collect();
+        paths.sort();
+        paths
+    }
+
+    fn is_stale(&self, path: &Path) -> bool {
+        let meta = match fs::metadata(path) {
+            Ok(m) => m,
+            Err(_) => return true,
+        };
+        let data = match self.data.read() {
+            Ok(d) => d,
+            Err(_) => return true,
+        };
+        match data.paths.get(path.to_string_lossy().as_ref()) {
+            Some(id) => match data.docs.get(id) {
+                Some(doc) => doc.len != meta.len() || doc.mtime_ns != mtime_ns(&meta),
+                None => true,
+            },
+            None => true,
+        }
+    }
+
+    /// Evaluate `query` against every indexed document and rank the matches,
+    /// best first. `roots` maps workspace paths to their names for `root:`.
+    /// Returns the total number of matches and the requested page.
+    pub fn search(
+        &self,
+        query: &Query,
+        roots: &[(String, PathBuf)],
+        offset: usize,
+        limit: usize,
+    ) -> (usize, Vec<SearchHit>) {
+        let data = match self.data.read() {
+            Ok(d) => d,
+            Err(_) => return (0, Vec::new()),
+        };
+        let root_names: HashMap<String, &str> = roots
+            .iter()
+            .map(|(name, path)| (path.to_string_lossy().to_string(), name.as_str()))
+            .collect();
+
+        let term_scores: Vec<HashMap<u32, f64>> = query
+            .positive_terms()
+            .iter()
+            .map(|term| data.term_scores(term))
+            .collect();
+
+        let mut ranked: Vec<(u32, f64)> = data
+            .docs
+            .iter()
+            .filter(|(id, doc)| {
+                let mut eval = DocEval {
+                    data: &data,
+                    id: **id,
+                    doc,
+                    root_name: root_names.get(&doc.root).copied().unwrap_or(""),
+                    text: None,
+                };
+                eval.eval(query)
+            })
+            .map(|(id, _)| {
+                let score = term_scores.iter().filter_map(|s| s.get(id)).sum();
+                (*id, score)
+            })
+            .collect();
+
+        ranked.sort_by(|a, b| {
+            let (da, db) = (&data.docs[&a.0], &data.docs[&b.0]);
+            b.1.partial_cmp(&a.1)
+                .unwrap_or(std::cmp::Ordering::Equal)
+                .then_with(|| db.mtime_ns.cmp(&da.mtime_ns))
+                .then_with(|| da.path.cmp(&db.path))
+        });
+        let total = ranked.len();
+        let page = ranked
+            .into_iter()
+            .skip(offset)
+            .take(limit)
+            .map(|(doc, score)| SearchHit {
+                path: PathBuf::from(&data.docs[&doc].path),
+                score,
+            })
+            .collect();
+        (total, page)
+    }
+}
+
+impl IndexData {
+    fn remove_doc(&mut self, path: &str) {
+        let id = match self.paths.remove(path) {
+            Some(id) => id,
+            None => return,
+        };
+        if let Some(doc) = self.docs.remove(&id) {
+            self.total_length = self.total_length.saturating_sub(doc.length as u64);
+            for term in &doc.terms {
+                if let Some(list) = self.postings.get_mut(term) {
+                    list.retain(|p| p.doc != id);
+                    if list.is_empty() {
+                        self.postings.remove(term);
+                    }
+                }
+            }
+            for tag in &doc.tags {
+                if let Some(ids) = self.tags.get_mut(tag) {
+                    ids.retain(|d| *d != id);
+                    if ids.is_empty() {
+                        self.tags.remove(tag);
+                    }
+                }
+            }
+        }
+    }
+
+    fn insert_doc(&mut self, root: &Path, path: &Path, meta: &fs::Metadata, analyzed: Analyzed) {
+        let id = self.next_id;
+        self.next_id = self.next_id.wrapping_add(1);
+
+        let mut length = 0u32;
+        for (term, posting) in &analyzed.terms {
+            length += posting.title + posting.heading + posting.body + posting.code;
+            self.postings
+                .entry(term.clone())
+                .or_default()
+                .push(Posting { doc: id, ..*posting });
+        }
+
+        for tag in &analyzed.tags {
+            self.tags.entry(tag.clone()).or_default().push(id);
+        }
+
+        let key = path.to_string_lossy().to_string();
+        self.total_length += length as u64;
+        self.paths.insert(key.clone(), id);
+        self.docs.insert(
+            id,
+            IndexedDoc {
+                path: key,
+                root: root.to_string_lossy().to_string(),
+                len: meta.len(),
+                mtime_ns: mtime_ns(meta),
+                length,
+                terms: analyzed.terms.into_keys().collect(),
+                tags: analyzed.tags,
+                links: analyzed.links,
+            },
+        );
+    }
+
+    /// BM25 contribution of a query term to every document containing it
+    /// (the best of its exact and prefix matches).
+    fn term_scores(&self, term: &str) -> HashMap<u32, f64> {
+        let mut scores: HashMap<u32, f64> = HashMap::new();
+        if self.docs.is_empty() {
+            return scores;
+        }
+        let doc_count = self.docs.len() as f64;
+        let avg_length = (self.total_length as f64 / doc_count).max(1.0);
+        for (indexed, weight) in self.expand(term) {
+            let postings = &self.postings[indexed];
+            let df = postings.len() as f64;
+            let idf = (1.0 + (doc_count - df + 0.5) / (df + 0.5)).ln();
+            for posting in postings {
+                let length = self.docs.get(&posting.doc).map(|d| d.length).unwrap_or(0) as f64;
+                let tf = posting.weighted_tf();
+                let norm = tf + BM25_K1 * (1.0 - BM25_B + BM25_B * length / avg_length);
+                let score = weight * idf * tf * (BM25_K1 + 1.0) / norm;
+                let best = scores.entry(posting.doc).or_insert(0.0);
+                if score > *best {
+                    *best = score;
+                }
+            }
+        }
+        scores
+    }
+
+    /// True if `doc` contains `word` (exactly or as a prefix) within `field`.
+    fn has_term(&self, doc: u32, word: &str, field: Field) -> bool {
+        self.expand(word).into_iter().any(|(term, _)| {
+            let postings = &self.postings[term];
+            postings
+                .binary_search_by_key(&doc, |p| p.doc)
+                .map(|i| postings[i].in_field(field))
+                .unwrap_or(false)
+        })
+    }
+
+    /// Indexed terms matching a query term: the exact term at full weight and
+    /// (for terms of two or more characters) terms it is a prefix of.
+    fn expand<'a>(&'a self, term: &str) -> Vec<(&'a String, f64)> {
+        let mut matches: Vec<(&String, f64)> = Vec::new();
+        if let Some((key, _)) = self.postings.get_key_value(term) {
+            matches.push((key, 1.0));
+        }
+        if term.chars().count() >= 2 {
+            matches.extend(
+                self.postings
+                    .range::<str, _>((std::ops::Bound::Excluded(term), std::ops::Bound::Unbounded))
+                    .take_while(|(k, _)| k.starts_with(term))
+                    .take(MAX_PREFIX_EXPANSIONS)
+                    .map(|(k, _)| (k, PREFIX_WEIGHT)),
+            );
+        }
+        matches
+    }
+}
+
+// ── Analysis ───────────────────────────────────────────────────────────────────
+
+/// Evaluation state for one document; its file is read at most once, and
+/// only if a phrase or regex clause needs the text.
+struct DocEval<'a> {
+    data: &'a IndexData,
+    id: u32,
+    doc: &'a IndexedDoc,
+    root_name: &'a str,
+    text: Option<Option<String>>,
+}
+
+impl DocEval<'_> {
+    fn eval(&mut self, query: &Query) -> bool {
+        match query {
+            // Cheap index-only clauses first, so most documents never get read.
+            Query::And(items) => {
+                items.iter().filter(|q| !q.needs_content()).all(|q| self.eval(q))
+                    && items.iter().filter(|q| q.needs_content()).all(|q| self.eval(q))
+            }
+            Query::Or(items) => items.iter().any(|q| self.eval(q)),
+            Query::Not(inner) => !self.eval(inner),
+            Query::Modified { from, to } => {
+                let modified = self.doc.modified_unix();
+                from.map(|f| modified >= f).unwrap_or(true) && to.map(|t| modified < t).unwrap_or(true)
+            }
+            Query::Match { field, matcher } => self.eval_match(*field, matcher),
+        }
+    }
+
+    fn eval_match(&mut self, field: Field, matcher: &Matcher) -> bool {
+        match (field, matcher) {
+            (_, Matcher::Word(word)) => self.data.has_term(self.id, word, field),
+            (Field::Tag, Matcher::Literal(tag)) => self.doc.tags.iter().any(|t| {
+                t == tag || t.strip_prefix(tag.as_str()).map(|r| r.starts_with('/')).unwrap_or(false)
+            }),
+            (Field::Tag, Matcher::Regex(re)) => self.doc.tags.iter().any(|t| re.is_match(t)),
+            (Field::Path, Matcher::Literal(path)) => {
+                normalize_path(&self.doc.path).contains(&normalize_path(path))
+            }
+            (Field::Path, Matcher::Regex(re)) => re.is_match(&self.doc.path),
+            (Field::Root, Matcher::Literal(root)) => {
+                self.root_name.to_lowercase() == *root
+                    || self.doc.root.to_lowercase().contains(root.as_str())
+            }
+            (Field::Root, Matcher::Regex(re)) => {
+                re.is_match(self.root_name) || re.is_match(&self.doc.root)
+            }
+            (_, Matcher::Phrase(words)) => self
+                .field_texts(field)
+                .iter()
+                .any(|text| contains_phrase(text, words)),
+            (_, Matcher::Regex(re)) => self.field_texts(field).iter().any(|text| re.is_match(text)),
+            (_, Matcher::Literal(_)) => false,
+        }
+    }
+
+    /// The document's text for `field`, one entry per title/heading.
+    fn field_texts(&mut self, field: Field) -> Vec<String> {
+        let path = Path::new(&self.doc.path);
+        let content = match self
+            .text
+            .get_or_insert_with(|| fs::read_to_string(path).ok())
+        {
+            Some(c) => c.as_str(),
+            None => return Vec::new(),
+        };
+        let stem = file_stem(path);
+        let fields = split_fields(&stem, content);
+        let owned = |lines: Vec<&str>| lines.into_iter().map(String::from).collect();
+        match field {
+            Field::Title => owned(fields.title),
+            Field::Heading => owned(fields.headings),
+            Field::Code => vec![fields.code.join("\n")],
+            _ => vec![stem.clone(), content.to_string()],
+        }
+    }
+}
+
+fn contains_phrase(text: &str, words: &[String]) -> bool {
+    let tokens: Vec<String> = tokenize(text).collect();
+    tokens.windows(words.len()).any(|w| w == words)
+}
+
+fn normalize_path(path: &str) -> String {
+    path.replace('\\', "/").to_lowercase()
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+struct Analyzed {
+    terms: HashMap<String, Posting>,
+    tags: Vec<String>,
+    links: Vec<LinkRef>,
+}
+
+/// A document's text split by field. The first `# ` heading is both the
+/// title and a heading; fenced code only counts as code.
+struct DocFields<'a> {
+    title: Vec<&'a str>,
+    headings: Vec<&'a str>,
+    body: Vec<&'a str>,
+    code: Vec<&'a str>,
+    front_matter: Vec<&'a str>,
+}
+
+/// How a line of a document is indexed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LineKind {
+    /// The first `# ` heading.
+    Title,
+    Heading,
+    Body,
+    Code,
+    /// A ``` or ~~~ fence line.
+    Fence,
+    /// A line of the leading YAML/TOML front matter block, fences included.
+    FrontMatter,
+}
+
+/// Kind of every line of `content`, in `str::lines` order.
+pub fn line_kinds(content: &str) -> Vec<LineKind> {
+    let body = front_matter::body(content);
+    let header = &content[..content.len() - body.len()];
+    let mut kinds = vec![LineKind::FrontMatter; header.lines().count()];
+    let mut fence: Option<&str> = None;
+    let mut seen_title = false;
+    for line in body.lines() {
+        let trimmed = line.trim_start();
+        let kind = if let Some(marker) = fence {
+            if trimmed.starts_with(marker) {
+                fence = None;
+                LineKind::Fence
+            } else {
+                LineKind::Code
+            }
+        } else if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            fence = Some(&trimmed[..3]);
+            LineKind::Fence
+        } else {
+            match heading_text(trimmed) {
+                Some((1, _)) if !seen_title => {
+                    seen_title = true;
+                    LineKind::Title
+                }
+                Some(_) => LineKind::Heading,
+                None => LineKind::Body,
+            }
+        };
+        kinds.push(kind);
+    }
+    kinds
+}
+
+fn split_fields<'a>(stem: &'a str, content: &'a str) -> DocFields<'a> {
+    let mut fields = DocFields {
+        title: vec![stem],
+        headings: Vec::new(),
+        body: Vec::new(),
+        code: Vec::new(),
+        front_matter: Vec::new(),
+    };
+    for (line, kind) in content.lines().zip(line_kinds(content)) {
+        let heading = || heading_text(line.trim_start()).map(|(_, t)| t).unwrap_or("");
+        match kind {
+            LineKind::Title => {
+                fields.title.push(heading());
+                fields.headings.push(heading());
+            }
+            LineKind::Heading => fields.headings.push(heading()),
+            LineKind::Body => fields.body.push(line),
+            LineKind::Code => fields.code.push(line),
+            LineKind::FrontMatter => fields.front_matter.push(line),
+            LineKind::Fence => {}
+        }
+    }
+    fields
+}
+
+/// Count title, heading, body and code tokens and collect tags.
+fn analyze(path: &Path, content: &str) -> Analyzed {
+    let stem = file_stem(path);
+    let fields = split_fields(&stem, content);
+    let mut terms: HashMap<String, Posting> = HashMap::new();
+    let mut add = |lines: &[&str], field: fn(&mut Posting)| {
+        for line in lines {
+            for token in tokenize(line) {
+                field(terms.entry(token).or_default());
+            }
+        }
+    };
+    add(&fields.title, |p| p.title += 1);
+    add(&fields.headings, |p| p.heading += 1);
+    add(&fields.body, |p| p.body += 1);
+    add(&fields.code, |p| p.code += 1);
+    // Front matter values are searchable like body text; its `title` also
+    // counts as a title.
+    add(&fields.front_matter, |p| p.body += 1);
+    let metadata = front_matter::metadata(content).unwrap_or_default();
+    if let Some(title) = front_matter::title(&metadata) {
+        add(&[title], |p| p.title += 1);
+    }
+
+    Analyzed {
+        terms,
+        tags: extract_tags(&metadata, &fields.body),
+        links: links::link_refs(path, content),
+    }
+}
+
+/// Tags from the front matter `tags` key (a list or comma-separated string)
+/// and inline `#tag` words in body text. Nested tags keep their `/`.
+fn extract_tags(metadata: &Metadata, body: &[&str]) -> Vec<String> {
+    let mut tags: Vec<String> = Vec::new();
+    let mut push = |raw: &str| {
+        let tag = raw
+            .trim()
+            .trim_matches(|c| c == '"' || c == '\'' || c == '#')
+            .to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    };
+
+    for tag in front_matter::string_list(metadata, "tags") {
+        push(&tag);
+    }
+
+    for line in body {
+        for (start, end) in tags::inline_tags(line) {
+            push(&line[start..end]);
+        }
+    }
+    tags
+}
+
+/// Level and text of an ATX heading line (`## Text`).
+pub fn heading_text(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if level == 0 || level > 6 {
+        return None;
+    }
+    let rest = &line[level..];
+    if !rest.is_empty() && !rest.starts_with(' ') && !rest.starts_with('\t') {
+        return None;
+    }
+    Some((level, rest.trim().trim_end_matches('#').trim()))
+}
+
+/// Lowercased alphanumeric runs.
+pub fn tokenize(text: &str) -> impl Iterator<Item = String> + '_ {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|t| !t.is_empty())
+        .map(|t| t.to_lowercase())
+}
+
+fn mtime_ns(meta: &fs::Metadata) -> u128 {
+    meta.modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .map(|d| d.as_nanos())
+        .unwrap_or_default()
+}
+
+/// All documents below `dir` that `filter` shows.
+pub fn collect_documents(dir: &Path, filter: &mut WorkspaceFilter) -> Vec<PathBuf> {
+    let mut files = collect_files(dir, filter);
+    files.retain(|p| filter.is_document(p));
+    files
+}
+
+/// All files below `dir` that `filter` shows. `dir` itself is assumed to be shown.
+pub fn collect_files(dir: &Path, filter: &mut WorkspaceFilter) -> Vec<PathBuf> {
+    let mut out = Vec::new();
+    let mut stack = vec![dir.to_path_buf()];
+    while let Some(current) = stack.pop() {
+        let entries = match fs::read_dir(&current) {
+            Ok(e) => e,
+            Err(_) => continue,
+        };
+        for entry in entries.filter_map(Result::ok) {
+            let path = entry.path();
+            match entry.file_type() {
+                Ok(ft) if ft.is_dir() && filter.shows(&path, true) => stack.push(path),
+                Ok(ft) if ft.is_file() && filter.shows(&path, false) => out.push(path),
+                _ => {}
+            }
+        }
+    }
+    out
+}
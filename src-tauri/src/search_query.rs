@@ -0,0 +1,608 @@
+//! Search query language.
+//!
+//! ```text
+//! rust borrow            both words (implicit AND; words also match as prefixes)
+//! rust OR go             either word
+//! rust -unsafe           exclusion (also `NOT unsafe`)
+//! (rust OR go) AND web   grouping
+//! "borrow checker"       phrase
+//! /fn\s+main/  /todo/i   regular expression (`i` = case-insensitive)
+//! title:rust  heading:"getting started"  code:unwrap
+//! tag:project  path:notes/2026  root:Work   (tag:/root: also take a /regex/)
+//! modified:>2026-01-01   also >=, <, <= and = (a whole day)
+//! ```
+//!
+//! `parse` turns the query into a `Query` tree; `SearchIndex` evaluates it.
+//! Errors carry the character position of the problem.
+
+use regex::{Regex, RegexBuilder};
+
+use crate::search_index::{line_kinds, LineKind};
+use crate::storage::{HighlightRange, SearchMatch};
+
+/// Part of a document a clause applies to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Field {
+    /// Title, headings, body and code.
+    Any,
+    Title,
+    Heading,
+    Code,
+    Tag,
+    Path,
+    Root,
+}
+
+impl Field {
+    fn from_name(name: &str) -> Option<Field> {
+        match name {
+            "title" => Some(Field::Title),
+            "heading" => Some(Field::Heading),
+            "code" => Some(Field::Code),
+            "tag" => Some(Field::Tag),
+            "path" => Some(Field::Path),
+            "root" => Some(Field::Root),
+            _ => None,
+        }
+    }
+
+    /// Fields matched against the document's words rather than a literal string.
+    pub fn is_text(self) -> bool {
+        matches!(self, Field::Any | Field::Title | Field::Heading | Field::Code)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    /// A single lowercased word.
+    Word(String),
+    /// Consecutive lowercased words.
+    Phrase(Vec<String>),
+    /// Lowercased text for `tag:`, `path:` and `root:`.
+    Literal(String),
+    Regex(Regex),
+}
+
+#[derive(Debug, Clone)]
+pub enum Query {
+    Match { field: Field, matcher: Matcher },
+    /// Modification time within `[from, to)`, in unix seconds.
+    Modified { from: Option<u64>, to: Option<u64> },
+    And(Vec<Query>),
+    Or(Vec<Query>),
+    Not(Box<Query>),
+}
+
+impl Query {
+    /// True if evaluating the query needs the document text, not just the index.
+    pub fn needs_content(&self) -> bool {
+        match self {
+            Query::Match { field, matcher } => {
+                field.is_text() && matches!(matcher, Matcher::Phrase(_) | Matcher::Regex(_))
+            }
+            Query::Modified { .. } => false,
+            Query::And(items) | Query::Or(items) => items.iter().any(Query::needs_content),
+            Query::Not(inner) => inner.needs_content(),
+        }
+    }
+
+    /// Words the query asks for (outside any exclusion), used for ranking.
+    pub fn positive_terms(&self) -> Vec<String> {
+        let mut out = Vec::new();
+        for (_, matcher) in self.text_clauses() {
+            match matcher {
+                Matcher::Word(w) => out.push(w.clone()),
+                Matcher::Phrase(words) => out.extend(words.iter().cloned()),
+                _ => {}
+            }
+        }
+        out
+    }
+
+    /// Text clauses outside any exclusion: what gets highlighted.
+    pub fn text_clauses(&self) -> Vec<(Field, &Matcher)> {
+        let mut out = Vec::new();
+        self.collect_clauses(&mut out);
+        out
+    }
+
+    fn collect_clauses<'a>(&'a self, out: &mut Vec<(Field, &'a Matcher)>) {
+        match self {
+            Query::Match { field, matcher } if field.is_text() => out.push((*field, matcher)),
+            Query::And(items) | Query::Or(items) => {
+                items.iter().for_each(|q| q.collect_clauses(out))
+            }
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct QueryError {
+    /// Character offset (0-based) of the problem in the query string.
+    pub position: usize,
+    pub message: String,
+}
+
+impl QueryError {
+    fn new(position: usize, message: impl Into<String>) -> Self {
+        QueryError {
+            position,
+            message: message.into(),
+        }
+    }
+}
+
+/// Parse a query string. An empty (or all-whitespace) query yields `Ok(None)`.
+pub fn parse(input: &str) -> Result<Option<Query>, QueryError> {
+    let tokens = lex(input)?;
+    if tokens.is_empty() {
+        return Ok(None);
+    }
+    let mut parser = Parser {
+        tokens,
+        pos: 0,
+        end: input.chars().count(),
+    };
+    let query = parser.parse_or()?;
+    if let Some(tok) = parser.tokens.get(parser.pos) {
+        return Err(QueryError::new(tok.pos, "Unexpected ')'"));
+    }
+    Ok(Some(query))
+}
+
+// ── Lexer ──────────────────────────────────────────────────────────────────────
+
+#[derive(Debug, Clone)]
+enum Tok {
+    LParen,
+    RParen,
+    Not,
+    And,
+    Or,
+    Field(Field, String),
+    Text(String),
+    Phrase(String),
+    Regex(Regex),
+    Modified(Option<u64>, Option<u64>),
+}
+
+#[derive(Debug, Clone)]
+struct Spanned {
+    tok: Tok,
+    pos: usize,
+}
+
+fn lex(input: &str) -> Result<Vec<Spanned>, QueryError> {
+    let chars: Vec<char> = input.chars().collect();
+    let mut tokens = Vec::new();
+    let mut i = 0;
+    // Set right after `field:`, where the value must follow without a space.
+    let mut after_field: Option<(usize, String)> = None;
+
+    while i < chars.len() {
+        let c = chars[i];
+        if c.is_whitespace() {
+            if let Some((pos, name)) = after_field.take() {
+                return Err(QueryError::new(pos, format!("Expected a value after '{}:'", name)));
+            }
+            i += 1;
+            continue;
+        }
+        let start = i;
+        let field_name = after_field.take().map(|(_, name)| name);
+        let value_expected = field_name.is_some();
+        match c {
+            '(' | ')' if !value_expected => {
+                let tok = if c == '(' { Tok::LParen } else { Tok::RParen };
+                tokens.push(Spanned { tok, pos: start });
+                i += 1;
+            }
+            '-' if !value_expected
+                && chars.get(i + 1).map(|n| !n.is_whitespace()).unwrap_or(false) =>
+            {
+                tokens.push(Spanned { tok: Tok::Not, pos: start });
+                i += 1;
+            }
+            '"' => {
+                let close = (i + 1..chars.len()).find(|&j| chars[j] == '"');
+                let close = close.ok_or_else(|| QueryError::new(start, "Unterminated phrase"))?;
+                let text: String = chars[i + 1..close].iter().collect();
+                tokens.push(Spanned { tok: Tok::Phrase(text), pos: start });
+                i = close + 1;
+            }
+            // Paths start with slashes too, so `path:` values are always literal.
+            '/' if field_name.as_deref() != Some("path") => {
+                let mut j = i + 1;
+                let mut pattern = String::new();
+                while j < chars.len() && chars[j] != '/' {
+                    if chars[j] == '\\' && chars.get(j + 1) == Some(&'/') {
+                        j += 1;
+                    }
+                    pattern.push(chars[j]);
+                    j += 1;
+                }
+                if j >= chars.len() {
+                    return Err(QueryError::new(start, "Unterminated regular expression"));
+                }
+                if pattern.is_empty() {
+                    return Err(QueryError::new(start, "Empty regular expression"));
+                }
+                j += 1;
+                let case_insensitive = chars.get(j) == Some(&'i')
+                    && chars.get(j + 1).map(|n| n.is_whitespace() || *n == ')').unwrap_or(true);
+                if case_insensitive {
+                    j += 1;
+                }
+                let regex = RegexBuilder::new(&pattern)
+                    .case_insensitive(case_insensitive)
+                    .multi_line(true)
+                    .build()
+                    .map_err(|e| {
+                        QueryError::new(start, format!("Invalid regular expression: {}", e))
+                    })?;
+                tokens.push(Spanned { tok: Tok::Regex(regex), pos: start });
+                i = j;
+            }
+            _ => {
+                let mut j = i;
+                while j < chars.len() && !chars[j].is_whitespace() && chars[j] != '(' && chars[j] != ')' {
+                    if chars[j] == ':' && !value_expected {
+                        break;
+                    }
+                    j += 1;
+                }
+                if j == i {
+                    // Only reachable for `field:(` or `field:)`.
+                    return Err(QueryError::new(start, "Expected a value after ':'"));
+                }
+                let word: String = chars[i..j].iter().collect();
+
+                if j < chars.len() && chars[j] == ':' {
+                    let name = word.to_lowercase();
+                    if name == "modified" {
+                        let mut k = j + 1;
+                        while k < chars.len() && !chars[k].is_whitespace() && chars[k] != ')' {
+                            k += 1;
+                        }
+                        let value: String = chars[j + 1..k].iter().collect();
+                        let (from, to) = parse_modified(&value, j + 1)?;
+                        tokens.push(Spanned { tok: Tok::Modified(from, to), pos: start });
+                        i = k;
+                        continue;
+                    }
+                    if let Some(field) = Field::from_name(&name) {
+                        tokens.push(Spanned { tok: Tok::Field(field, name.clone()), pos: start });
+                        after_field = Some((start, name));
+                        i = j + 1;
+                        continue;
+                    }
+                    // Not a field (e.g. a URL): keep the colon as part of the word.
+                    j += 1;
+                    while j < chars.len() && !chars[j].is_whitespace() && chars[j] != '(' && chars[j] != ')' {
+                        j += 1;
+                    }
+                }
+
+                let word: String = chars[i..j].iter().collect();
+                let tok = match word.as_str() {
+                    "AND" if !value_expected => Tok::And,
+                    "OR" if !value_expected => Tok::Or,
+                    "NOT" if !value_expected => Tok::Not,
+                    _ => Tok::Text(word),
+                };
+                tokens.push(Spanned { tok, pos: start });
+                i = j;
+            }
+        }
+    }
+
+    if let Some((pos, name)) = after_field {
+        return Err(QueryError::new(pos, format!("Expected a value after '{}:'", name)));
+    }
+    Ok(tokens)
+}
+
+/// `>2026-01-01` etc. into a `[from, to)` range of unix seconds.
+fn parse_modified(value: &str, pos: usize) -> Result<(Option<u64>, Option<u64>), QueryError> {
+    let (op, date) = [">=", "<=", ">", "<", "="]
+        .iter()
+        .find_map(|op| value.strip_prefix(op).map(|rest| (*op, rest)))
+        .unwrap_or(("=", value));
+    let date_pos = pos + op.len().min(value.len());
+    if date.is_empty() {
+        return Err(QueryError::new(pos, "Expected a date (YYYY-MM-DD) after 'modified:'"));
+    }
+    let day = parse_date(date).ok_or_else(|| {
+        QueryError::new(date_pos, format!("Invalid date '{}', expected YYYY-MM-DD", date))
+    })?;
+    let next = day + 86400;
+    Ok(match op {
+        ">" => (Some(next), None),
+        ">=" => (Some(day), None),
+        "<" => (None, Some(day)),
+        "<=" => (None, Some(next)),
+        _ => (Some(day), Some(next)),
+    })
+}
+
+/// Start of a UTC day as unix seconds.
+fn parse_date(text: &str) -> Option<u64> {
+    let mut parts = text.splitn(3, '-');
+    let y: i64 = parts.next()?.parse().ok()?;
+    let m: i64 = parts.next()?.parse().ok()?;
+    let d: i64 = parts.next()?.parse().ok()?;
+    if !(1..=12).contains(&m) || !(1..=31).contains(&d) || y < 1970 {
+        return None;
+    }
+    // Days from civil (proleptic Gregorian), inverse of `civil_from_days`.
+    let y = if m <= 2 { y - 1 } else { y };
+    let era = y.div_euclid(400);
+    let yoe = y - era * 400;
+    let mp = (m + 9) % 12;
+    let doy = (153 * mp + 2) / 5 + d - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    let days = era * 146097 + doe - 719468;
+    u64::try_from(days * 86400).ok()
+}
+
+// ── Parser ─────────────────────────────────────────────────────────────────────
+
+struct Parser {
+    tokens: Vec<Spanned>,
+    pos: usize,
+    /// Length of the input, reported for errors at the end of the query.
+    end: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Tok> {
+        self.tokens.get(self.pos).map(|t| &t.tok)
+    }
+
+    fn here(&self) -> usize {
+        self.tokens.get(self.pos).map(|t| t.pos).unwrap_or(self.end)
+    }
+
+    fn parse_or(&mut self) -> Result<Query, QueryError> {
+        let mut items = vec![self.parse_and()?];
+        while matches!(self.peek(), Some(Tok::Or)) {
+            self.pos += 1;
+            items.push(self.parse_and()?);
+        }
+        Ok(if items.len() == 1 { items.remove(0) } else { Query::Or(items) })
+    }
+
+    fn parse_and(&mut self) -> Result<Query, QueryError> {
+        let mut items = Vec::new();
+        loop {
+            match self.peek() {
+                None | Some(Tok::RParen) | Some(Tok::Or) => break,
+                Some(Tok::And) => {
+                    if items.is_empty() {
+                        return Err(QueryError::new(self.here(), "Expected a search term before 'AND'"));
+                    }
+                    self.pos += 1;
+                    items.push(self.parse_unary()?);
+                }
+                _ => items.push(self.parse_unary()?),
+            }
+        }
+        match items.len() {
+            0 => Err(QueryError::new(self.here(), match self.peek() {
+                Some(Tok::Or) => "Expected a search term before 'OR'",
+                Some(Tok::RParen) => "Expected a search term before ')'",
+                _ => "Expected a search term",
+            })),
+            1 => Ok(items.remove(0)),
+            _ => Ok(Query::And(items)),
+        }
+    }
+
+    fn parse_unary(&mut self) -> Result<Query, QueryError> {
+        if matches!(self.peek(), Some(Tok::Not)) {
+            self.pos += 1;
+            if self.peek().is_none() {
+                return Err(QueryError::new(self.end, "Expected a search term after '-' or 'NOT'"));
+            }
+            return Ok(Query::Not(Box::new(self.parse_unary()?)));
+        }
+        self.parse_primary()
+    }
+
+    fn parse_primary(&mut self) -> Result<Query, QueryError> {
+        let here = self.here();
+        let tok = match self.tokens.get(self.pos) {
+            Some(t) => t.tok.clone(),
+            None => return Err(QueryError::new(here, "Expected a search term")),
+        };
+        self.pos += 1;
+        match tok {
+            Tok::LParen => {
+                let inner = self.parse_or()?;
+                if !matches!(self.peek(), Some(Tok::RParen)) {
+                    return Err(QueryError::new(here, "Missing ')' for this '('"));
+                }
+                self.pos += 1;
+                Ok(inner)
+            }
+            Tok::Field(field, name) => {
+                let value = self.tokens.get(self.pos).map(|t| t.tok.clone());
+                self.pos += 1;
+                match value {
+                    Some(Tok::Text(text)) | Some(Tok::Phrase(text)) => {
+                        text_match(field, &text, here, &name)
+                    }
+                    Some(Tok::Regex(regex)) => Ok(Query::Match {
+                        field,
+                        matcher: Matcher::Regex(regex),
+                    }),
+                    _ => Err(QueryError::new(here, format!("Expected a value after '{}:'", name))),
+                }
+            }
+            Tok::Text(text) | Tok::Phrase(text) => text_match(Field::Any, &text, here, "text"),
+            Tok::Regex(regex) => Ok(Query::Match {
+                field: Field::Any,
+                matcher: Matcher::Regex(regex),
+            }),
+            Tok::Modified(from, to) => Ok(Query::Modified { from, to }),
+            Tok::RParen => Err(QueryError::new(here, "Unexpected ')'")),
+            Tok::And | Tok::Or | Tok::Not => Err(QueryError::new(here, "Expected a search term")),
+        }
+    }
+}
+
+fn text_match(field: Field, text: &str, pos: usize, name: &str) -> Result<Query, QueryError> {
+    let matcher = if field.is_text() {
+        let mut words: Vec<String> = crate::search_index::tokenize(text).collect();
+        match words.len() {
+            0 => {
+                return Err(QueryError::new(
+                    pos,
+                    format!("'{}' contains no searchable characters", text),
+                ))
+            }
+            1 => Matcher::Word(words.remove(0)),
+            _ => Matcher::Phrase(words),
+        }
+    } else {
+        let literal = text.trim().to_lowercase();
+        if literal.is_empty() {
+            return Err(QueryError::new(pos, format!("Expected a value after '{}:'", name)));
+        }
+        Matcher::Literal(literal)
+    };
+    Ok(Query::Match { field, matcher })
+}
+
+// ── Match locations ────────────────────────────────────────────────────────────
+
+/// Whether the file name `stem` matches, the first `max_matches` lines of
+/// `content` hit by the query's text clauses, with `context` lines around
+/// each, and how many lines were hit in all.
+pub fn find_matches(
+    query: &Query,
+    stem: &str,
+    content: &str,
+    context: usize,
+    max_matches: usize,
+) -> (bool, Vec<SearchMatch>, usize) {
+    let clauses = query.text_clauses();
+    let name_match = clauses
+        .iter()
+        .filter(|(field, _)| matches!(field, Field::Any | Field::Title))
+        .any(|(_, matcher)| !line_highlights(stem, matcher).is_empty());
+
+    let lines: Vec<&str> = content.lines().collect();
+    let kinds = line_kinds(content);
+    let mut matches = Vec::new();
+    let mut total = 0;
+    let mut line_start = 0;
+
+    for (i, (line, kind)) in lines.iter().zip(kinds).enumerate() {
+        let start = line_start;
+        line_start += content[start..].find('\n').map(|n| n + 1).unwrap_or(content.len() - start);
+
+        let mut ranges: Vec<HighlightRange> = clauses
+            .iter()
+            .filter(|(field, _)| applies_to(*field, kind))
+            .flat_map(|(_, matcher)| line_highlights(line, matcher))
+            .collect();
+        if ranges.is_empty() {
+            continue;
+        }
+        total += 1;
+        if matches.len() >= max_matches {
+            continue;
+        }
+        ranges.sort_by_key(|r| (r.start, r.end));
+        let mut merged: Vec<HighlightRange> = Vec::new();
+        for r in ranges {
+            match merged.last_mut() {
+                Some(last) if r.start <= last.end => last.end = last.end.max(r.end),
+                _ => merged.push(r),
+            }
+        }
+
+        let first = merged[0].start;
+        let byte_in_line = line.char_indices().nth(first).map(|(b, _)| b).unwrap_or(line.len());
+        matches.push(SearchMatch {
+            line: i + 1,
+            column: first + 1,
+            offset: start + byte_in_line,
+            text: line.to_string(),
+            highlights: merged,
+            context_before: lines[i.saturating_sub(context)..i]
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+            context_after: lines[(i + 1).min(lines.len())..(i + 1 + context).min(lines.len())]
+                .iter()
+                .map(|l| l.to_string())
+                .collect(),
+        });
+    }
+    (name_match, matches, total)
+}
+
+fn applies_to(field: Field, kind: LineKind) -> bool {
+    match field {
+        Field::Title => kind == LineKind::Title,
+        Field::Heading => matches!(kind, LineKind::Title | LineKind::Heading),
+        Field::Code => kind == LineKind::Code,
+        _ => kind != LineKind::Fence,
+    }
+}
+
+/// Character ranges of `line` that `matcher` hits.
+fn line_highlights(line: &str, matcher: &Matcher) -> Vec<HighlightRange> {
+    match matcher {
+        Matcher::Word(word) => token_spans(line)
+            .into_iter()
+            .filter(|(_, _, token)| {
+                token == word || (word.chars().count() >= 2 && token.starts_with(word.as_str()))
+            })
+            .map(|(start, end, _)| HighlightRange { start, end })
+            .collect(),
+        Matcher::Phrase(words) => {
+            let spans = token_spans(line);
+            spans
+                .windows(words.len())
+                .filter(|w| w.iter().zip(words).all(|((_, _, token), word)| token == word))
+                .map(|w| HighlightRange {
+                    start: w[0].0,
+                    end: w[w.len() - 1].1,
+                })
+                .collect()
+        }
+        Matcher::Regex(re) => re
+            .find_iter(line)
+            .filter(|m| !m.is_empty())
+            .map(|m| HighlightRange {
+                start: line[..m.start()].chars().count(),
+                end: line[..m.end()].chars().count(),
+            })
+            .collect(),
+        Matcher::Literal(_) => Vec::new(),
+    }
+}
+
+/// Lowercased words of `line` with their character ranges, split like
+/// `search_index::tokenize`.
+fn token_spans(line: &str) -> Vec<(usize, usize, String)> {
+    let mut spans = Vec::new();
+    let mut current: Option<(usize, String)> = None;
+    let mut count = 0;
+    for (i, c) in line.chars().enumerate() {
+        if c.is_alphanumeric() {
+            current.get_or_insert_with(|| (i, String::new())).1.extend(c.to_lowercase());
+        } else if let Some((start, token)) = current.take() {
+            spans.push((start, i, token));
+        }
+        count = i + 1;
+    }
+    if let Some((start, token)) = current {
+        spans.push((start, count, token));
+    }
+    spans
+}
@@ -1,10 +1,15 @@
 use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+use crate::front_matter::Metadata;
 
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 #[serde(rename_all = "lowercase")]
 pub enum TreeNodeKind {
     Folder,
     Document,
+    /// An image, PDF or other asset file (see `attachments.rs`).
+    Attachment,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -17,6 +22,13 @@ pub struct TreeNode {
     pub content: Option<String>,
     pub created_at: String,
     pub updated_at: String,
+    /// Opaque token identifying the on-disk version of a document. Pass it back
+    /// as `expected_revision` when updating to detect concurrent edits.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+    /// Parsed YAML/TOML front matter of a document, if it has a valid block.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<Metadata>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -27,5 +39,261 @@ pub struct SearchResult {
     pub kind: TreeNodeKind,
     pub created_at: String,
     pub updated_at: String,
-    pub matched_line: String,
+    /// BM25 relevance; results are ordered by descending score.
+    pub score: f64,
+    /// True if the file name matched the query.
+    pub name_match: bool,
+    /// The first 20 lines hit by the query's words, phrases or regexes
+    /// (`filesystem::MAX_MATCHES_PER_RESULT`).
+    pub matches: Vec<SearchMatch>,
+    /// How many lines were hit in all.
+    pub total_matches: usize,
+}
+
+/// One matching line within a search result.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchMatch {
+    /// 1-based line number.
+    pub line: usize,
+    /// 1-based column (in characters) of the first highlight.
+    pub column: usize,
+    /// Byte offset of the first highlight from the start of the document.
+    pub offset: usize,
+    /// The full line, without its line break.
+    pub text: String,
+    /// Highlighted spans of `text`, sorted and non-overlapping.
+    pub highlights: Vec<HighlightRange>,
+    pub context_before: Vec<String>,
+    pub context_after: Vec<String>,
+}
+
+/// Character offsets (0-based, end exclusive) within a line.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct HighlightRange {
+    pub start: usize,
+    pub end: usize,
+}
+
+/// A fuzzy file name match from `quick_open`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuickOpenResult {
+    pub id: String,
+    pub name: String,
+    pub root_id: String,
+    /// Workspace name followed by the path inside the root, e.g. `Notes/guides/onboarding.md`.
+    pub display_path: String,
+    pub score: i32,
+    /// Matched character positions in `display_path`, for highlighting.
+    pub positions: Vec<usize>,
+}
+
+/// A file imported into a workspace's assets folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportedAttachment {
+    pub entry: TreeNode,
+    /// Link to the file relative to the document, ready to insert
+    /// (`![image](assets/image.png)`).
+    pub markdown: String,
+}
+
+/// A link to a document from another document.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Backlink {
+    /// Path of the linking document.
+    pub id: String,
+    pub name: String,
+    /// 1-based position of the link in the linking document.
+    pub line: usize,
+    pub column: usize,
+    /// The line containing the link.
+    pub text: String,
+    /// Heading of the target the link points at, if any.
+    pub heading: Option<String>,
+}
+
+/// Result of checking the links of every document in a folder.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkReport {
+    /// The checked folder.
+    pub id: String,
+    /// Number of documents checked.
+    pub documents: usize,
+    /// Number of links and images checked.
+    pub links: usize,
+    /// Broken links, ordered by document and position.
+    pub problems: Vec<LinkProblem>,
+    /// Documents in the folder that no other document links to.
+    pub orphans: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum LinkProblemKind {
+    /// The linked document or file doesn't exist.
+    MissingTarget,
+    /// The target exists but has no heading matching the `#anchor`.
+    MissingAnchor,
+}
+
+/// A broken link found by the link checker.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LinkProblem {
+    /// Path of the document containing the link.
+    pub id: String,
+    /// 1-based position of the link.
+    pub line: usize,
+    pub column: usize,
+    pub kind: LinkProblemKind,
+    /// The link as written, e.g. `[intro](../old.md#intro)`.
+    pub link: String,
+    /// True for images and other embeds.
+    pub embed: bool,
+}
+
+/// A tag and the number of documents using it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagCount {
+    pub tag: String,
+    pub count: usize,
+}
+
+/// Outcome of renaming (or merging) a tag across all documents.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TagRenameResult {
+    pub from: String,
+    pub to: String,
+    /// Documents that were rewritten.
+    pub changed: Vec<String>,
+    /// Documents that use the tag but could not be rewritten.
+    pub failed: Vec<UpdateFailure>,
+}
+
+/// A document that should have been rewritten but couldn't be.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateFailure {
+    pub id: String,
+    pub error: String,
+}
+
+/// An entry after a rename or move. Serializes as the entry itself, plus
+/// the documents whose links were rewritten to follow it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MovedEntry {
+    #[serde(flatten)]
+    pub entry: TreeNode,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub updated_links: Vec<String>,
+    /// Documents linking to the entry that could not be rewritten.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub link_failures: Vec<UpdateFailure>,
+}
+
+/// What a create, rename or move does when an entry already exists at its
+/// destination.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum CollisionPolicy {
+    /// Fail with `StorageError::AlreadyExists`.
+    #[default]
+    Fail,
+    /// Use the first free numbered name instead: `Note (2).md`, `Note (3).md`, ...
+    AutoSuffix,
+    /// Move the existing entry to the trash and take its place.
+    Overwrite,
+}
+
+/// One page of ranked search results.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SearchPage {
+    /// Number of matching documents across all pages.
+    pub total: usize,
+    pub offset: usize,
+    pub results: Vec<SearchResult>,
+}
+
+/// Storage failures that callers must be able to tell apart from plain I/O
+/// errors. They travel through the existing `Result<_, String>` plumbing as a
+/// JSON object with a `code` discriminator and a human-readable `message`, so
+/// both the frontend and MCP clients can parse them back.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "code", rename_all = "snake_case")]
+pub enum StorageError {
+    /// The document changed on disk since the caller last read it.
+    Conflict {
+        id: String,
+        expected_revision: String,
+        current_revision: String,
+        current_content: String,
+    },
+    /// The search query could not be parsed.
+    InvalidQuery {
+        query: String,
+        /// Character offset (0-based) of the problem.
+        position: usize,
+        reason: String,
+    },
+    /// The path or name would reach outside the registered workspace roots.
+    PermissionDenied { path: String, reason: String },
+    /// An entry already exists where one was to be created, renamed or moved.
+    AlreadyExists { path: String },
+    /// Mermaid could not render a diagram of the document.
+    InvalidDiagram {
+        /// 0-based position of the diagram among the document's Mermaid blocks.
+        index: usize,
+        /// Mermaid's error message.
+        reason: String,
+    },
+    /// An export's page didn't finish rendering in time.
+    RenderTimeout {
+        timeout_secs: u64,
+        /// What was still loading, e.g. `fonts`, `image logo.png` or `Mermaid diagram 2`.
+        pending: Vec<String>,
+    },
+}
+
+impl StorageError {
+    pub fn message(&self) -> String {
+        match self {
+            StorageError::Conflict { id, .. } => {
+                format!("Edit conflict: {} was modified since it was last read", id)
+            }
+            StorageError::InvalidQuery { position, reason, .. } => {
+                format!("Invalid search query at position {}: {}", position, reason)
+            }
+            StorageError::PermissionDenied { path, reason } => {
+                format!("Permission denied: {} {}", path, reason)
+            }
+            StorageError::AlreadyExists { path } => format!("{} already exists", path),
+            StorageError::InvalidDiagram { index, reason } => {
+                format!("Diagram {} could not be rendered: {}", index + 1, reason)
+            }
+            StorageError::RenderTimeout { timeout_secs, pending } if pending.is_empty() => {
+                format!("Timed out after {}s waiting for the page to render", timeout_secs)
+            }
+            StorageError::RenderTimeout { timeout_secs, pending } => {
+                format!("Timed out after {}s waiting for {}", timeout_secs, pending.join(", "))
+            }
+        }
+    }
+}
+
+impl From<StorageError> for String {
+    fn from(err: StorageError) -> String {
+        let mut value = serde_json::to_value(&err).unwrap_or(Value::Null);
+        if let Value::Object(map) = &mut value {
+            map.insert("message".into(), Value::String(err.message()));
+        }
+        value.to_string()
+    }
+}
+
+/// Parse an error string produced from a `StorageError` back into JSON.
+/// Returns None for ordinary error messages.
+pub fn structured_error(message: &str) -> Option<Value> {
+    if !message.starts_with('{') {
+        return None;
+    }
+    serde_json::from_str::<Value>(message)
+        .ok()
+        .filter(|v| v.get("code").and_then(Value::as_str).is_some())
 }
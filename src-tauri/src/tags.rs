@@ -0,0 +1,122 @@
+//! Tag names and tag rewriting inside documents.
+//!
+//! A document's tags come from its front matter `tags` key and from inline
+//! `#tag` words in body text (not code or headings). Tags are compared
+//! case-insensitively and may be nested with `/` (`project/alpha`); acting on
+//! a tag also acts on the tags nested below it. The tag-to-documents index
+//! itself lives in the search index, which already re-reads changed files.
+
+use serde_json::Value;
+
+use crate::front_matter::{self, Metadata};
+use crate::search_index::{line_kinds, LineKind};
+
+/// Clean up a tag typed by a user: surrounding whitespace, a leading `#` and
+/// stray `/` are dropped. Fails if the result couldn't be written inline.
+pub fn normalize(tag: &str) -> Result<String, String> {
+    let cleaned = tag.trim().trim_start_matches('#').trim_matches('/');
+    let valid = !cleaned.is_empty()
+        && cleaned.chars().all(is_tag_char)
+        && cleaned.chars().any(char::is_alphabetic)
+        && !cleaned.contains("//");
+    if valid {
+        Ok(cleaned.to_string())
+    } else {
+        Err(format!(
+            "Invalid tag name: {:?} (use letters, digits, '-', '_' and '/')",
+            tag
+        ))
+    }
+}
+
+/// Characters allowed in an inline `#tag`.
+pub fn is_tag_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '_' || c == '-' || c == '/'
+}
+
+/// Inline `#tag` words of one line of body text, as byte ranges of the tag
+/// (without the `#`). `#123` is usually an issue number, so purely numeric
+/// words are skipped.
+pub fn inline_tags(line: &str) -> Vec<(usize, usize)> {
+    let mut found = Vec::new();
+    let mut prev = ' ';
+    for (i, c) in line.char_indices() {
+        if c == '#' && prev.is_whitespace() {
+            let start = i + 1;
+            let rest = &line[start..];
+            let end = rest.find(|ch: char| !is_tag_char(ch)).unwrap_or(rest.len());
+            let word = rest[..end].trim_end_matches('/');
+            if word.chars().any(char::is_alphabetic) {
+                found.push((start, start + word.len()));
+            }
+        }
+        prev = c;
+    }
+    found
+}
+
+/// `tag` with `from` (lowercase) replaced by `to`, if `tag` is `from` or
+/// nested below it.
+fn renamed(tag: &str, from: &str, to: &str) -> Option<String> {
+    let head = tag.get(..from.len())?;
+    let rest = &tag[from.len()..];
+    if head.to_lowercase() != from || !(rest.is_empty() || rest.starts_with('/')) {
+        return None;
+    }
+    Some(format!("{}{}", to, rest))
+}
+
+/// Rename tag `from` (lowercase) to `to` in front matter and inline tags.
+/// Returns the new content, or None if the document doesn't use the tag.
+/// Renaming onto a tag the document already has merges the two.
+pub fn rename_in_document(content: &str, from: &str, to: &str) -> Result<Option<String>, String> {
+    let mut text = content.to_string();
+    if let Some(metadata) = front_matter::metadata(content) {
+        if let Some(tags) = renamed_front_matter_tags(&metadata, from, to) {
+            let mut updates = Metadata::new();
+            updates.insert("tags".into(), tags);
+            text = front_matter::update(content, &updates)?;
+        }
+    }
+
+    let kinds = line_kinds(&text);
+    let mut out = String::with_capacity(text.len());
+    // `split_inclusive` yields the same lines as `str::lines`, line breaks kept.
+    for (line, kind) in text.split_inclusive('\n').zip(kinds) {
+        if kind != LineKind::Body {
+            out.push_str(line);
+            continue;
+        }
+        let mut pos = 0;
+        for (start, end) in inline_tags(line) {
+            if let Some(new_tag) = renamed(&line[start..end], from, to) {
+                out.push_str(&line[pos..start]);
+                out.push_str(&new_tag);
+                pos = end;
+            }
+        }
+        out.push_str(&line[pos..]);
+    }
+
+    Ok(if out == content { None } else { Some(out) })
+}
+
+/// The front matter `tags` value after renaming, in its original shape (a
+/// list or a comma-separated string), or None if nothing changed.
+fn renamed_front_matter_tags(metadata: &Metadata, from: &str, to: &str) -> Option<Value> {
+    let tags = front_matter::string_list(metadata, "tags");
+    if !tags.iter().any(|t| renamed(t, from, to).is_some()) {
+        return None;
+    }
+    let mut result: Vec<String> = Vec::new();
+    for tag in tags {
+        let tag = renamed(&tag, from, to).unwrap_or(tag);
+        if !result.iter().any(|t| t.to_lowercase() == tag.to_lowercase()) {
+            result.push(tag);
+        }
+    }
+    Some(match metadata.get("tags") {
+        Some(Value::String(_)) => Value::String(result.join(", ")),
+        _ => Value::Array(result.into_iter().map(Value::String).collect()),
+    })
+}
@@ -0,0 +1,236 @@
+//! Copying entries, and moving them between filesystems.
+//!
+//! `fs::rename` can't cross a mount point or drive, so `move_path` falls back
+//! to copying the entry and deleting the original when the rename fails with
+//! a cross-device error. Copies keep the modification and access times of
+//! the original where the platform allows it. Symlinks are recreated rather
+//! than followed (on Unix; elsewhere they are skipped), so a copy never pulls
+//! in files from outside the workspace.
+//!
+//! New copies get a free name next to their siblings, and so do entries
+//! created, renamed or moved with `CollisionPolicy::AutoSuffix`:
+//!
+//! ```text
+//! copies:       Note.md  →  Note (copy).md  →  Note (copy 2).md  → ...
+//! auto-suffix:  Note.md  →  Note (2).md     →  Note (3).md       → ...
+//! ```
+//!
+//! Folders are suffixed the same way; only files keep their extension last.
+//!
+//! `fs::rename` silently replaces a file (or empty folder) at the target, so
+//! an entry created there after the caller checked the name would be lost.
+//! Renames and moves go through `rename_no_replace` instead, which fails with
+//! `ErrorKind::AlreadyExists` in that case: atomically where the platform
+//! supports it (`renameat2` on Linux, `renamex_np` on macOS), elsewhere by
+//! hard-linking files to the new name before unlinking the old one.
+
+use std::fs::{self, File, FileTimes};
+use std::io::{self, ErrorKind};
+use std::path::{Path, PathBuf};
+
+use crate::storage::StorageError;
+
+/// Move `from` to `to`, copying and deleting when they are on different
+/// filesystems. On a failed copy the partial destination is removed and
+/// `from` is left alone. An entry at `to` is never replaced; that fails with
+/// `StorageError::AlreadyExists`.
+pub fn move_path(from: &Path, to: &Path) -> Result<(), String> {
+    match rename_no_replace(from, to) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == ErrorKind::AlreadyExists => Err(already_exists(to)),
+        Err(e) if e.kind() == ErrorKind::CrossesDevices => {
+            if fs::symlink_metadata(to).is_ok() {
+                return Err(already_exists(to));
+            }
+            copy_path(from, to)?;
+            remove_path(from).map_err(|e| {
+                format!("Copied {} to {} but could not remove the original: {}", from.display(), to.display(), e)
+            })
+        }
+        Err(e) => Err(format!("Failed to move {} to {}: {}", from.display(), to.display(), e)),
+    }
+}
+
+/// Rename `from` to `to`, failing with `ErrorKind::AlreadyExists` instead of
+/// replacing an entry at `to`.
+pub fn rename_no_replace(from: &Path, to: &Path) -> io::Result<()> {
+    match rename_exclusive(from, to) {
+        Some(Err(e)) if is_unsupported(&e) => {}
+        Some(result) => return result,
+        None => {}
+    }
+    if !fs::symlink_metadata(from)?.is_dir() {
+        match fs::hard_link(from, to) {
+            Ok(()) => {
+                return fs::remove_file(from).inspect_err(|_| {
+                    let _ = fs::remove_file(to);
+                })
+            }
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => return Err(e),
+            // No hard links on this filesystem (or across devices)
+            Err(_) => {}
+        }
+    }
+    // Folders elsewhere: a plain rename can still replace an empty folder
+    // created in the meantime, but never one with contents or a file.
+    if fs::symlink_metadata(to).is_ok() {
+        return Err(ErrorKind::AlreadyExists.into());
+    }
+    fs::rename(from, to)
+}
+
+/// The platform's atomic no-replace rename, or None where there is none.
+#[cfg(target_os = "linux")]
+fn rename_exclusive(from: &Path, to: &Path) -> Option<io::Result<()>> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let from = CString::new(from.as_os_str().as_bytes()).ok()?;
+    let to = CString::new(to.as_os_str().as_bytes()).ok()?;
+    // SAFETY: both paths are valid NUL-terminated strings for the call.
+    let result = unsafe {
+        libc::syscall(
+            libc::SYS_renameat2,
+            libc::AT_FDCWD,
+            from.as_ptr(),
+            libc::AT_FDCWD,
+            to.as_ptr(),
+            libc::RENAME_NOREPLACE,
+        )
+    };
+    Some(if result == 0 { Ok(()) } else { Err(io::Error::last_os_error()) })
+}
+
+#[cfg(target_os = "macos")]
+fn rename_exclusive(from: &Path, to: &Path) -> Option<io::Result<()>> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let from = CString::new(from.as_os_str().as_bytes()).ok()?;
+    let to = CString::new(to.as_os_str().as_bytes()).ok()?;
+    // SAFETY: both paths are valid NUL-terminated strings for the call.
+    let result = unsafe { libc::renamex_np(from.as_ptr(), to.as_ptr(), libc::RENAME_EXCL) };
+    Some(if result == 0 { Ok(()) } else { Err(io::Error::last_os_error()) })
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn rename_exclusive(_from: &Path, _to: &Path) -> Option<io::Result<()>> {
+    None
+}
+
+/// Whether the no-replace rename failed only because the kernel or the
+/// filesystem doesn't offer it.
+#[cfg(unix)]
+fn is_unsupported(e: &io::Error) -> bool {
+    matches!(e.raw_os_error(), Some(libc::EINVAL | libc::ENOSYS | libc::ENOTSUP))
+}
+
+#[cfg(not(unix))]
+fn is_unsupported(_e: &io::Error) -> bool {
+    false
+}
+
+fn already_exists(path: &Path) -> String {
+    StorageError::AlreadyExists {
+        path: path.to_string_lossy().to_string(),
+    }
+    .into()
+}
+
+/// Recursively copy the file or folder `from` to `to`, which must not exist.
+/// On failure, whatever was already copied is removed again.
+pub fn copy_path(from: &Path, to: &Path) -> Result<(), String> {
+    if to.starts_with(from) {
+        return Err(format!("Cannot copy {} into itself", from.display()));
+    }
+    copy_recursive(from, to).map_err(|e| {
+        let _ = remove_path(to);
+        format!("Failed to copy {} to {}: {}", from.display(), to.display(), e)
+    })
+}
+
+fn copy_recursive(from: &Path, to: &Path) -> std::io::Result<()> {
+    let meta = fs::symlink_metadata(from)?;
+    if meta.file_type().is_symlink() {
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(fs::read_link(from)?, to)?;
+        return Ok(());
+    }
+    if meta.is_dir() {
+        fs::create_dir(to)?;
+        for entry in fs::read_dir(from)? {
+            let entry = entry?;
+            copy_recursive(&entry.path(), &to.join(entry.file_name()))?;
+        }
+    } else {
+        if fs::symlink_metadata(to).is_ok() {
+            return Err(ErrorKind::AlreadyExists.into());
+        }
+        fs::copy(from, to)?;
+    }
+    keep_times(&meta, to);
+    Ok(())
+}
+
+/// Give `to` the modification and access times in `meta`, if possible.
+fn keep_times(meta: &fs::Metadata, to: &Path) {
+    let mut times = FileTimes::new();
+    if let Ok(modified) = meta.modified() {
+        times = times.set_modified(modified);
+    }
+    if let Ok(accessed) = meta.accessed() {
+        times = times.set_accessed(accessed);
+    }
+    // Opening a folder for writing isn't possible everywhere; that's fine.
+    let file = if meta.is_dir() { File::open(to) } else { File::options().write(true).open(to) };
+    if let Ok(file) = file {
+        let _ = file.set_times(times);
+    }
+}
+
+fn remove_path(path: &Path) -> std::io::Result<()> {
+    let meta = fs::symlink_metadata(path)?;
+    if meta.is_dir() {
+        fs::remove_dir_all(path)
+    } else {
+        fs::remove_file(path)
+    }
+}
+
+/// `dir/name` if that is free, otherwise the first free `(copy)` variant.
+pub fn free_copy_path(dir: &Path, name: &str, is_dir: bool) -> PathBuf {
+    free_variant(dir, name, is_dir, |n| {
+        if n == 1 {
+            "(copy)".to_string()
+        } else {
+            format!("(copy {})", n)
+        }
+    })
+}
+
+/// `dir/name` if that is free, otherwise the first free numbered variant.
+pub fn free_numbered_path(dir: &Path, name: &str, is_dir: bool) -> PathBuf {
+    free_variant(dir, name, is_dir, |n| format!("({})", n + 1))
+}
+
+/// `dir/name`, or `name` with the `suffix` of the first free n = 1, 2, ...
+/// inserted before the extension.
+fn free_variant(dir: &Path, name: &str, is_dir: bool, suffix: impl Fn(u32) -> String) -> PathBuf {
+    let candidate = dir.join(name);
+    if fs::symlink_metadata(&candidate).is_err() {
+        return candidate;
+    }
+    let file = Path::new(name);
+    let (stem, ext) = match (is_dir, file.file_stem(), file.extension()) {
+        (false, Some(stem), Some(ext)) => (stem.to_string_lossy().to_string(), format!(".{}", ext.to_string_lossy())),
+        _ => (name.to_string(), String::new()),
+    };
+    let mut n = 1;
+    loop {
+        let candidate = dir.join(format!("{} {}{}", stem, suffix(n), ext));
+        if fs::symlink_metadata(&candidate).is_err() {
+            return candidate;
+        }
+        n += 1;
+    }
+}
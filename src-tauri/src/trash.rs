@@ -0,0 +1,203 @@
+//! Recoverable deletes.
+//!
+//! Deleted entries are moved into a hidden `.markdownui-trash` directory at the
+//! top of their workspace root instead of being removed. Each deletion gets its
+//! own slot:
+//!
+//! ```text
+//! <root>/.markdownui-trash/<trash id>/<original name>
+//! <root>/.markdownui-trash/<trash id>.json      (TrashEntry metadata)
+//! ```
+//!
+//! The dot prefix keeps the trash out of the tree, search and the watcher.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::atomic::write_atomic;
+use crate::sandbox;
+use crate::storage::{StorageError, TreeNodeKind};
+use crate::transfer;
+
+pub const TRASH_DIR: &str = ".markdownui-trash";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrashEntry {
+    /// Identifier of this deletion, unique within its trash directory.
+    pub id: String,
+    /// Directory whose trash holds the entry (normally the workspace root).
+    pub root_id: String,
+    /// Absolute path the entry had before it was deleted.
+    pub original_path: String,
+    pub name: String,
+    pub kind: TreeNodeKind,
+    pub deleted_at: String,
+    pub deleted_at_unix: u64,
+}
+
+fn trash_dir(root: &Path) -> PathBuf {
+    root.join(TRASH_DIR)
+}
+
+fn now_unix() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or_default()
+}
+
+/// Trash ids are the deletion time in nanoseconds, bumped until unused.
+fn new_trash_id(dir: &Path) -> String {
+    let mut nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    loop {
+        let id = format!("{:x}", nanos);
+        if !dir.join(&id).exists() && !dir.join(format!("{}.json", id)).exists() {
+            return id;
+        }
+        nanos += 1;
+    }
+}
+
+/// Move `path` into the trash of `root`. `deleted_at` is the display timestamp
+/// stored alongside the entry.
+pub fn move_to_trash(
+    root: &Path,
+    path: &Path,
+    kind: TreeNodeKind,
+    deleted_at: String,
+) -> Result<TrashEntry, String> {
+    let dir = trash_dir(root);
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .ok_or_else(|| format!("Invalid path: {}", path.display()))?;
+
+    fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create trash directory {}: {}", dir.display(), e))?;
+
+    let id = new_trash_id(&dir);
+    let slot = dir.join(&id);
+    fs::create_dir(&slot).map_err(|e| format!("Failed to create trash slot: {}", e))?;
+
+    let entry = TrashEntry {
+        id: id.clone(),
+        root_id: root.to_string_lossy().to_string(),
+        original_path: path.to_string_lossy().to_string(),
+        name: name.clone(),
+        kind,
+        deleted_at,
+        deleted_at_unix: now_unix(),
+    };
+
+    // Write the metadata before moving: an item without metadata could never be
+    // restored, while metadata whose item is missing is simply skipped by `list`.
+    let json = serde_json::to_string_pretty(&entry)
+        .map_err(|e| format!("Failed to serialize trash entry: {}", e))?;
+    write_atomic(&dir.join(format!("{}.json", id)), json.as_bytes())
+        .map_err(|e| format!("Failed to write trash metadata: {}", e))?;
+
+    if let Err(e) = fs::rename(path, slot.join(&name)) {
+        let _ = fs::remove_file(dir.join(format!("{}.json", id)));
+        let _ = fs::remove_dir(&slot);
+        return Err(format!("Failed to move {} to trash: {}", path.display(), e));
+    }
+
+    Ok(entry)
+}
+
+/// All entries in the trash of `root`, newest first.
+pub fn list(root: &Path) -> Vec<TrashEntry> {
+    let dir = trash_dir(root);
+    let entries = match fs::read_dir(&dir) {
+        Ok(e) => e,
+        Err(_) => return Vec::new(),
+    };
+
+    let mut items: Vec<TrashEntry> = entries
+        .filter_map(Result::ok)
+        .filter(|e| e.path().extension().map(|x| x == "json").unwrap_or(false))
+        .filter_map(|e| fs::read_to_string(e.path()).ok())
+        .filter_map(|json| serde_json::from_str::<TrashEntry>(&json).ok())
+        .filter(|entry| dir.join(&entry.id).join(&entry.name).exists())
+        .collect();
+
+    items.sort_by_key(|e| std::cmp::Reverse(e.deleted_at_unix));
+    items
+}
+
+/// The entry `trash_id` of `root`'s trash. The metadata is a file in the
+/// workspace like any other, so its slot and name must be plain names.
+fn find(root: &Path, trash_id: &str) -> Result<TrashEntry, String> {
+    let entry = list(root)
+        .into_iter()
+        .find(|e| e.id == trash_id)
+        .ok_or_else(|| format!("Trash entry not found: {}", trash_id))?;
+    sandbox::check_name(&entry.id)?;
+    sandbox::check_name(&entry.name)?;
+    Ok(entry)
+}
+
+/// Move a trashed entry back to its original location, recreating missing
+/// parent folders. Fails with `StorageError::AlreadyExists` if something
+/// exists at that path (even if it appeared while restoring), and with
+/// `StorageError::PermissionDenied` if that path is outside `root`.
+pub fn restore(root: &Path, trash_id: &str) -> Result<PathBuf, String> {
+    let entry = find(root, trash_id)?;
+    let dir = trash_dir(root);
+    let original = sandbox::confine_entry(&entry.original_path, &[root.to_path_buf()])?;
+
+    if let Some(parent) = original.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to recreate {}: {}", parent.display(), e))?;
+    }
+
+    transfer::rename_no_replace(&dir.join(&entry.id).join(&entry.name), &original).map_err(|e| {
+        if e.kind() == ErrorKind::AlreadyExists {
+            StorageError::AlreadyExists {
+                path: original.to_string_lossy().to_string(),
+            }
+            .into()
+        } else {
+            format!("Failed to restore {}: {}", original.display(), e)
+        }
+    })?;
+    remove_slot(&dir, &entry.id);
+    Ok(original)
+}
+
+/// Permanently delete one trashed entry.
+pub fn purge(root: &Path, trash_id: &str) -> Result<(), String> {
+    let entry = find(root, trash_id)?;
+    let dir = trash_dir(root);
+    fs::remove_dir_all(dir.join(&entry.id))
+        .map_err(|e| format!("Failed to purge trash entry: {}", e))?;
+    remove_slot(&dir, &entry.id);
+    Ok(())
+}
+
+/// Permanently delete every entry deleted before `cutoff_unix` (all entries
+/// when None). Returns how many were purged.
+pub fn purge_older_than(root: &Path, cutoff_unix: Option<u64>) -> usize {
+    list(root)
+        .into_iter()
+        .filter(|e| cutoff_unix.map(|c| e.deleted_at_unix < c).unwrap_or(true))
+        .filter(|e| purge(root, &e.id).is_ok())
+        .count()
+}
+
+/// Cutoff for a retention period of `days`, as a unix timestamp.
+pub fn retention_cutoff(days: u32) -> u64 {
+    now_unix().saturating_sub(u64::from(days) * 86400)
+}
+
+fn remove_slot(dir: &Path, id: &str) {
+    let _ = fs::remove_file(dir.join(format!("{}.json", id)));
+    let _ = fs::remove_dir_all(dir.join(id));
+}
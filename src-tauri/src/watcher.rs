@@ -0,0 +1,285 @@
+//! Recursive filesystem watcher for the registered workspace roots.
+//!
+//! Wraps a `notify` debouncer so bursts of raw OS events (editor save dances,
+//! `git pull`, scripts writing many files) are coalesced into a single batch of
+//! `FsChange`s per quiet period. `main.rs` forwards every batch to the frontend
+//! as an `fs-changes` Tauri event so the tree and the open document can refresh
+//! without polling.
+//!
+//! Only changes to entries the tree shows are reported, by the same
+//! `WorkspaceFilter` rules as listings, so churn in ignored trees
+//! (`node_modules`, build output, `.git`) never leaves the watcher. Changes to
+//! ignore files are the exception, since they change what is shown.
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use notify_debouncer_full::notify::event::{ModifyKind, RenameMode};
+use notify_debouncer_full::notify::{EventKind, RecommendedWatcher, RecursiveMode};
+use notify_debouncer_full::{
+    new_debouncer, DebounceEventResult, DebouncedEvent, Debouncer, RecommendedCache,
+};
+use serde::Serialize;
+
+use crate::workspace_filter::{WorkspaceFilter, IGNORE_FILES};
+
+/// Name of the Tauri event carrying a `Vec<FsChange>` batch.
+pub const FS_CHANGES_EVENT: &str = "fs-changes";
+
+/// Quiet period after the last raw event before a batch is flushed.
+const DEBOUNCE_MS: u64 = 300;
+
+#[derive(Debug, Clone, Copy, Serialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum FsChangeKind {
+    Created,
+    Modified,
+    Removed,
+    Renamed,
+}
+
+/// One coalesced change to an entry below a workspace root.
+#[derive(Debug, Clone, Serialize)]
+pub struct FsChange {
+    pub kind: FsChangeKind,
+    /// Absolute path of the affected entry (the new path for renames).
+    pub id: String,
+    pub parent_id: Option<String>,
+    /// Previous path, only set for renames.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_id: Option<String>,
+}
+
+pub struct WorkspaceWatcher {
+    debouncer: Mutex<Debouncer<RecommendedWatcher, RecommendedCache>>,
+    roots: Arc<Mutex<HashSet<PathBuf>>>,
+}
+
+impl WorkspaceWatcher {
+    /// Start the watcher thread. `on_change` is called from that thread with every
+    /// non-empty batch of coalesced changes; `filters` provides the current
+    /// listing rules of every root, once per batch.
+    pub fn new<R, F>(filters: R, on_change: F) -> Result<Self, String>
+    where
+        R: Fn() -> Vec<WorkspaceFilter> + Send + 'static,
+        F: Fn(Vec<FsChange>) + Send + 'static,
+    {
+        let roots: Arc<Mutex<HashSet<PathBuf>>> = Arc::new(Mutex::new(HashSet::new()));
+        let handler_roots = Arc::clone(&roots);
+
+        let debouncer = new_debouncer(
+            Duration::from_millis(DEBOUNCE_MS),
+            None,
+            move |result: DebounceEventResult| {
+                let events = match result {
+                    Ok(events) => events,
+                    Err(_) => return,
+                };
+                let mut filters = filters();
+                match handler_roots.lock() {
+                    Ok(watched) => filters.retain(|f| watched.contains(f.root())),
+                    Err(_) => return,
+                }
+                let changes = coalesce(events, &mut filters);
+                if !changes.is_empty() {
+                    on_change(changes);
+                }
+            },
+        )
+        .map_err(|e| format!("Failed to start filesystem watcher: {}", e))?;
+
+        Ok(WorkspaceWatcher {
+            debouncer: Mutex::new(debouncer),
+            roots,
+        })
+    }
+
+    /// Make the set of watched directories match `roots`: new roots are watched
+    /// recursively, roots that were removed from the config are unwatched.
+    /// Roots that cannot be watched (e.g. missing directories) are skipped and
+    /// reported in the returned error after the others have been processed.
+    pub fn sync_roots(&self, roots: &[PathBuf]) -> Result<(), String> {
+        let mut debouncer = self.debouncer.lock().map_err(|e| e.to_string())?;
+        let mut watched = self.roots.lock().map_err(|e| e.to_string())?;
+        let wanted: HashSet<PathBuf> = roots.iter().cloned().collect();
+        let mut failures: Vec<String> = Vec::new();
+
+        for stale in watched.difference(&wanted).cloned().collect::<Vec<_>>() {
+            let _ = debouncer.unwatch(&stale);
+            watched.remove(&stale);
+        }
+
+        for root in wanted.difference(&watched.clone()) {
+            match debouncer.watch(root, RecursiveMode::Recursive) {
+                Ok(()) => {
+                    watched.insert(root.clone());
+                }
+                Err(e) => failures.push(format!("{}: {}", root.display(), e)),
+            }
+        }
+
+        if failures.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("Failed to watch {}", failures.join("; ")))
+        }
+    }
+}
+
+// ── Coalescing ─────────────────────────────────────────────────────────────────
+
+/// Changes collected for one batch, keyed by path and kept in first-seen order.
+#[derive(Default)]
+struct PendingChanges {
+    order: Vec<PathBuf>,
+    by_path: HashMap<PathBuf, (FsChangeKind, Option<PathBuf>)>,
+}
+
+impl PendingChanges {
+    fn get(&self, path: &Path) -> Option<FsChangeKind> {
+        self.by_path.get(path).map(|(kind, _)| *kind)
+    }
+
+    fn record(&mut self, path: PathBuf, kind: FsChangeKind, old: Option<PathBuf>) {
+        if !self.by_path.contains_key(&path) {
+            self.order.push(path.clone());
+        }
+        self.by_path.insert(path, (kind, old));
+    }
+
+    fn take(&mut self, path: &Path) -> Option<(FsChangeKind, Option<PathBuf>)> {
+        let taken = self.by_path.remove(path)?;
+        self.order.retain(|p| p != path);
+        Some(taken)
+    }
+
+    fn into_changes(mut self) -> Vec<FsChange> {
+        let by_path = &mut self.by_path;
+        self.order
+            .into_iter()
+            .filter_map(|path| {
+                let (kind, old) = by_path.remove(&path)?;
+                Some(FsChange {
+                    kind,
+                    id: path.to_string_lossy().to_string(),
+                    parent_id: path.parent().map(|p| p.to_string_lossy().to_string()),
+                    old_id: old.map(|p| p.to_string_lossy().to_string()),
+                })
+            })
+            .collect()
+    }
+}
+
+/// Collapse a debounced batch into at most one change per path.
+///
+/// - created then modified  → created
+/// - created then removed   → dropped
+/// - removed then created   → modified (editors that save by delete + recreate)
+/// - renamed then modified  → renamed
+/// - renamed then removed   → removed at the original path
+fn coalesce(events: Vec<DebouncedEvent>, filters: &mut [WorkspaceFilter]) -> Vec<FsChange> {
+    let mut pending = PendingChanges::default();
+
+    for event in events {
+        let paths: Vec<&PathBuf> = event
+            .paths
+            .iter()
+            .filter(|p| is_visible(p, filters))
+            .collect();
+
+        match event.kind {
+            EventKind::Access(_) => {}
+            EventKind::Create(_) => {
+                for path in paths {
+                    let kind = match pending.get(path) {
+                        Some(FsChangeKind::Removed) => FsChangeKind::Modified,
+                        _ => FsChangeKind::Created,
+                    };
+                    pending.record(path.clone(), kind, None);
+                }
+            }
+            EventKind::Remove(_) => {
+                for path in paths {
+                    match pending.take(path) {
+                        Some((FsChangeKind::Created, _)) => {}
+                        Some((FsChangeKind::Renamed, Some(old))) => {
+                            pending.record(old, FsChangeKind::Removed, None)
+                        }
+                        _ => pending.record(path.clone(), FsChangeKind::Removed, None),
+                    }
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::Both)) if event.paths.len() == 2 => {
+                let (from, to) = (&event.paths[0], &event.paths[1]);
+                match (is_visible(from, filters), is_visible(to, filters)) {
+                    (true, true) => match pending.take(from) {
+                        Some((FsChangeKind::Created, _)) => {
+                            pending.record(to.clone(), FsChangeKind::Created, None)
+                        }
+                        Some((FsChangeKind::Renamed, Some(origin))) => {
+                            pending.record(to.clone(), FsChangeKind::Renamed, Some(origin))
+                        }
+                        _ => pending.record(to.clone(), FsChangeKind::Renamed, Some(from.clone())),
+                    },
+                    // Moved out of view (e.g. into an ignored folder) or into view.
+                    (true, false) => pending.record(from.clone(), FsChangeKind::Removed, None),
+                    (false, true) => pending.record(to.clone(), FsChangeKind::Created, None),
+                    (false, false) => {}
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::From)) => {
+                for path in paths {
+                    pending.record(path.clone(), FsChangeKind::Removed, None);
+                }
+            }
+            EventKind::Modify(ModifyKind::Name(RenameMode::To)) => {
+                for path in paths {
+                    pending.record(path.clone(), FsChangeKind::Created, None);
+                }
+            }
+            _ => {
+                for path in paths {
+                    if pending.get(path).is_none() {
+                        pending.record(path.clone(), FsChangeKind::Modified, None);
+                    }
+                }
+            }
+        }
+    }
+
+    pending.into_changes()
+}
+
+/// Whether `path` is shown by its root's listing rules. An ignore file
+/// counts as shown when its folder is, and `.git/info/exclude` always does,
+/// since editing one changes what the tree and the index contain.
+fn is_visible(path: &Path, filters: &mut [WorkspaceFilter]) -> bool {
+    let filter = match filters.iter_mut().find(|f| path.starts_with(f.root())) {
+        Some(filter) => filter,
+        None => return false,
+    };
+    if path == git_exclude(filter.root()) {
+        return true;
+    }
+    match path.parent() {
+        Some(dir) if is_ignore_file(path) => dir == filter.root() || filter.contains(dir),
+        _ => filter.contains(path),
+    }
+}
+
+/// Whether `path` holds listing rules for the workspace rooted at `root`:
+/// an ignore file, or the root's `.git/info/exclude`.
+pub fn is_rules_file(path: &Path, root: &Path) -> bool {
+    is_ignore_file(path) || path == git_exclude(root)
+}
+
+fn is_ignore_file(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| IGNORE_FILES.contains(&name.to_string_lossy().as_ref()))
+}
+
+fn git_exclude(root: &Path) -> PathBuf {
+    root.join(".git").join("info").join("exclude")
+}
@@ -0,0 +1,185 @@
+//! Which entries of a workspace are listed, indexed and searched.
+//!
+//! Dot-prefixed names are hidden unless the workspace sets `show_hidden`
+//! (`.git` and the trash always are). Entries matched by an ignore file are
+//! hidden as well. A folder's ignore files apply to everything below it, a
+//! deeper folder's rules win over its parents', and within one folder the
+//! later file wins:
+//!
+//! ```text
+//! .gitignore           honored with or without a git repository
+//! .ignore
+//! .markdownuiignore    rules for MarkdownUI only
+//! ```
+//!
+//! The workspace's `include` and `exclude` globs (gitignore syntax, relative
+//! to the root) take precedence over ignore files: an excluded entry is always
+//! hidden and an included one always shown. Once there are include globs,
+//! files matching none of them are hidden; folders are not, so
+//! `include: ["*.md"]` still shows every folder.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::overrides::{Override, OverrideBuilder};
+use ignore::Match;
+use serde::{Deserialize, Serialize};
+
+use crate::documents::DocumentTypes;
+use crate::trash::TRASH_DIR;
+
+/// Ignore files read in every folder, lowest precedence first.
+pub const IGNORE_FILES: &[&str] = &[".gitignore", ".ignore", ".markdownuiignore"];
+
+/// Hidden even with `show_hidden`, and off limits to storage operations
+/// (see `sandbox.rs`).
+pub const ALWAYS_HIDDEN: &[&str] = &[".git", TRASH_DIR];
+
+/// Per-workspace listing options, stored in `WorkspaceEntry`.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FilterSettings {
+    /// Globs of entries to show even when ignored; with any set, other files are hidden.
+    #[serde(default)]
+    pub include: Vec<String>,
+    /// Globs of entries to always hide, e.g. `node_modules` or `drafts/**/*.txt`.
+    #[serde(default)]
+    pub exclude: Vec<String>,
+    /// Show dot-prefixed files and folders.
+    #[serde(default)]
+    pub show_hidden: bool,
+}
+
+impl FilterSettings {
+    pub fn is_empty(&self) -> bool {
+        *self == FilterSettings::default()
+    }
+
+    /// Fail with a message naming the first glob that doesn't parse.
+    pub fn validate(&self) -> Result<(), String> {
+        self.overrides(Path::new("")).map(|_| ())
+    }
+
+    fn overrides(&self, root: &Path) -> Result<Override, String> {
+        let mut builder = OverrideBuilder::new(root);
+        for glob in &self.include {
+            builder
+                .add(glob)
+                .map_err(|e| format!("Invalid include pattern {:?}: {}", glob, e))?;
+        }
+        for glob in &self.exclude {
+            builder
+                .add(&format!("!{}", glob))
+                .map_err(|e| format!("Invalid exclude pattern {:?}: {}", glob, e))?;
+        }
+        builder.build().map_err(|e| format!("Invalid patterns: {}", e))
+    }
+}
+
+/// The listing rules of one workspace root. Ignore files are read on first
+/// use and cached, so build a new filter for each listing or walk.
+pub struct WorkspaceFilter {
+    root: PathBuf,
+    types: DocumentTypes,
+    show_hidden: bool,
+    overrides: Override,
+    /// Ignore rules of each folder looked at so far; None if it has none.
+    ignores: HashMap<PathBuf, Option<Gitignore>>,
+}
+
+impl WorkspaceFilter {
+    /// Invalid globs are left out; `FilterSettings::validate` rejects them
+    /// before they are saved.
+    pub fn new(root: &Path, settings: &FilterSettings, types: DocumentTypes) -> Self {
+        WorkspaceFilter {
+            root: root.to_path_buf(),
+            types,
+            show_hidden: settings.show_hidden,
+            overrides: settings.overrides(root).unwrap_or_else(|_| Override::empty()),
+            ignores: HashMap::new(),
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn is_document(&self, path: &Path) -> bool {
+        self.types.is_document(path)
+    }
+
+    /// Whether the entry at `path`, inside a folder that is shown, is shown.
+    pub fn shows(&mut self, path: &Path, is_dir: bool) -> bool {
+        let name = match path.file_name() {
+            Some(name) => name.to_string_lossy(),
+            None => return true,
+        };
+        if ALWAYS_HIDDEN.contains(&name.as_ref()) || (!self.show_hidden && name.starts_with('.')) {
+            return false;
+        }
+        match self.overrides.matched(path, is_dir) {
+            Match::Ignore(_) => return false,
+            Match::Whitelist(_) => return true,
+            Match::None => {}
+        }
+        let mut dir = path.parent();
+        while let Some(current) = dir.filter(|d| d.starts_with(&self.root)) {
+            if let Some(rules) = self.ignore_rules(current) {
+                match rules.matched(path, is_dir) {
+                    Match::Ignore(_) => return false,
+                    Match::Whitelist(_) => return true,
+                    Match::None => {}
+                }
+            }
+            dir = current.parent();
+        }
+        true
+    }
+
+    /// Whether `path` and every folder between it and the root are shown.
+    pub fn contains(&mut self, path: &Path) -> bool {
+        let relative = match path.strip_prefix(&self.root) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => return false,
+        };
+        let depth = relative.components().count();
+        let mut current = self.root.clone();
+        for (i, component) in relative.components().enumerate() {
+            current.push(component);
+            let is_dir = i + 1 < depth || path.is_dir();
+            if !self.shows(&current, is_dir) {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn ignore_rules(&mut self, dir: &Path) -> Option<&Gitignore> {
+        if !self.ignores.contains_key(dir) {
+            let rules = read_ignore_files(dir, dir == self.root);
+            self.ignores.insert(dir.to_path_buf(), rules);
+        }
+        self.ignores.get(dir).and_then(Option::as_ref)
+    }
+}
+
+/// The rules of `dir`'s ignore files (and, for the root, `.git/info/exclude`).
+fn read_ignore_files(dir: &Path, is_root: bool) -> Option<Gitignore> {
+    let mut builder = GitignoreBuilder::new(dir);
+    let mut files: Vec<PathBuf> = Vec::new();
+    if is_root {
+        files.push(dir.join(".git").join("info").join("exclude"));
+    }
+    files.extend(IGNORE_FILES.iter().map(|name| dir.join(name)));
+    let mut found = false;
+    for file in files.iter().filter(|f| fs::metadata(f).is_ok_and(|m| m.is_file())) {
+        // A malformed line only loses that line's rule.
+        let _ = builder.add(file);
+        found = true;
+    }
+    if !found {
+        return None;
+    }
+    builder.build().ok()
+}
@@ -2,19 +2,23 @@ import { useState, useEffect, useRef } from "react";
 import { save } from "@tauri-apps/api/dialog";
 import FilesystemBrowser from "./components/FilesystemBrowser";
 import DocumentEditor from "./components/DocumentEditor";
+import type { EditorPosition } from "./components/DocumentEditor";
 import Header from "./components/Header";
 import SettingsModal from "./components/SettingsModal";
+import QuickOpen from "./components/QuickOpen";
 import { SettingsProvider } from "./contexts/SettingsContext";
 import { useTheme } from "./ThemeContext";
 import { useSettings } from "./contexts/SettingsContext";
 import { useToast } from "./contexts/ToastContext";
 import { useMcpEvents } from "./hooks/useMcpEvents";
+import { useFsChanges } from "./hooks/useFsChanges";
 import { useSidebarResize } from "./hooks/useSidebarResize";
 import * as api from "./api";
-import type { ExportFormat, TreeNode } from "./api";
+import type { DiagramFormat, ExportFormat, ExportOptions, SearchMatch, TreeNode } from "./api";
 
 function AppContent() {
     const [scrollToHeadingId, setScrollToHeadingId] = useState<string | null>(null);
+    const [revealPosition, setRevealPosition] = useState<EditorPosition | null>(null);
     const containerRef = useRef<HTMLDivElement>(null);
     const { sidebarCollapsed, sidebarWidth, isDraggingSidebar, toggleSidebar, startDrag } = useSidebarResize(containerRef);
     const { theme, toggleTheme } = useTheme();
@@ -35,6 +39,7 @@ function AppContent() {
     // Workspace roots (filesystem mode)
     const [workspaceRoots, setWorkspaceRoots] = useState<TreeNode[]>([]);
     const [selectedFsDoc, setSelectedFsDoc] = useState<TreeNode | null>(null);
+    const [quickOpenVisible, setQuickOpenVisible] = useState(false);
 
     // MCP server state
     const [mcpRunning, setMcpRunning] = useState(false);
@@ -55,6 +60,7 @@ function AppContent() {
                 setSelectedFsDoc(null);
                 return;
             }
+            setSelectedFsDoc(entry);
             setDocumentContent(entry.content || "");
             setDocumentName(entry.name);
             setHasChanges(false);
@@ -64,10 +70,33 @@ function AppContent() {
         });
     }, [animatingIds, selectedFsDoc?.id, mcpRunning]);
 
+    // Follow on-disk changes to the open document made outside the editor
+    useFsChanges((changes) => {
+        if (!selectedFsDoc) return;
+        const change = changes.find(
+            (c) => c.id === selectedFsDoc.id || c.old_id === selectedFsDoc.id,
+        );
+        if (!change) return;
+        if (change.kind === "removed") {
+            if (!hasChanges) setSelectedFsDoc(null);
+            return;
+        }
+        if (hasChanges) return;
+        api.getEntry(change.id).then((entry) => {
+            if (!entry) return;
+            setSelectedFsDoc(entry);
+            setDocumentContent(entry.content || "");
+            setDocumentName(entry.name);
+        }).catch((err) => console.error("Failed to reload changed document:", err));
+    });
+
     useEffect(() => {
         // Check initial MCP server status on mount and load roots
         api.getMcpServerStatus().then(setMcpRunning).catch(() => {});
         api.getMcpPort().then(setMcpPort).catch(() => {});
+        api.takeConfigWarning()
+            .then((warning) => warning && showToast(warning, "error"))
+            .catch(() => {});
         fetchWorkspaceRoots();
     }, []);
 
@@ -87,19 +116,61 @@ function AppContent() {
             setDocumentContent(fsDoc.content || "");
             setHasChanges(false);
             setHasNameChanges(false);
+            api.recordOpened(fsDoc.id).catch(() => {});
+        }
+    };
+
+    // Pasted/dropped files go to the workspace's assets folder; returns the link to insert
+    const handleImportFile = async (file: File): Promise<string | null> => {
+        if (!selectedFsDoc) return null;
+        const subtype = file.type.split("/")[1] ?? "bin";
+        const name = file.name || `pasted.${subtype.replace("jpeg", "jpg").replace("svg+xml", "svg")}`;
+        try {
+            const imported = await api.importAttachment(selectedFsDoc.id, file, name);
+            return imported.markdown;
+        } catch (err) {
+            console.error("Failed to import file:", err);
+            showToast(`Failed to import ${name}: ${api.errorMessage(err)}`, "error");
+            return null;
         }
     };
 
+    const handleQuickOpenSelect = async (id: string) => {
+        try {
+            const entry = await api.getEntry(id);
+            if (entry) handleFsDocumentSelect(entry);
+        } catch (err) {
+            console.error("Failed to open document:", err);
+        }
+    };
+
+    // Ctrl/Cmd+P: quick open
+    useEffect(() => {
+        const handleKeyDown = (e: KeyboardEvent) => {
+            if ((e.ctrlKey || e.metaKey) && !e.shiftKey && e.key.toLowerCase() === "p") {
+                e.preventDefault();
+                setQuickOpenVisible(true);
+            }
+        };
+        document.addEventListener("keydown", handleKeyDown);
+        return () => document.removeEventListener("keydown", handleKeyDown);
+    }, []);
+
     const handleFsDocumentUpdate = async (
         id: string,
         name: string,
         content: string,
     ) => {
         try {
-            const updated = await api.updateDoc(id, name, content);
+            const expectedRevision = selectedFsDoc?.id === id ? selectedFsDoc.revision : undefined;
+            const updated = await api.updateDoc(id, name, content, expectedRevision);
             setSelectedFsDoc(updated);
             return updated;
         } catch (error) {
+            const storageError = api.parseStorageError(error);
+            if (storageError?.code === "conflict") {
+                showToast("Document was changed on disk. Reload it or copy your edits before saving again.", "error");
+            }
             console.error("Failed to update filesystem document:", error);
             throw error;
         }
@@ -123,8 +194,10 @@ function AppContent() {
             await handleFsDocumentUpdate(selectedFsDoc.id, documentName, documentContent);
             setHasChanges(false);
             setHasNameChanges(false);
-        } catch {
-            showToast("Failed to save document", "error");
+        } catch (error) {
+            if (api.parseStorageError(error)?.code !== "conflict") {
+                showToast("Failed to save document", "error");
+            }
         }
     };
 
@@ -149,6 +222,23 @@ function AppContent() {
         setScrollToHeadingId(null);
     };
 
+    const handleSearchMatchClick = async (docId: string, match: SearchMatch) => {
+        try {
+            const entry = await api.getEntry(docId);
+            if (entry) {
+                handleFsDocumentSelect(entry);
+                const first = match.highlights[0];
+                setRevealPosition({
+                    line: match.line,
+                    column: match.column,
+                    length: first ? first.end - first.start : 0,
+                });
+            }
+        } catch (err) {
+            console.error("Failed to load document for search match:", err);
+        }
+    };
+
     // Auto-save effect
     useEffect(() => {
         if (!hasChanges || !autoSaveEnabled || !selectedFsDoc) return;
@@ -246,7 +336,7 @@ function AppContent() {
         }
     };
 
-    const handleExportDocument = async (format: ExportFormat) => {
+    const handleExportDocument = async (format: ExportFormat, options?: ExportOptions) => {
         if (!selectedFsDoc) return;
         try {
             const formatInfo: Record<ExportFormat, { ext: string; name: string }> = {
@@ -259,10 +349,30 @@ function AppContent() {
                 filters: [{ name: filterName, extensions: [ext] }],
             });
             if (!filePath) return;
-            await api.exportDocToFile(selectedFsDoc.id, format, filePath);
+            const report = await api.exportDocToFile(selectedFsDoc.id, format, filePath, options);
+            const [first, ...rest] = report.diagram_errors;
+            if (first) {
+                const more = rest.length ? ` (and ${rest.length} more)` : "";
+                showToast(`Diagram ${first.index + 1} could not be rendered${more}: ${first.message}`, "error");
+            }
         } catch (error) {
             console.error("Export failed:", error);
-            showToast(`Failed to export as ${format.toUpperCase()}`, "error");
+            showToast(`Failed to export as ${format.toUpperCase()}: ${api.errorMessage(error)}`, "error");
+        }
+    };
+
+    const handleExportDiagram = async (index: number, format: DiagramFormat) => {
+        if (!selectedFsDoc) return;
+        try {
+            const filePath = await save({
+                defaultPath: `${documentName || "document"}-diagram-${index + 1}.${format}`,
+                filters: [{ name: format === "svg" ? "SVG Images" : "PNG Images", extensions: [format] }],
+            });
+            if (!filePath) return;
+            await api.exportDiagram(selectedFsDoc.id, index, format, filePath);
+        } catch (error) {
+            console.error("Diagram export failed:", error);
+            showToast(`Failed to export diagram: ${api.errorMessage(error)}`, "error");
         }
     };
 
@@ -305,6 +415,7 @@ function AppContent() {
                                 selectedDocId={selectedFsDoc?.id ?? null}
                                 onDocumentSelect={handleFsDocumentSelect}
                                 onHeadingClick={handleFsHeadingClick}
+                                onSearchMatchClick={handleSearchMatchClick}
                                 onRootsChanged={fetchWorkspaceRoots}
                                 onRemoveWorkspaceRoot={handleRemoveWorkspaceRoot}
                                 mcpAnimatingIds={animatingIds}
@@ -335,7 +446,11 @@ function AppContent() {
                             zoomLevel={zoomLevel}
                             scrollToHeadingId={scrollToHeadingId}
                             onHeadingScrolled={handleHeadingScrolled}
+                            revealPosition={revealPosition}
+                            onPositionRevealed={() => setRevealPosition(null)}
                             mcpFlash={mcpFlash}
+                            onImportFile={handleImportFile}
+                            onExportDiagram={handleExportDiagram}
                         />
                     ) : (
                         <div className="flex flex-col items-center justify-center h-full text-gray-600 dark:text-gray-400">
@@ -366,6 +481,12 @@ function AppContent() {
                 onAddWorkspaceRoot={handleAddWorkspaceRoot}
                 onRemoveWorkspaceRoot={handleRemoveWorkspaceRoot}
             />
+
+            <QuickOpen
+                isOpen={quickOpenVisible}
+                onClose={() => setQuickOpenVisible(false)}
+                onSelect={handleQuickOpenSelect}
+            />
         </div>
     );
 }
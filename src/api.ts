@@ -8,10 +8,14 @@ export interface TreeNode {
     id: string;
     parent_id: string | null;
     name: string;
-    kind: "folder" | "document";
+    kind: "folder" | "document" | "attachment";
     content?: string;
     created_at: string;
     updated_at: string;
+    /** On-disk version token for documents; pass back to `updateDoc` to detect conflicts. */
+    revision?: string;
+    /** Parsed YAML/TOML front matter of a document (title, tags, status, ...). */
+    metadata?: Record<string, unknown>;
 }
 
 export interface SearchResult {
@@ -21,11 +25,295 @@ export interface SearchResult {
     kind: "folder" | "document";
     created_at: string;
     updated_at: string;
-    matched_line: string;
+    /** Relevance score; results arrive best first. */
+    score: number;
+    /** True if the file name matched the query. */
+    name_match: boolean;
+    /** The first 20 matching lines in the document. */
+    matches: SearchMatch[];
+    /** How many lines matched in all. */
+    total_matches: number;
+}
+
+/** Character offsets (0-based, end exclusive) within `SearchMatch.text`. */
+export interface HighlightRange {
+    start: number;
+    end: number;
+}
+
+export interface SearchMatch {
+    /** 1-based line number. */
+    line: number;
+    /** 1-based column (in characters) of the first highlight. */
+    column: number;
+    /** Byte offset of the first highlight in the document. */
+    offset: number;
+    text: string;
+    highlights: HighlightRange[];
+    context_before: string[];
+    context_after: string[];
+}
+
+/** One page of ranked search results. */
+export interface SearchPage {
+    total: number;
+    offset: number;
+    results: SearchResult[];
+}
+
+export interface QuickOpenResult {
+    id: string;
+    name: string;
+    root_id: string;
+    /** Workspace name followed by the path inside it, e.g. `Notes/guides/onboarding.md`. */
+    display_path: string;
+    score: number;
+    /** Matched character positions in `display_path`. */
+    positions: number[];
+}
+
+/** A link to a document from another document. */
+export interface Backlink {
+    /** Path of the linking document. */
+    id: string;
+    name: string;
+    /** 1-based position of the link in the linking document. */
+    line: number;
+    column: number;
+    /** The line containing the link. */
+    text: string;
+    heading: string | null;
+}
+
+/** Broken links and orphaned documents below a folder. */
+export interface LinkReport {
+    id: string;
+    documents: number;
+    links: number;
+    problems: LinkProblem[];
+    orphans: string[];
+}
+
+export interface LinkProblem {
+    /** Path of the document containing the link. */
+    id: string;
+    line: number;
+    column: number;
+    kind: "missing_target" | "missing_anchor";
+    /** The link as written. */
+    link: string;
+    embed: boolean;
+}
+
+export interface TagCount {
+    tag: string;
+    count: number;
+}
+
+export interface UpdateFailure {
+    id: string;
+    error: string;
+}
+
+export interface TagRenameResult {
+    from: string;
+    to: string;
+    /** Paths of the documents that were rewritten. */
+    changed: string[];
+    failed: UpdateFailure[];
+}
+
+/** A renamed or moved entry, with the documents whose links were rewritten to follow it. */
+export interface MovedEntry extends TreeNode {
+    updated_links?: string[];
+    link_failures?: UpdateFailure[];
 }
 
 export type ExportFormat = "html" | "pdf";
 
+export interface ExportOptions {
+    /** Inline the bundled Mermaid runtime so the HTML renders offline; PDFs always are. */
+    self_contained?: boolean;
+    /** Pre-render Mermaid diagrams to inline SVG (needs Chrome); PDFs always are. */
+    static_diagrams?: boolean;
+    /** Seconds a PDF export waits for fonts, images and diagrams (default 30). */
+    ready_timeout_secs?: number;
+    /** Page layout of PDF exports (default A4 portrait, 0.5in margins). */
+    pdf?: PdfOptions;
+}
+
+export type PaperSize =
+    | "a3"
+    | "a4"
+    | "a5"
+    | "letter"
+    | "legal"
+    | "tabloid"
+    /** Width and height in inches. */
+    | { custom: { width: number; height: number } };
+
+/** Margins in inches. */
+export interface PdfMargins {
+    top: number;
+    bottom: number;
+    left: number;
+    right: number;
+}
+
+/**
+ * PDF page layout. Header and footer templates are HTML; Chrome fills elements
+ * with the classes `title`, `date`, `url`, `pageNumber` and `totalPages`.
+ */
+export interface PdfOptions {
+    paper?: PaperSize;
+    margins?: PdfMargins;
+    orientation?: "portrait" | "landscape";
+    /** 0.1 to 2 (default 1). */
+    scale?: number;
+    header_template?: string;
+    footer_template?: string;
+    /** Default true. */
+    print_background?: boolean;
+}
+
+export type DiagramFormat = "svg" | "png";
+
+/** A Mermaid block that could not be rendered; it is exported as its source. */
+export interface DiagramError {
+    /** 0-based position among the document's Mermaid blocks. */
+    index: number;
+    message: string;
+}
+
+export interface ExportReport {
+    diagram_errors: DiagramError[];
+}
+
+/** Structured errors raised by the storage layer (serialized as JSON strings). */
+export type StorageError =
+    | {
+          code: "conflict";
+          message: string;
+          id: string;
+          expected_revision: string;
+          current_revision: string;
+          current_content: string;
+      }
+    | {
+          code: "invalid_query";
+          message: string;
+          query: string;
+          /** 0-based character offset of the problem in the query. */
+          position: number;
+          reason: string;
+      }
+    | {
+          /** The path or name reaches outside the registered workspace roots. */
+          code: "permission_denied";
+          message: string;
+          path: string;
+          reason: string;
+      }
+    | {
+          /** A create, rename or move would replace the entry at `path`. */
+          code: "already_exists";
+          message: string;
+          path: string;
+      }
+    | {
+          /** Mermaid rejected diagram `index` (0-based) of the document. */
+          code: "invalid_diagram";
+          message: string;
+          index: number;
+          reason: string;
+      }
+    | {
+          /** An export's page was still loading `pending` (fonts, images, diagrams) after the timeout. */
+          code: "render_timeout";
+          message: string;
+          timeout_secs: number;
+          pending: string[];
+      };
+
+/**
+ * What a create, rename or move does when the destination name is taken:
+ * fail with `already_exists` (the default), use a free name like
+ * `Note (2).md`, or move the existing entry to the trash.
+ */
+export type CollisionPolicy = "fail" | "auto_suffix" | "overwrite";
+
+/** Parse a rejected command's error into a `StorageError`, or null for plain messages. */
+export function parseStorageError(error: unknown): StorageError | null {
+    if (typeof error !== "string" || !error.startsWith("{")) return null;
+    try {
+        const parsed = JSON.parse(error);
+        return typeof parsed?.code === "string" ? (parsed as StorageError) : null;
+    } catch {
+        return null;
+    }
+}
+
+/** Human-readable text of a rejected command's error, structured or not. */
+export function errorMessage(error: unknown): string {
+    return parseStorageError(error)?.message ?? String(error);
+}
+
+/** A file saved into a workspace's assets folder. */
+export interface ImportedAttachment {
+    entry: TreeNode;
+    /** Relative link to insert into the document, e.g. `![image](assets/image.png)`. */
+    markdown: string;
+}
+
+/** Files treated as documents: by extension, or by exact name for files without one. */
+export interface DocumentTypes {
+    /** Without the dot, e.g. "md"; the first one is used for new documents. */
+    extensions: string[];
+    /** e.g. "README" */
+    file_names: string[];
+}
+
+/** Per-workspace listing options; `.gitignore`, `.ignore` and `.markdownuiignore` apply as well. */
+export interface FilterSettings {
+    /** Globs (gitignore syntax) shown even when ignored; with any set, other files are hidden. */
+    include: string[];
+    /** Globs always hidden, e.g. "node_modules". */
+    exclude: string[];
+    /** Show dot-prefixed entries. */
+    show_hidden: boolean;
+}
+
+export interface TrashEntry {
+    /** Trash id, passed to `restoreTrashEntry` / `purgeTrash`. */
+    id: string;
+    root_id: string;
+    original_path: string;
+    name: string;
+    kind: "folder" | "document" | "attachment";
+    deleted_at: string;
+    deleted_at_unix: number;
+}
+
+export interface HistoryRevision {
+    revision: number;
+    created_at_unix: number;
+    size: number;
+    origin: "baseline" | "app" | "mcp" | "restore";
+    /** Path the document had when the revision was recorded. */
+    path: string;
+}
+
+export type FsChangeKind = "created" | "modified" | "removed" | "renamed";
+
+/** One coalesced on-disk change, delivered in batches via the `fs-changes` event. */
+export interface FsChange {
+    kind: FsChangeKind;
+    id: string;
+    parent_id: string | null;
+    /** Previous path, only present for renames. */
+    old_id?: string;
+}
+
 // ── Filesystem storage API ───────────────────────────────────────────────────
 
 export async function listRoots(): Promise<TreeNode[]> {
@@ -40,6 +328,25 @@ export async function removeRoot(id: string): Promise<boolean> {
     return invoke<boolean>("storage_remove_root", { id });
 }
 
+/** Document types of a workspace root, or the global setting when `rootId` is omitted. */
+export async function getDocumentTypes(rootId?: string): Promise<DocumentTypes> {
+    return invoke<DocumentTypes>("storage_get_document_types", { rootId: rootId ?? null });
+}
+
+/** Pass `null` to clear a root's override, or to restore the global defaults. */
+export async function setDocumentTypes(types: DocumentTypes | null, rootId?: string): Promise<DocumentTypes> {
+    return invoke<DocumentTypes>("storage_set_document_types", { rootId: rootId ?? null, types });
+}
+
+export async function getWorkspaceFilter(rootId: string): Promise<FilterSettings> {
+    return invoke<FilterSettings>("storage_get_workspace_filter", { rootId });
+}
+
+/** Rejects invalid globs; the search index catches up in the background. */
+export async function setWorkspaceFilter(rootId: string, settings: FilterSettings): Promise<FilterSettings> {
+    return invoke<FilterSettings>("storage_set_workspace_filter", { rootId, settings });
+}
+
 export async function getEntry(id: string): Promise<TreeNode | null> {
     return invoke<TreeNode | null>("storage_get_entry", { id });
 }
@@ -51,40 +358,223 @@ export async function listChildren(parentId: string): Promise<TreeNode[]> {
 export async function createFolderEntry(
     parentId: string,
     name: string,
+    onCollision?: CollisionPolicy,
 ): Promise<TreeNode> {
-    return invoke<TreeNode>("storage_create_folder", { parentId, name });
+    return invoke<TreeNode>("storage_create_folder", { parentId, name, onCollision: onCollision ?? null });
 }
 
 export async function createDocEntry(
     parentId: string,
     name: string,
     content: string,
+    onCollision?: CollisionPolicy,
 ): Promise<TreeNode> {
-    return invoke<TreeNode>("storage_create_document", { parentId, name, content });
+    return invoke<TreeNode>("storage_create_document", {
+        parentId,
+        name,
+        content,
+        onCollision: onCollision ?? null,
+    });
 }
 
 export async function updateDoc(
     id: string,
     name: string,
     content: string,
+    expectedRevision?: string,
+    onCollision?: CollisionPolicy,
+): Promise<TreeNode> {
+    return invoke<TreeNode>("storage_update_document", {
+        id,
+        name,
+        content,
+        expectedRevision: expectedRevision ?? null,
+        onCollision: onCollision ?? null,
+    });
+}
+
+/** Front matter of a document; empty when it has none. */
+export async function getMetadata(id: string): Promise<Record<string, unknown>> {
+    return invoke<Record<string, unknown>>("storage_get_metadata", { id });
+}
+
+/** Set front matter keys without touching the body; a `null` value removes the key. */
+export async function updateMetadata(
+    id: string,
+    updates: Record<string, unknown>,
+    expectedRevision?: string,
 ): Promise<TreeNode> {
-    return invoke<TreeNode>("storage_update_document", { id, name, content });
+    return invoke<TreeNode>("storage_update_metadata", {
+        id,
+        updates,
+        expectedRevision: expectedRevision ?? null,
+    });
 }
 
-export async function renameEntry(id: string, newName: string): Promise<TreeNode> {
-    return invoke<TreeNode>("storage_rename_entry", { id, newName });
+export async function renameEntry(
+    id: string,
+    newName: string,
+    updateLinks?: boolean,
+    onCollision?: CollisionPolicy,
+): Promise<MovedEntry> {
+    return invoke<MovedEntry>("storage_rename_entry", {
+        id,
+        newName,
+        updateLinks: updateLinks ?? null,
+        onCollision: onCollision ?? null,
+    });
 }
 
 export async function deleteEntry(id: string): Promise<boolean> {
     return invoke<boolean>("storage_delete_entry", { id });
 }
 
-export async function moveEntry(id: string, newParentId: string): Promise<TreeNode> {
-    return invoke<TreeNode>("storage_move_entry", { id, newParentId });
+export async function moveEntry(
+    id: string,
+    newParentId: string,
+    updateLinks?: boolean,
+    onCollision?: CollisionPolicy,
+): Promise<MovedEntry> {
+    return invoke<MovedEntry>("storage_move_entry", {
+        id,
+        newParentId,
+        updateLinks: updateLinks ?? null,
+        onCollision: onCollision ?? null,
+    });
+}
+
+export async function copyEntry(id: string, newParentId: string): Promise<TreeNode> {
+    return invoke<TreeNode>("storage_copy_entry", { id, newParentId });
+}
+
+export async function duplicateEntry(id: string): Promise<TreeNode> {
+    return invoke<TreeNode>("storage_duplicate_entry", { id });
+}
+
+export async function listTrash(rootId?: string): Promise<TrashEntry[]> {
+    return invoke<TrashEntry[]>("storage_list_trash", { rootId: rootId ?? null });
+}
+
+export async function restoreTrashEntry(trashId: string, rootId?: string): Promise<TreeNode> {
+    return invoke<TreeNode>("storage_restore_trash_entry", { trashId, rootId: rootId ?? null });
+}
+
+/** Permanently delete one trashed entry, or the whole trash when `trashId` is omitted. */
+export async function purgeTrash(rootId?: string, trashId?: string): Promise<number> {
+    return invoke<number>("storage_purge_trash", {
+        rootId: rootId ?? null,
+        trashId: trashId ?? null,
+    });
+}
+
+export async function getTrashRetention(): Promise<number | null> {
+    return invoke<number | null>("get_trash_retention");
+}
+
+export async function setTrashRetention(days: number | null): Promise<void> {
+    return invoke<void>("set_trash_retention", { days });
+}
+
+export async function getAssetsFolder(): Promise<string> {
+    return invoke<string>("get_assets_folder");
+}
+
+/** `assets` for a folder at the top of each workspace root, `./assets` for one next to each document. */
+export async function setAssetsFolder(folder: string): Promise<void> {
+    return invoke<void>("set_assets_folder", { folder });
+}
+
+/** Saved PDF layouts by name. */
+export async function getPdfPresets(): Promise<Record<string, PdfOptions>> {
+    return invoke<Record<string, PdfOptions>>("get_pdf_presets");
+}
+
+/** Save `options` as the preset `name`, replacing one of the same name. */
+export async function savePdfPreset(name: string, options: PdfOptions): Promise<void> {
+    return invoke<void>("save_pdf_preset", { name, options });
+}
+
+export async function deletePdfPreset(name: string): Promise<void> {
+    return invoke<void>("delete_pdf_preset", { name });
+}
+
+/** Save a pasted or dropped file into the assets folder of `documentId`'s workspace. */
+export async function importAttachment(documentId: string, file: Blob, fileName: string): Promise<ImportedAttachment> {
+    const bytes = new Uint8Array(await file.arrayBuffer());
+    let binary = "";
+    for (let i = 0; i < bytes.length; i += 0x8000) {
+        binary += String.fromCharCode(...bytes.subarray(i, i + 0x8000));
+    }
+    return invoke<ImportedAttachment>("storage_import_attachment", {
+        documentId,
+        fileName,
+        dataBase64: btoa(binary),
+    });
+}
+
+export async function listHistory(id: string): Promise<HistoryRevision[]> {
+    return invoke<HistoryRevision[]>("storage_list_history", { id });
+}
+
+export async function getHistoryRevision(id: string, revision: number): Promise<string> {
+    return invoke<string>("storage_get_history_revision", { id, revision });
+}
+
+/** Unified diff from `from` to `to`, or to the current file when `to` is omitted. */
+export async function diffHistory(id: string, from: number, to?: number): Promise<string> {
+    return invoke<string>("storage_diff_history", { id, from, to: to ?? null });
+}
+
+export async function restoreHistory(id: string, revision: number): Promise<TreeNode> {
+    return invoke<TreeNode>("storage_restore_history", { id, revision });
+}
+
+export async function searchEntries(
+    query: string,
+    offset?: number,
+    limit?: number,
+    contextLines?: number,
+): Promise<SearchPage> {
+    return invoke<SearchPage>("storage_search", {
+        query,
+        offset: offset ?? null,
+        limit: limit ?? null,
+        contextLines: contextLines ?? null,
+    });
+}
+
+/** Fuzzy-match document paths; an empty query lists recently opened files. */
+export async function quickOpen(query: string, limit?: number): Promise<QuickOpenResult[]> {
+    return invoke<QuickOpenResult[]>("storage_quick_open", { query, limit: limit ?? null });
 }
 
-export async function searchEntries(query: string): Promise<SearchResult[]> {
-    return invoke<SearchResult[]>("storage_search", { query });
+export async function recordOpened(id: string): Promise<void> {
+    return invoke<void>("storage_record_opened", { id });
+}
+
+/** `[[wiki links]]` and relative markdown links pointing at a document. */
+export async function getBacklinks(id: string): Promise<Backlink[]> {
+    return invoke<Backlink[]>("storage_get_backlinks", { id });
+}
+
+/** Check every document below a root or folder for broken links, missing anchors and orphans. */
+export async function checkLinks(id: string): Promise<LinkReport> {
+    return invoke<LinkReport>("storage_check_links", { id });
+}
+
+/** Every tag across all workspaces with its document count, sorted by tag. */
+export async function listTags(): Promise<TagCount[]> {
+    return invoke<TagCount[]>("storage_list_tags");
+}
+
+/** Documents tagged `tag`, including nested tags (`tag/...`). */
+export async function documentsForTag(tag: string): Promise<TreeNode[]> {
+    return invoke<TreeNode[]>("storage_documents_for_tag", { tag });
+}
+
+/** Rename (or, onto an existing tag, merge) a tag in every document using it. */
+export async function renameTag(from: string, to: string): Promise<TagRenameResult> {
+    return invoke<TagRenameResult>("storage_rename_tag", { from, to });
 }
 
 // ── Export ───────────────────────────────────────────────────────────────────
@@ -93,8 +583,19 @@ export async function exportDocToFile(
     id: string,
     format: ExportFormat,
     outputPath: string,
+    options?: ExportOptions,
+): Promise<ExportReport> {
+    return invoke<ExportReport>("storage_export_document", { id, format, outputPath, options: options ?? null });
+}
+
+/** Render Mermaid block `index` (0-based) of a document to an image file. */
+export async function exportDiagram(
+    id: string,
+    index: number,
+    format: DiagramFormat,
+    outputPath: string,
 ): Promise<void> {
-    return invoke<void>("storage_export_document", { id, format, outputPath });
+    return invoke<void>("storage_export_diagram", { id, index, format, outputPath });
 }
 
 export async function exportMarkdown(
@@ -137,6 +638,13 @@ export async function setMcpPort(port: number): Promise<void> {
     return invoke<void>("set_mcp_port", { port });
 }
 
+// ── Config ───────────────────────────────────────────────────────────────────
+
+/** Message explaining why settings could not be loaded at startup (returned once). */
+export async function takeConfigWarning(): Promise<string | null> {
+    return invoke<string | null>("take_config_warning");
+}
+
 // ── PDF availability ─────────────────────────────────────────────────────────
 
 export async function checkPdfAvailable(): Promise<boolean> {
@@ -4,6 +4,14 @@ import DocumentPreview from "./DocumentPreview";
 import ResizableSplit from "./ResizableSplit";
 import { useTheme } from "../ThemeContext";
 import { slugify } from "../utils/slugify";
+import type { DiagramFormat } from "../api";
+
+/** A location to select in the editor (1-based line/column, length in characters). */
+export interface EditorPosition {
+    line: number;
+    column: number;
+    length: number;
+}
 
 interface DocumentEditorProps {
     content: string;
@@ -11,7 +19,13 @@ interface DocumentEditorProps {
     zoomLevel: number;
     scrollToHeadingId?: string | null;
     onHeadingScrolled?: () => void;
+    revealPosition?: EditorPosition | null;
+    onPositionRevealed?: () => void;
     mcpFlash?: boolean;
+    /** Save a pasted or dropped file and return the markdown linking it, or null to skip it. */
+    onImportFile?: (file: File) => Promise<string | null>;
+    /** Save Mermaid block `index` (0-based) of the document as an image. */
+    onExportDiagram?: (index: number, format: DiagramFormat) => void;
 }
 
 export default function DocumentEditor({
@@ -20,11 +34,18 @@ export default function DocumentEditor({
     zoomLevel,
     scrollToHeadingId,
     onHeadingScrolled,
+    revealPosition,
+    onPositionRevealed,
     mcpFlash,
+    onImportFile,
+    onExportDiagram,
 }: DocumentEditorProps) {
     const previewRef = useRef<HTMLDivElement>(null);
     const { theme } = useTheme();
     const editorRef = useRef<any>(null);
+    // The mount handler runs once; read the latest callback through a ref
+    const onImportFileRef = useRef(onImportFile);
+    onImportFileRef.current = onImportFile;
 
     // Define custom theme before Monaco editor mounts
     const handleEditorBeforeMount = (monaco: any) => {
@@ -60,6 +81,34 @@ export default function DocumentEditor({
     // Handle Monaco Editor mount
     const handleEditorMount = (editor: any) => {
         editorRef.current = editor;
+
+        // Pasted or dropped files (images, PDFs, ...) become attachments linked at the cursor
+        const importFiles = (files: File[], e: Event) => {
+            const importFile = onImportFileRef.current;
+            if (!importFile || files.length === 0) return;
+            e.preventDefault();
+            e.stopPropagation();
+            (async () => {
+                for (const file of files) {
+                    const markdown = await importFile(file);
+                    if (!markdown) continue;
+                    editor.executeEdits("import-attachment", [
+                        { range: editor.getSelection(), text: markdown, forceMoveMarkers: true },
+                    ]);
+                }
+            })();
+        };
+        const node: HTMLElement | null = editor.getDomNode();
+        node?.addEventListener(
+            "paste",
+            (e: ClipboardEvent) => importFiles(Array.from(e.clipboardData?.files ?? []), e),
+            true
+        );
+        node?.addEventListener(
+            "drop",
+            (e: DragEvent) => importFiles(Array.from(e.dataTransfer?.files ?? []), e),
+            true
+        );
     };
 
     // Trigger layout refresh when zoom level changes
@@ -91,6 +140,22 @@ export default function DocumentEditor({
         }
     }, [scrollToHeadingId, content]);
 
+    // Select a search match once its document is loaded
+    useEffect(() => {
+        if (!revealPosition || !editorRef.current) return;
+        const { line, column, length } = revealPosition;
+        if (line > content.split("\n").length) return;
+        editorRef.current.revealLineInCenter(line);
+        editorRef.current.setSelection({
+            startLineNumber: line,
+            startColumn: column,
+            endLineNumber: line,
+            endColumn: column + length,
+        });
+        editorRef.current.focus();
+        onPositionRevealed?.();
+    }, [revealPosition, content]);
+
     // Navigate to specific line
     const handleNavigateToLine = (line: number) => {
         if (editorRef.current) {
@@ -149,6 +214,7 @@ export default function DocumentEditor({
                                     onNavigateToLine={handleNavigateToLine}
                                     scrollToHeadingId={scrollToHeadingId}
                                     onHeadingScrolled={onHeadingScrolled}
+                                    onExportDiagram={onExportDiagram}
                                 />
                             </div>
                         </div>
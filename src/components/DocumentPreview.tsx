@@ -10,6 +10,7 @@ import oneLight from "react-syntax-highlighter/dist/esm/styles/prism/one-light";
 import TurndownService from "turndown";
 import { useTheme } from "../ThemeContext";
 import { slugify } from "../utils/slugify";
+import type { DiagramFormat } from "../api";
 
 interface DocumentPreviewProps {
     content: string;
@@ -17,6 +18,8 @@ interface DocumentPreviewProps {
     onNavigateToLine?: (line: number) => void;
     scrollToHeadingId?: string | null;
     onHeadingScrolled?: () => void;
+    /** Save Mermaid block `index` (0-based, in document order) as an image. */
+    onExportDiagram?: (index: number, format: DiagramFormat) => void;
 }
 
 // Context shares heading-slug data + callbacks with the hoisted HeadingRenderer,
@@ -106,7 +109,15 @@ function YamlFrontmatter({ yaml, theme }: { yaml: string; theme: 'light' | 'dark
     );
 }
 
-function MermaidDiagram({ code, theme }: { code: string; theme: 'light' | 'dark' }) {
+function MermaidDiagram({
+    code,
+    theme,
+    onExport,
+}: {
+    code: string;
+    theme: 'light' | 'dark';
+    onExport?: (format: DiagramFormat) => void;
+}) {
     const containerRef = useRef<HTMLDivElement>(null);
     const [error, setError] = useState<string | null>(null);
 
@@ -147,11 +158,32 @@ function MermaidDiagram({ code, theme }: { code: string; theme: 'light' | 'dark'
         );
     }
 
-    return <div ref={containerRef} className="mermaid-container" />;
+    if (!onExport) return <div ref={containerRef} className="mermaid-container" />;
+
+    return (
+        <div className="group relative">
+            <div ref={containerRef} className="mermaid-container" />
+            <div className="absolute top-2 right-2 hidden group-hover:flex gap-1">
+                {(["svg", "png"] as const).map((format) => (
+                    <button
+                        key={format}
+                        onClick={() => onExport(format)}
+                        className={`px-2 py-0.5 rounded text-xs ${theme === 'dark'
+                            ? 'bg-gray-700 text-gray-200 hover:bg-gray-600'
+                            : 'bg-white text-gray-600 border border-gray-200 hover:bg-gray-100'
+                            }`}
+                        title={`Export diagram as ${format.toUpperCase()}`}
+                    >
+                        {format.toUpperCase()}
+                    </button>
+                ))}
+            </div>
+        </div>
+    );
 }
 
 const DocumentPreview = forwardRef<HTMLDivElement, DocumentPreviewProps>(
-    function DocumentPreview({ content, zoomLevel = 1.0, onNavigateToLine, scrollToHeadingId, onHeadingScrolled }, ref) {
+    function DocumentPreview({ content, zoomLevel = 1.0, onNavigateToLine, scrollToHeadingId, onHeadingScrolled, onExportDiagram }, ref) {
         const previewRef = useRef<HTMLDivElement>(null);
         const combinedRef = useCombinedRefs(ref, previewRef);
 
@@ -204,6 +236,21 @@ const DocumentPreview = forwardRef<HTMLDivElement, DocumentPreviewProps>(
             return result;
         }, [markdownBody]);
 
+        // Lines (1-based, in markdownBody) opening a Mermaid block; a block's
+        // position in this list is the index the diagram export uses. Read
+        // through refs so the renderers below (and the diagrams they mount)
+        // aren't recreated on every edit.
+        const mermaidLinesRef = useRef<number[]>([]);
+        mermaidLinesRef.current = useMemo(() => {
+            const lines: number[] = [];
+            markdownBody.split('\n').forEach((line, i) => {
+                if (line.trim() === '```mermaid') lines.push(i + 1);
+            });
+            return lines;
+        }, [markdownBody]);
+        const onExportDiagramRef = useRef(onExportDiagram);
+        onExportDiagramRef.current = onExportDiagram;
+
         const [isDragging, setIsDragging] = useState(false);
         const [startX, setStartX] = useState(0);
         const [startY, setStartY] = useState(0);
@@ -228,7 +275,7 @@ const DocumentPreview = forwardRef<HTMLDivElement, DocumentPreviewProps>(
             h6: HeadingRenderer,
             // Fenced/indented code blocks. Unqualified fences (no language) render
             // as a plain "text" block via the syntax highlighter.
-            pre({ children }: any) {
+            pre({ node, children }: any) {
                 const child = Array.isArray(children) ? children[0] : children;
                 const className: string = child?.props?.className || "";
                 const match = /language-(\w+)/.exec(className);
@@ -243,7 +290,11 @@ const DocumentPreview = forwardRef<HTMLDivElement, DocumentPreviewProps>(
 
                 // Handle mermaid code blocks
                 if (language === "mermaid") {
-                    return <MermaidDiagram code={codeString} theme={theme} />;
+                    const index = mermaidLinesRef.current.indexOf(node?.position?.start?.line);
+                    const onExport = onExportDiagramRef.current && index >= 0
+                        ? (format: DiagramFormat) => onExportDiagramRef.current?.(index, format)
+                        : undefined;
+                    return <MermaidDiagram code={codeString} theme={theme} onExport={onExport} />;
                 }
 
                 return (
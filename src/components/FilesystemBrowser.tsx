@@ -1,22 +1,28 @@
 import { createContext, useContext, useEffect, useRef, useState } from "react";
-import type { TreeNode, SearchResult } from "../api";
+import type { TreeNode, SearchResult, SearchMatch, HighlightRange } from "../api";
 import * as api from "../api";
 import { parseHeadings } from "../utils/headings";
 import { getParentPath } from "../utils/paths";
 import InlineRename from "./InlineRename";
 import IconAction from "./IconAction";
 import { useToast } from "../contexts/ToastContext";
+import { useSettings } from "../contexts/SettingsContext";
+import { useFsChanges } from "../hooks/useFsChanges";
 
 interface FilesystemBrowserProps {
     roots: TreeNode[];
     selectedDocId: string | null;
     onDocumentSelect: (doc: TreeNode | null) => void;
     onHeadingClick?: (docId: string, headingId: string) => void;
+    onSearchMatchClick?: (docId: string, match: SearchMatch) => void;
     onRootsChanged?: () => void;
     onRemoveWorkspaceRoot?: (id: string) => Promise<void>;
     mcpAnimatingIds?: Set<string>;
 }
 
+/** Search results show at most this many matching lines each. */
+const MAX_VISIBLE_MATCHES = 3;
+
 // ── Tree refresh/drag context ────────────────────────────────────────────────
 
 interface TreeContextValue {
@@ -43,6 +49,81 @@ function isDescendant(ancestor: string, path: string): boolean {
     return path.startsWith(ancestor + "/") || path.startsWith(ancestor + "\\");
 }
 
+// ── Search highlighting ──────────────────────────────────────────────────────
+
+/** Render `text` with the highlighted character ranges (shifted left by `offset`) marked. */
+function HighlightedLine({
+    text,
+    offset,
+    highlights,
+}: {
+    text: string;
+    offset: number;
+    highlights: HighlightRange[];
+}) {
+    // Ranges count characters, not UTF-16 units
+    const chars = Array.from(text);
+    const parts: JSX.Element[] = [];
+    let pos = 0;
+    highlights.forEach((h, i) => {
+        const start = Math.max(h.start - offset, pos);
+        const end = Math.min(h.end - offset, chars.length);
+        if (end <= start) return;
+        if (start > pos) parts.push(<span key={`t${i}`}>{chars.slice(pos, start).join("")}</span>);
+        parts.push(
+            <mark key={`h${i}`} className="bg-yellow-200 dark:bg-yellow-700 text-inherit rounded-sm">
+                {chars.slice(start, end).join("")}
+            </mark>
+        );
+        pos = end;
+    });
+    if (pos < chars.length) parts.push(<span key="rest">{chars.slice(pos).join("")}</span>);
+    return <>{parts}</>;
+}
+
+/** Front matter `tags`: a list, or a comma-separated string. */
+function frontMatterTags(metadata: TreeNode["metadata"]): string[] {
+    const tags = metadata?.tags;
+    if (Array.isArray(tags)) return tags.filter((t): t is string => typeof t === "string");
+    if (typeof tags === "string") return tags.split(",").map((t) => t.trim()).filter(Boolean);
+    return [];
+}
+
+/** Toast message for the link rewrites that followed a rename or move, if any. */
+function linkUpdateMessage(moved: api.MovedEntry): string | null {
+    const updated = moved.updated_links?.length ?? 0;
+    const failed = moved.link_failures?.length ?? 0;
+    if (failed > 0) {
+        return `Updated links in ${updated} file${updated === 1 ? "" : "s"}; ${failed} could not be updated`;
+    }
+    return updated > 0 ? `Updated links in ${updated} file${updated === 1 ? "" : "s"}` : null;
+}
+
+/** Duplicate an entry next to itself ("Note (copy)") and refresh its folder. */
+function useDuplicateEntry() {
+    const { refreshPath } = useTreeContext();
+    const { showToast } = useToast();
+    return async (id: string) => {
+        try {
+            await api.duplicateEntry(id);
+            const parent = getParentPath(id);
+            if (parent) refreshPath(parent);
+        } catch (err) {
+            console.error("Duplicate failed:", err);
+            showToast(`Duplicate failed: ${api.errorMessage(err)}`, "error");
+        }
+    };
+}
+
+function DuplicateAction({ onClick }: { onClick: () => void }) {
+    return (
+        <IconAction title="Duplicate" onClick={onClick}>
+            <rect x="9" y="9" width="13" height="13" rx="2" ry="2" />
+            <path d="M5 15H4a2 2 0 0 1-2-2V4a2 2 0 0 1 2-2h9a2 2 0 0 1 2 2v1" />
+        </IconAction>
+    );
+}
+
 // ── Main component ───────────────────────────────────────────────────────────
 
 export default function FilesystemBrowser({
@@ -50,6 +131,7 @@ export default function FilesystemBrowser({
     selectedDocId,
     onDocumentSelect,
     onHeadingClick,
+    onSearchMatchClick,
     onRootsChanged,
     onRemoveWorkspaceRoot,
     mcpAnimatingIds,
@@ -61,21 +143,30 @@ export default function FilesystemBrowser({
     const [query, setQuery] = useState("");
     const [results, setResults] = useState<SearchResult[] | null>(null);
     const [searching, setSearching] = useState(false);
+    const [queryError, setQueryError] = useState<string | null>(null);
 
     // Debounced search
     useEffect(() => {
         const trimmed = query.trim();
         if (!trimmed) {
             setResults(null);
+            setQueryError(null);
             return;
         }
         setSearching(true);
         debounceRef.current = setTimeout(async () => {
             try {
                 const r = await api.searchEntries(trimmed);
-                setResults(r);
+                setResults(r.results);
+                setQueryError(null);
             } catch (err) {
-                console.error("Search failed:", err);
+                const storageError = api.parseStorageError(err);
+                if (storageError?.code === "invalid_query") {
+                    setQueryError(storageError.message);
+                } else {
+                    console.error("Search failed:", err);
+                    setQueryError(null);
+                }
                 setResults([]);
             } finally {
                 setSearching(false);
@@ -92,9 +183,16 @@ export default function FilesystemBrowser({
         debounceRef.current = setTimeout(async () => {
             try {
                 const r = await api.searchEntries(trimmed);
-                setResults(r);
+                setResults(r.results);
+                setQueryError(null);
             } catch (err) {
-                console.error("Search failed:", err);
+                const storageError = api.parseStorageError(err);
+                if (storageError?.code === "invalid_query") {
+                    setQueryError(storageError.message);
+                } else {
+                    console.error("Search failed:", err);
+                    setQueryError(null);
+                }
                 setResults([]);
             } finally {
                 setSearching(false);
@@ -124,10 +222,27 @@ export default function FilesystemBrowser({
         return () => document.removeEventListener("keydown", handler);
     }, []);
 
-    const handleResultClick = async (result: SearchResult) => {
+    // Refresh expanded folders whose contents changed on disk
+    useFsChanges((changes) => {
+        const dirs = new Set<string>();
+        for (const change of changes) {
+            if (change.parent_id) dirs.add(change.parent_id);
+            if (change.old_id) {
+                const oldParent = getParentPath(change.old_id);
+                if (oldParent) dirs.add(oldParent);
+            }
+        }
+        dirs.forEach((dir) => registryRef.current.get(dir)?.());
+    });
+
+    const handleResultClick = async (result: SearchResult, match?: SearchMatch) => {
         try {
-            const entry = await api.getEntry(result.id);
-            if (entry) onDocumentSelect(entry);
+            if (match && onSearchMatchClick) {
+                onSearchMatchClick(result.id, match);
+            } else {
+                const entry = await api.getEntry(result.id);
+                if (entry) onDocumentSelect(entry);
+            }
         } catch (err) {
             console.error("Failed to open search result:", err);
         }
@@ -187,7 +302,12 @@ export default function FilesystemBrowser({
                                     Searching...
                                 </div>
                             )}
-                            {results !== null && results.length === 0 && (
+                            {queryError && (
+                                <div className="px-4 py-3 text-sm text-red-500 dark:text-red-400">
+                                    {queryError}
+                                </div>
+                            )}
+                            {!queryError && results !== null && results.length === 0 && (
                                 <div className="px-4 py-3 text-sm text-gray-400 dark:text-gray-500">
                                     No results for "{query}"
                                 </div>
@@ -205,7 +325,22 @@ export default function FilesystemBrowser({
                                         </svg>
                                         <div className="flex-1 min-w-0">
                                             <div className="text-sm text-gray-700 dark:text-gray-300 truncate">{r.name}</div>
-                                            <div className="text-xs text-gray-400 dark:text-gray-500 italic truncate">{r.matched_line}</div>
+                                            {r.matches.slice(0, MAX_VISIBLE_MATCHES).map((m) => (
+                                                <div
+                                                    key={m.line}
+                                                    className="text-xs text-gray-400 dark:text-gray-500 truncate hover:text-gray-600 dark:hover:text-gray-300"
+                                                    title={`Line ${m.line}`}
+                                                    onClick={(e) => { e.stopPropagation(); handleResultClick(r, m); }}
+                                                >
+                                                    <span className="mr-1 tabular-nums">{m.line}:</span>
+                                                    <HighlightedLine text={m.text.trim()} offset={m.text.length - m.text.trimStart().length} highlights={m.highlights} />
+                                                </div>
+                                            ))}
+                                            {r.total_matches > MAX_VISIBLE_MATCHES && (
+                                                <div className="text-xs text-gray-400 dark:text-gray-500 italic">
+                                                    +{r.total_matches - MAX_VISIBLE_MATCHES} more
+                                                </div>
+                                            )}
                                         </div>
                                     </div>
                                 ))
@@ -241,6 +376,7 @@ export default function FilesystemBrowser({
 function useExpandableDir(dirId: string) {
     const { register, unregister } = useTreeContext();
     const { showToast } = useToast();
+    const { updateLinks } = useSettings();
     const [expanded, setExpanded] = useState(false);
     const [children, setChildren] = useState<TreeNode[] | null>(null);
     const [loading, setLoading] = useState(false);
@@ -286,16 +422,17 @@ function useExpandableDir(dirId: string) {
         }
         const placeholder = kind === "folder" ? "New Folder" : "New Document";
         try {
+            // A second placeholder becomes "New Document (2)" rather than failing.
             const node =
                 kind === "folder"
-                    ? await api.createFolderEntry(dirId, placeholder)
-                    : await api.createDocEntry(dirId, placeholder, "");
+                    ? await api.createFolderEntry(dirId, placeholder, "auto_suffix")
+                    : await api.createDocEntry(dirId, placeholder, "", "auto_suffix");
             await refreshChildren();
             setRenamingId(node.id);
             setNewlyCreatedId(node.id);
         } catch (err) {
             console.error("Failed to create entry:", err);
-            showToast(`Failed to create ${kind}: ${err}`, "error");
+            showToast(`Failed to create ${kind}: ${api.errorMessage(err)}`, "error");
         }
     };
 
@@ -304,11 +441,13 @@ function useExpandableDir(dirId: string) {
         const wasNew = newlyCreatedId === id;
         setNewlyCreatedId(null);
         try {
-            await api.renameEntry(id, newName);
+            const moved = await api.renameEntry(id, newName, updateLinks && !wasNew);
             await refreshChildren();
+            const message = linkUpdateMessage(moved);
+            if (message) showToast(message, moved.link_failures?.length ? "error" : "info");
         } catch (err) {
             console.error("Rename failed:", err);
-            showToast(`Rename failed: ${err}`, "error");
+            showToast(`Rename failed: ${api.errorMessage(err)}`, "error");
             if (wasNew) {
                 try {
                     await api.deleteEntry(id);
@@ -446,7 +585,7 @@ function DeleteConfirm({
         >
             <p className="text-xs text-red-700 dark:text-red-300 mb-1">
                 Delete "{entryName}"{isFolder ? " and all of its contents" : ""}
-                ? This cannot be undone.
+                ? It will be moved to the workspace trash.
             </p>
             <div className="flex gap-1.5">
                 <button
@@ -504,7 +643,9 @@ function FsDirBody({
     onRootsChanged?: () => void;
 }) {
     const { dragState, refreshPath, selectedDocId, onDocumentSelect } = useTreeContext();
+    const duplicateEntry = useDuplicateEntry();
     const { showToast } = useToast();
+    const { updateLinks } = useSettings();
     const [isDragOver, setIsDragOver] = useState(false);
     const [confirmDelete, setConfirmDelete] = useState(false);
     const indent = depth === 0 ? 12 : 28 + depth * 16;
@@ -523,18 +664,43 @@ function FsDirBody({
         setIsDragOver(false);
         const src = dragState.current;
         if (!src || !canDropHere()) return;
+        // Alt/Option-drag copies instead of moving.
+        if (e.altKey) {
+            try {
+                await api.copyEntry(src, node.id);
+                refreshPath(node.id);
+            } catch (err) {
+                console.error("Copy failed:", err);
+                showToast(`Copy failed: ${api.errorMessage(err)}`, "error");
+            }
+            dragState.current = null;
+            return;
+        }
         try {
-            await api.moveEntry(src, node.id);
+            const moved = await api.moveEntry(src, node.id, updateLinks);
             refreshPath(node.id);
             const srcParent = getParentPath(src);
             if (srcParent) refreshPath(srcParent);
+            const message = linkUpdateMessage(moved);
+            if (message) showToast(message, moved.link_failures?.length ? "error" : "info");
         } catch (err) {
             console.error("Move failed:", err);
-            showToast(`Move failed: ${err}`, "error");
+            showToast(`Move failed: ${api.errorMessage(err)}`, "error");
         }
         dragState.current = null;
     };
 
+    const toggleHiddenEntries = async () => {
+        try {
+            const filter = await api.getWorkspaceFilter(node.id);
+            await api.setWorkspaceFilter(node.id, { ...filter, show_hidden: !filter.show_hidden });
+            refreshPath(node.id);
+        } catch (err) {
+            console.error("Toggling hidden files failed:", err);
+            showToast(`Toggling hidden files failed: ${err}`, "error");
+        }
+    };
+
     const handleDelete = async () => {
         setConfirmDelete(false);
         try {
@@ -546,7 +712,7 @@ function FsDirBody({
             if (parent) refreshPath(parent);
         } catch (err) {
             console.error("Delete failed:", err);
-            showToast(`Delete failed: ${err}`, "error");
+            showToast(`Delete failed: ${api.errorMessage(err)}`, "error");
         }
     };
 
@@ -563,7 +729,7 @@ function FsDirBody({
                 onDragStart={(e) => {
                     if (isRoot) return;
                     dragState.current = node.id;
-                    e.dataTransfer.effectAllowed = "move";
+                    e.dataTransfer.effectAllowed = "copyMove";
                     try { e.dataTransfer.setData("text/plain", node.id); } catch { /* ignore */ }
                 }}
                 onDragEnd={() => { dragState.current = null; }}
@@ -571,7 +737,13 @@ function FsDirBody({
                 onDoubleClick={(e) => {
                     if (!isRoot) { e.stopPropagation(); onRenameStart(node.id); }
                 }}
-                onDragOver={(e) => { if (canDropHere()) { e.preventDefault(); setIsDragOver(true); } }}
+                onDragOver={(e) => {
+                    if (canDropHere()) {
+                        e.preventDefault();
+                        e.dataTransfer.dropEffect = e.altKey ? "copy" : "move";
+                        setIsDragOver(true);
+                    }
+                }}
                 onDragLeave={() => setIsDragOver(false)}
                 onDrop={handleDrop}
             >
@@ -630,6 +802,7 @@ function FsDirBody({
                             <line x1="12" y1="11" x2="12" y2="17" />
                             <line x1="9" y1="14" x2="15" y2="14" />
                         </IconAction>
+                        {!isRoot && <DuplicateAction onClick={() => duplicateEntry(node.id)} />}
                         {!isRoot && (
                             <IconAction title="Delete" danger onClick={() => setConfirmDelete(true)}>
                                 <polyline points="3 6 5 6 21 6" />
@@ -638,6 +811,12 @@ function FsDirBody({
                                 <path d="M9 6V4a1 1 0 0 1 1-1h4a1 1 0 0 1 1 1v2" />
                             </IconAction>
                         )}
+                        {isRoot && (
+                            <IconAction title="Show/hide hidden files" onClick={toggleHiddenEntries}>
+                                <path d="M1 12s4-8 11-8 11 8 11 8-4 8-11 8-11-8-11-8z" />
+                                <circle cx="12" cy="12" r="3" />
+                            </IconAction>
+                        )}
                         {isRoot && onRemoveWorkspaceRoot && (
                             <IconAction title="Remove root from sidebar" danger
                                 onClick={async () => { await onRemoveWorkspaceRoot(node.id); onRootsChanged?.(); }}
@@ -673,6 +852,17 @@ function FsDirBody({
                                 onHeadingClick={onHeadingClick}
                                 mcpAnimatingIds={mcpAnimatingIds}
                             />
+                        ) : child.kind === "attachment" ? (
+                            <FsAttachmentRow
+                                key={child.id}
+                                node={child}
+                                depth={depth + 1}
+                                mcpAnimatingIds={mcpAnimatingIds}
+                                renaming={renamingId === child.id}
+                                onRenameStart={() => onRenameStart(child.id)}
+                                onRenameCommit={(v) => onRenameCommit(child.id, v)}
+                                onRenameCancel={() => onRenameCancel(child.id)}
+                            />
                         ) : (
                             <FsDocumentRow
                                 key={child.id}
@@ -720,11 +910,15 @@ function FsDocumentRow({
     onRenameCancel: () => void;
 }) {
     const { dragState, refreshPath, selectedDocId, onDocumentSelect } = useTreeContext();
+    const duplicateEntry = useDuplicateEntry();
     const { showToast } = useToast();
     const [tocExpanded, setTocExpanded] = useState(false);
     const [docHeadings, setDocHeadings] = useState<import("../utils/headings").Heading[] | null>(null);
     const [confirmDelete, setConfirmDelete] = useState(false);
     const indent = 28 + depth * 16;
+    const title = doc.metadata?.title;
+    const metaTitle = typeof title === "string" ? title : undefined;
+    const metaTags = frontMatterTags(doc.metadata);
 
     const loadHeadings = async () => {
         if (docHeadings !== null) return;
@@ -756,7 +950,7 @@ function FsDocumentRow({
             if (parent) refreshPath(parent);
         } catch (err) {
             console.error("Delete failed:", err);
-            showToast(`Delete failed: ${err}`, "error");
+            showToast(`Delete failed: ${api.errorMessage(err)}`, "error");
         }
     };
 
@@ -766,7 +960,7 @@ function FsDocumentRow({
                 draggable={!renaming}
                 onDragStart={(e) => {
                     dragState.current = doc.id;
-                    e.dataTransfer.effectAllowed = "move";
+                    e.dataTransfer.effectAllowed = "copyMove";
                     try { e.dataTransfer.setData("text/plain", doc.id); } catch { /* ignore */ }
                 }}
                 onDragEnd={() => { dragState.current = null; }}
@@ -806,14 +1000,29 @@ function FsDocumentRow({
                         onCancel={onRenameCancel}
                     />
                 ) : (
-                    <span className="flex-1 text-sm truncate text-gray-700 dark:text-gray-300">
+                    <span className="flex-1 text-sm truncate text-gray-700 dark:text-gray-300" title={metaTitle}>
                         {doc.name}
                     </span>
                 )}
 
-                {/* Hover delete */}
+                {/* Front matter tags */}
+                {!renaming && metaTags.length > 0 && (
+                    <span className="flex-shrink-0 flex gap-0.5 group-hover:hidden" title={metaTags.join(", ")}>
+                        {metaTags.slice(0, 2).map((tag) => (
+                            <span key={tag} className="px-1 rounded text-[10px] leading-4 bg-gray-200 dark:bg-gray-700 text-gray-500 dark:text-gray-400">
+                                {tag}
+                            </span>
+                        ))}
+                        {metaTags.length > 2 && (
+                            <span className="text-[10px] leading-4 text-gray-400 dark:text-gray-500">+{metaTags.length - 2}</span>
+                        )}
+                    </span>
+                )}
+
+                {/* Hover actions */}
                 {!renaming && (
                     <div className="hidden group-hover:flex items-center gap-0.5 flex-shrink-0" onClick={(e) => e.stopPropagation()}>
+                        <DuplicateAction onClick={() => duplicateEntry(doc.id)} />
                         <IconAction title="Delete" danger onClick={() => setConfirmDelete(true)}>
                             <polyline points="3 6 5 6 21 6" />
                             <path d="M19 6l-1 14a2 2 0 0 1-2 2H8a2 2 0 0 1-2-2L5 6" />
@@ -860,3 +1069,115 @@ function FsDocumentRow({
         </div>
     );
 }
+
+// ── Attachment row ────────────────────────────────────────────────────────────
+
+const IMAGE_EXTENSIONS = /\.(png|jpe?g|gif|webp|svg|bmp|ico|avif|tiff?)$/i;
+
+function FsAttachmentRow({
+    node,
+    depth,
+    mcpAnimatingIds,
+    renaming,
+    onRenameStart,
+    onRenameCommit,
+    onRenameCancel,
+}: {
+    node: TreeNode;
+    depth: number;
+    mcpAnimatingIds?: Set<string>;
+    renaming: boolean;
+    onRenameStart: () => void;
+    onRenameCommit: (newName: string) => void;
+    onRenameCancel: () => void;
+}) {
+    const { dragState, refreshPath } = useTreeContext();
+    const duplicateEntry = useDuplicateEntry();
+    const { showToast } = useToast();
+    const [confirmDelete, setConfirmDelete] = useState(false);
+    const indent = 28 + depth * 16;
+    const isImage = IMAGE_EXTENSIONS.test(node.name);
+
+    const handleDelete = async () => {
+        setConfirmDelete(false);
+        try {
+            await api.deleteEntry(node.id);
+            const parent = getParentPath(node.id);
+            if (parent) refreshPath(parent);
+        } catch (err) {
+            console.error("Delete failed:", err);
+            showToast(`Delete failed: ${api.errorMessage(err)}`, "error");
+        }
+    };
+
+    return (
+        <div>
+            <div
+                draggable={!renaming}
+                onDragStart={(e) => {
+                    dragState.current = node.id;
+                    e.dataTransfer.effectAllowed = "copyMove";
+                    try { e.dataTransfer.setData("text/plain", node.id); } catch { /* ignore */ }
+                }}
+                onDragEnd={() => { dragState.current = null; }}
+                className={`group flex items-center gap-1.5 select-none py-0.5 pr-1 hover:bg-gray-200 dark:hover:bg-gray-700 ${
+                    mcpAnimatingIds?.has(node.id) ? "mcp-animate-pulse" : ""
+                }`}
+                style={{ paddingLeft: indent + 20 }}
+                title={node.id}
+                onDoubleClick={(e) => { e.stopPropagation(); onRenameStart(); }}
+            >
+                {/* Attachment icon */}
+                <svg width="13" height="13" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" className="text-gray-400 flex-shrink-0">
+                    {isImage ? (
+                        <>
+                            <rect x="3" y="3" width="18" height="18" rx="2" ry="2" />
+                            <circle cx="8.5" cy="8.5" r="1.5" />
+                            <polyline points="21 15 16 10 5 21" />
+                        </>
+                    ) : (
+                        <path d="M21.44 11.05l-9.19 9.19a6 6 0 0 1-8.49-8.49l9.19-9.19a4 4 0 0 1 5.66 5.66l-9.2 9.19a2 2 0 0 1-2.83-2.83l8.49-8.48" />
+                    )}
+                </svg>
+
+                {/* Name / InlineRename */}
+                {renaming ? (
+                    <InlineRename
+                        initialValue={node.name}
+                        className="flex-1 text-sm px-1 py-0 rounded border border-blue-400 bg-white dark:bg-gray-900 text-gray-700 dark:text-gray-300 outline-none"
+                        onCommit={onRenameCommit}
+                        onCancel={onRenameCancel}
+                    />
+                ) : (
+                    <span className="flex-1 text-sm truncate text-gray-500 dark:text-gray-400">
+                        {node.name}
+                    </span>
+                )}
+
+                {/* Hover actions */}
+                {!renaming && (
+                    <div className="hidden group-hover:flex items-center gap-0.5 flex-shrink-0" onClick={(e) => e.stopPropagation()}>
+                        <DuplicateAction onClick={() => duplicateEntry(node.id)} />
+                        <IconAction title="Delete" danger onClick={() => setConfirmDelete(true)}>
+                            <polyline points="3 6 5 6 21 6" />
+                            <path d="M19 6l-1 14a2 2 0 0 1-2 2H8a2 2 0 0 1-2-2L5 6" />
+                            <path d="M10 11v6M14 11v6" />
+                            <path d="M9 6V4a1 1 0 0 1 1-1h4a1 1 0 0 1 1 1v2" />
+                        </IconAction>
+                    </div>
+                )}
+            </div>
+
+            {/* Delete confirm */}
+            {confirmDelete && (
+                <DeleteConfirm
+                    entryName={node.name}
+                    isFolder={false}
+                    indent={indent}
+                    onConfirm={handleDelete}
+                    onCancel={() => setConfirmDelete(false)}
+                />
+            )}
+        </div>
+    );
+}
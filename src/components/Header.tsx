@@ -1,7 +1,7 @@
 import ZoomControls from "./ZoomControls";
 import ThemeToggle from "./ThemeToggle";
 import { useSettings } from "../contexts/SettingsContext";
-import type { ExportFormat, TreeNode } from "../api";
+import type { ExportFormat, ExportOptions, TreeNode } from "../api";
 
 interface HeaderProps {
     theme: 'light' | 'dark';
@@ -19,7 +19,7 @@ interface HeaderProps {
     onZoomOut: () => void;
     onResetZoom: () => void;
     onExportMd: () => void;
-    onExportDocument: (format: ExportFormat) => void;
+    onExportDocument: (format: ExportFormat, options?: ExportOptions) => void;
     mcpRunning: boolean;
     mcpPending: boolean;
     mcpPort: number;
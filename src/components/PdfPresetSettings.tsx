@@ -0,0 +1,310 @@
+import { useEffect, useState } from 'react';
+import SegmentedToggle from './SegmentedToggle';
+import * as api from '../api';
+import type { PaperSize, PdfMargins, PdfOptions } from '../api';
+
+type PaperName = Exclude<PaperSize, { custom: unknown }> | 'custom';
+
+const PAPER_NAMES: { value: PaperName; label: string }[] = [
+    { value: 'a3', label: 'A3' },
+    { value: 'a4', label: 'A4' },
+    { value: 'a5', label: 'A5' },
+    { value: 'letter', label: 'Letter' },
+    { value: 'legal', label: 'Legal' },
+    { value: 'tabloid', label: 'Tabloid' },
+    { value: 'custom', label: 'Custom' },
+];
+
+/** Title and date on the left, "Page X of Y" on the right. */
+const STANDARD_FOOTER =
+    '<div style="font-size:9px; width:100%; margin:0 0.5in; display:flex; justify-content:space-between">' +
+    '<span><span class="title"></span> · <span class="date"></span></span>' +
+    '<span>Page <span class="pageNumber"></span> of <span class="totalPages"></span></span>' +
+    '</div>';
+
+interface Draft {
+    name: string;
+    paper: PaperName;
+    width: number;
+    height: number;
+    orientation: 'portrait' | 'landscape';
+    margins: PdfMargins;
+    scale: number;
+    header: string;
+    footer: string;
+    background: boolean;
+}
+
+const EMPTY_DRAFT: Draft = {
+    name: '',
+    paper: 'a4',
+    width: 8.27,
+    height: 11.69,
+    orientation: 'portrait',
+    margins: { top: 0.5, bottom: 0.5, left: 0.5, right: 0.5 },
+    scale: 1,
+    header: '',
+    footer: '',
+    background: true,
+};
+
+function toDraft(name: string, options: PdfOptions): Draft {
+    const paper = options.paper ?? 'a4';
+    return {
+        name,
+        paper: typeof paper === 'string' ? paper : 'custom',
+        width: typeof paper === 'string' ? EMPTY_DRAFT.width : paper.custom.width,
+        height: typeof paper === 'string' ? EMPTY_DRAFT.height : paper.custom.height,
+        orientation: options.orientation ?? 'portrait',
+        margins: options.margins ?? EMPTY_DRAFT.margins,
+        scale: options.scale ?? 1,
+        header: options.header_template ?? '',
+        footer: options.footer_template ?? '',
+        background: options.print_background ?? true,
+    };
+}
+
+function toOptions(draft: Draft): PdfOptions {
+    return {
+        paper: draft.paper === 'custom' ? { custom: { width: draft.width, height: draft.height } } : draft.paper,
+        margins: draft.margins,
+        orientation: draft.orientation,
+        scale: draft.scale,
+        header_template: draft.header.trim() || undefined,
+        footer_template: draft.footer.trim() || undefined,
+        print_background: draft.background,
+    };
+}
+
+function describe(options: PdfOptions): string {
+    const paper = options.paper ?? 'a4';
+    const size = typeof paper === 'string'
+        ? PAPER_NAMES.find((p) => p.value === paper)?.label ?? paper
+        : `${paper.custom.width} × ${paper.custom.height} in`;
+    const parts = [size, options.orientation ?? 'portrait'];
+    if (options.header_template || options.footer_template) parts.push('header/footer');
+    return parts.join(' · ');
+}
+
+const inputClass =
+    'px-2 py-1 text-sm border border-gray-300 dark:border-gray-600 rounded bg-white dark:bg-gray-700 text-gray-900 dark:text-gray-100 focus:outline-none focus:ring-1 focus:ring-blue-500';
+
+function NumberField({ label, value, step, onChange }: {
+    label: string;
+    value: number;
+    step: number;
+    onChange: (value: number) => void;
+}) {
+    return (
+        <label className="flex items-center gap-1.5 text-xs text-gray-600 dark:text-gray-400">
+            {label}
+            <input
+                type="number"
+                min="0"
+                step={step}
+                value={value}
+                onChange={(e) => onChange(parseFloat(e.target.value) || 0)}
+                className={`w-16 ${inputClass}`}
+            />
+        </label>
+    );
+}
+
+/** Settings section listing the saved PDF layouts, with an editor for one. */
+export default function PdfPresetSettings() {
+    const [presets, setPresets] = useState<Record<string, PdfOptions>>({});
+    const [draft, setDraft] = useState<Draft | null>(null);
+    const [error, setError] = useState<string | null>(null);
+
+    const reload = () =>
+        api.getPdfPresets()
+            .then(setPresets)
+            .catch((e) => setError(api.errorMessage(e)));
+
+    useEffect(() => {
+        reload();
+    }, []);
+
+    const update = (changes: Partial<Draft>) => setDraft((d) => (d ? { ...d, ...changes } : d));
+    const updateMargin = (side: keyof PdfMargins, value: number) =>
+        setDraft((d) => (d ? { ...d, margins: { ...d.margins, [side]: value } } : d));
+
+    const handleSave = async () => {
+        if (!draft) return;
+        try {
+            await api.savePdfPreset(draft.name, toOptions(draft));
+            setDraft(null);
+            setError(null);
+            await reload();
+        } catch (e) {
+            setError(api.errorMessage(e));
+        }
+    };
+
+    const handleDelete = async (name: string) => {
+        try {
+            await api.deletePdfPreset(name);
+            setError(null);
+            await reload();
+        } catch (e) {
+            setError(api.errorMessage(e));
+        }
+    };
+
+    const names = Object.keys(presets);
+
+    return (
+        <div className="rounded-lg border border-gray-200 dark:border-gray-700 bg-gray-50 dark:bg-gray-800 p-4">
+            <p className="text-xs text-gray-500 dark:text-gray-400 mb-3">
+                Saved layouts appear in the Export menu next to the default A4 PDF.
+            </p>
+            {names.length === 0 && !draft && (
+                <p className="text-xs text-gray-400 dark:text-gray-500 mb-2">
+                    No PDF presets yet.
+                </p>
+            )}
+            <div className="space-y-1.5 mb-2">
+                {names.map((name) => (
+                    <div key={name} className="flex items-center justify-between gap-2 text-xs">
+                        <div className="min-w-0">
+                            <span className="text-gray-700 dark:text-gray-300">{name}</span>
+                            <span className="ml-2 text-gray-400 dark:text-gray-500">{describe(presets[name])}</span>
+                        </div>
+                        <div className="flex items-center gap-2 flex-shrink-0">
+                            <button
+                                onClick={() => setDraft(toDraft(name, presets[name]))}
+                                className="text-blue-500 hover:text-blue-600 dark:hover:text-blue-400 transition-colors"
+                            >
+                                Edit
+                            </button>
+                            <button
+                                onClick={() => handleDelete(name)}
+                                className="text-red-400 hover:text-red-600 dark:hover:text-red-300 transition-colors"
+                            >
+                                Delete
+                            </button>
+                        </div>
+                    </div>
+                ))}
+            </div>
+
+            {draft ? (
+                <div className="space-y-3 border-t border-gray-200 dark:border-gray-700 pt-3">
+                    <label className="flex items-center gap-2 text-xs text-gray-600 dark:text-gray-400">
+                        Name
+                        <input
+                            type="text"
+                            value={draft.name}
+                            onChange={(e) => update({ name: e.target.value })}
+                            placeholder="e.g. US Letter"
+                            className={`flex-1 ${inputClass}`}
+                        />
+                    </label>
+                    <div className="flex items-center gap-3 flex-wrap">
+                        <select
+                            value={draft.paper}
+                            onChange={(e) => update({ paper: e.target.value as PaperName })}
+                            className={inputClass}
+                        >
+                            {PAPER_NAMES.map((p) => (
+                                <option key={p.value} value={p.value}>{p.label}</option>
+                            ))}
+                        </select>
+                        {draft.paper === 'custom' && (
+                            <>
+                                <NumberField label="W" value={draft.width} step={0.1} onChange={(width) => update({ width })} />
+                                <NumberField label="H" value={draft.height} step={0.1} onChange={(height) => update({ height })} />
+                                <span className="text-xs text-gray-400 dark:text-gray-500">in</span>
+                            </>
+                        )}
+                        <SegmentedToggle
+                            options={[
+                                { value: 'portrait', label: 'Portrait' },
+                                { value: 'landscape', label: 'Landscape' },
+                            ]}
+                            value={draft.orientation}
+                            onChange={(orientation) => update({ orientation })}
+                        />
+                    </div>
+                    <div className="flex items-center gap-3 flex-wrap">
+                        <span className="text-xs text-gray-600 dark:text-gray-400">Margins (in)</span>
+                        <NumberField label="Top" value={draft.margins.top} step={0.1} onChange={(v) => updateMargin('top', v)} />
+                        <NumberField label="Bottom" value={draft.margins.bottom} step={0.1} onChange={(v) => updateMargin('bottom', v)} />
+                        <NumberField label="Left" value={draft.margins.left} step={0.1} onChange={(v) => updateMargin('left', v)} />
+                        <NumberField label="Right" value={draft.margins.right} step={0.1} onChange={(v) => updateMargin('right', v)} />
+                    </div>
+                    <div className="flex items-center gap-3 flex-wrap">
+                        <NumberField label="Scale" value={draft.scale} step={0.05} onChange={(scale) => update({ scale })} />
+                        <label className="flex items-center gap-1.5 text-xs text-gray-600 dark:text-gray-400">
+                            <input
+                                type="checkbox"
+                                checked={draft.background}
+                                onChange={(e) => update({ background: e.target.checked })}
+                            />
+                            Print backgrounds
+                        </label>
+                    </div>
+                    <label className="block text-xs text-gray-600 dark:text-gray-400">
+                        Header HTML
+                        <textarea
+                            value={draft.header}
+                            onChange={(e) => update({ header: e.target.value })}
+                            rows={2}
+                            className={`mt-1 w-full font-mono text-xs ${inputClass}`}
+                        />
+                    </label>
+                    <label className="block text-xs text-gray-600 dark:text-gray-400">
+                        <span className="flex items-center justify-between">
+                            Footer HTML
+                            <button
+                                type="button"
+                                onClick={() => update({ footer: STANDARD_FOOTER })}
+                                className="text-blue-500 hover:text-blue-600 dark:hover:text-blue-400 transition-colors"
+                            >
+                                Title, date and page number
+                            </button>
+                        </span>
+                        <textarea
+                            value={draft.footer}
+                            onChange={(e) => update({ footer: e.target.value })}
+                            rows={2}
+                            className={`mt-1 w-full font-mono text-xs ${inputClass}`}
+                        />
+                    </label>
+                    <p className="text-xs text-gray-400 dark:text-gray-500">
+                        Elements with the classes title, date, url, pageNumber and totalPages are
+                        filled in. Templates need their own font size and a margin tall enough to hold them.
+                    </p>
+                    <div className="flex justify-end gap-2">
+                        <button
+                            onClick={() => {
+                                setDraft(null);
+                                setError(null);
+                            }}
+                            className="px-3 py-1.5 text-xs font-medium rounded text-gray-600 dark:text-gray-300 hover:bg-gray-200 dark:hover:bg-gray-700 transition-colors"
+                        >
+                            Cancel
+                        </button>
+                        <button
+                            onClick={handleSave}
+                            disabled={!draft.name.trim()}
+                            className="px-3 py-1.5 text-xs font-medium bg-blue-500 text-white rounded hover:bg-blue-600 transition-colors disabled:opacity-50 disabled:cursor-not-allowed"
+                        >
+                            Save Preset
+                        </button>
+                    </div>
+                </div>
+            ) : (
+                <button
+                    onClick={() => setDraft({ ...EMPTY_DRAFT })}
+                    className="w-full px-3 py-1.5 text-xs font-medium bg-blue-500 text-white rounded hover:bg-blue-600 transition-colors"
+                >
+                    + New Preset
+                </button>
+            )}
+            {error && (
+                <p className="mt-3 text-xs text-red-600 dark:text-red-400">{error}</p>
+            )}
+        </div>
+    );
+}
@@ -0,0 +1,141 @@
+import { useEffect, useRef, useState } from "react";
+import { useFocusTrap } from "../hooks/useFocusTrap";
+import * as api from "../api";
+import type { QuickOpenResult } from "../api";
+
+const QUICK_OPEN_LIMIT = 50;
+
+interface QuickOpenProps {
+    isOpen: boolean;
+    onClose: () => void;
+    onSelect: (id: string) => void;
+}
+
+/** `display_path` with the matched characters in bold. Positions count characters, not UTF-16 units. */
+function MatchedPath({ path, positions }: { path: string; positions: number[] }) {
+    const matched = new Set(positions);
+    return (
+        <>
+            {Array.from(path).map((ch, i) =>
+                matched.has(i) ? (
+                    <span key={i} className="font-semibold text-blue-600 dark:text-blue-400">{ch}</span>
+                ) : (
+                    ch
+                )
+            )}
+        </>
+    );
+}
+
+export default function QuickOpen({ isOpen, onClose, onSelect }: QuickOpenProps) {
+    const modalRef = useRef<HTMLDivElement>(null);
+    const listRef = useRef<HTMLUListElement>(null);
+    const [query, setQuery] = useState("");
+    const [results, setResults] = useState<QuickOpenResult[]>([]);
+    const [selected, setSelected] = useState(0);
+    useFocusTrap(modalRef, isOpen);
+
+    useEffect(() => {
+        if (isOpen) setQuery("");
+    }, [isOpen]);
+
+    useEffect(() => {
+        if (!isOpen) return;
+        let cancelled = false;
+        const timer = setTimeout(async () => {
+            try {
+                const r = await api.quickOpen(query, QUICK_OPEN_LIMIT);
+                if (!cancelled) {
+                    setResults(r);
+                    setSelected(0);
+                }
+            } catch (err) {
+                console.error("Quick open failed:", err);
+            }
+        }, 50);
+        return () => {
+            cancelled = true;
+            clearTimeout(timer);
+        };
+    }, [query, isOpen]);
+
+    useEffect(() => {
+        listRef.current?.children[selected]?.scrollIntoView({ block: "nearest" });
+    }, [selected]);
+
+    if (!isOpen) return null;
+
+    const choose = (result: QuickOpenResult | undefined) => {
+        if (!result) return;
+        onSelect(result.id);
+        onClose();
+    };
+
+    const handleKeyDown = (e: React.KeyboardEvent) => {
+        if (e.key === "ArrowDown") {
+            e.preventDefault();
+            setSelected((s) => Math.min(s + 1, results.length - 1));
+        } else if (e.key === "ArrowUp") {
+            e.preventDefault();
+            setSelected((s) => Math.max(s - 1, 0));
+        } else if (e.key === "Enter") {
+            e.preventDefault();
+            choose(results[selected]);
+        } else if (e.key === "Escape") {
+            e.preventDefault();
+            onClose();
+        }
+    };
+
+    return (
+        <div
+            className="fixed inset-0 bg-black bg-opacity-50 flex items-start justify-center pt-24 z-50"
+            onClick={onClose}
+        >
+            <div
+                ref={modalRef}
+                role="dialog"
+                aria-modal="true"
+                aria-label="Quick open"
+                tabIndex={-1}
+                className="bg-white dark:bg-gray-900 rounded-lg shadow-lg max-w-xl w-full mx-4 outline-none overflow-hidden"
+                onClick={(e) => e.stopPropagation()}
+                onKeyDown={handleKeyDown}
+            >
+                <input
+                    type="text"
+                    value={query}
+                    onChange={(e) => setQuery(e.target.value)}
+                    placeholder="Go to file..."
+                    aria-label="File name"
+                    className="w-full px-4 py-3 text-sm bg-transparent border-b border-gray-200 dark:border-gray-700 text-gray-900 dark:text-gray-100 outline-none"
+                />
+                <ul ref={listRef} role="listbox" className="max-h-80 overflow-y-auto py-1">
+                    {results.length === 0 ? (
+                        <li className="px-4 py-2 text-sm text-gray-500 dark:text-gray-400 italic">
+                            {query.trim() ? "No matching files" : "No recently opened files"}
+                        </li>
+                    ) : (
+                        results.map((r, i) => (
+                            <li
+                                key={r.id}
+                                role="option"
+                                aria-selected={i === selected}
+                                className={`px-4 py-1.5 cursor-pointer text-sm truncate ${
+                                    i === selected
+                                        ? "bg-blue-100 dark:bg-blue-900/40"
+                                        : "hover:bg-gray-100 dark:hover:bg-gray-800"
+                                } text-gray-700 dark:text-gray-300`}
+                                onMouseEnter={() => setSelected(i)}
+                                onClick={() => choose(r)}
+                                title={r.id}
+                            >
+                                <MatchedPath path={r.display_path} positions={r.positions} />
+                            </li>
+                        ))
+                    )}
+                </ul>
+            </div>
+        </div>
+    );
+}
@@ -1,7 +1,9 @@
 import { useEffect, useState, useRef } from 'react';
 import SegmentedToggle from './SegmentedToggle';
 import SettingsRow from './SettingsRow';
+import PdfPresetSettings from './PdfPresetSettings';
 import { useFocusTrap } from '../hooks/useFocusTrap';
+import { useSettings } from '../contexts/SettingsContext';
 import type { TreeNode } from '../api';
 
 interface SettingsModalProps {
@@ -19,7 +21,7 @@ interface SettingsModalProps {
     onRemoveWorkspaceRoot: (id: string) => Promise<void>;
 }
 
-type CategoryId = 'general' | 'mcp-server' | 'storage';
+type CategoryId = 'general' | 'mcp-server' | 'storage' | 'export';
 
 interface Category {
     id: CategoryId;
@@ -60,6 +62,18 @@ const CATEGORIES: Category[] = [
             </svg>
         ),
     },
+    {
+        id: 'export',
+        label: 'Export',
+        icon: (
+            <svg width="16" height="16" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round" strokeLinejoin="round">
+                <path d="M14 2H6a2 2 0 00-2 2v16a2 2 0 002 2h12a2 2 0 002-2V8z" />
+                <polyline points="14 2 14 8 20 8" />
+                <line x1="12" y1="18" x2="12" y2="12" />
+                <polyline points="9 15 12 12 15 15" />
+            </svg>
+        ),
+    },
 ];
 
 const sunIcon = (
@@ -80,6 +94,13 @@ const moonIcon = (
     </svg>
 );
 
+const linkIcon = (
+    <svg width="16" height="16" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round" strokeLinejoin="round">
+        <path d="M10 13a5 5 0 007.54.54l3-3a5 5 0 00-7.07-7.07l-1.72 1.71" />
+        <path d="M14 11a5 5 0 00-7.54-.54l-3 3a5 5 0 007.07 7.07l1.71-1.71" />
+    </svg>
+);
+
 const themeIcon = (
     <svg width="16" height="16" viewBox="0 0 24 24" fill="none" stroke="currentColor" strokeWidth="2" strokeLinecap="round" strokeLinejoin="round">
         <circle cx="12" cy="12" r="5" />
@@ -110,6 +131,7 @@ export default function SettingsModal({
 }: SettingsModalProps) {
     const [activeCategory, setActiveCategory] = useState<CategoryId>('general');
     const [localMcpPort, setLocalMcpPort] = useState(mcpPort);
+    const { updateLinks, setUpdateLinks } = useSettings();
     const modalRef = useRef<HTMLDivElement>(null);
     useFocusTrap(modalRef, isOpen);
 
@@ -206,6 +228,20 @@ export default function SettingsModal({
                                             onChange={onThemeChange}
                                         />
                                     </SettingsRow>
+
+                                    <h3 className="text-xs font-semibold text-gray-500 dark:text-gray-400 uppercase tracking-wide">
+                                        Files
+                                    </h3>
+                                    <SettingsRow label="Update links on rename/move" icon={linkIcon}>
+                                        <SegmentedToggle
+                                            options={[
+                                                { value: 'on', label: 'On' },
+                                                { value: 'off', label: 'Off' },
+                                            ]}
+                                            value={updateLinks ? 'on' : 'off'}
+                                            onChange={(v) => setUpdateLinks(v === 'on')}
+                                        />
+                                    </SettingsRow>
                                 </div>
                             </div>
                         )}
@@ -348,6 +384,20 @@ export default function SettingsModal({
                                 </div>
                             </div>
                         )}
+
+                        {activeCategory === 'export' && (
+                            <div>
+                                <h2 className="text-xl font-semibold text-gray-900 dark:text-gray-100 mb-4">
+                                    Export
+                                </h2>
+                                <div className="space-y-4">
+                                    <h3 className="text-xs font-semibold text-gray-500 dark:text-gray-400 uppercase tracking-wide">
+                                        PDF Presets
+                                    </h3>
+                                    <PdfPresetSettings />
+                                </div>
+                            </div>
+                        )}
                     </div>
                 </div>
             </div>
@@ -1,5 +1,6 @@
 import { useState, useRef, useEffect } from "react";
-import type { ExportFormat } from "../api";
+import * as api from "../api";
+import type { ExportFormat, ExportOptions, PdfOptions } from "../api";
 
 interface ZoomControlsProps {
     zoomLevel: number;
@@ -7,7 +8,7 @@ interface ZoomControlsProps {
     onZoomOut: () => void;
     onResetZoom: () => void;
     onExportMd?: () => void;
-    onExportDocument?: (format: ExportFormat) => void;
+    onExportDocument?: (format: ExportFormat, options?: ExportOptions) => void;
     minZoom?: number;
     maxZoom?: number;
 }
@@ -26,6 +27,7 @@ export default function ZoomControls({
     const canZoomIn = zoomLevel < maxZoom;
     const canZoomOut = zoomLevel > minZoom;
     const [showExportMenu, setShowExportMenu] = useState(false);
+    const [pdfPresets, setPdfPresets] = useState<Record<string, PdfOptions>>({});
     const menuRef = useRef<HTMLDivElement>(null);
 
     // Close menu when clicking outside
@@ -44,9 +46,17 @@ export default function ZoomControls({
         };
     }, [showExportMenu]);
 
-    const handleExportFormat = (format: ExportFormat) => {
+    // Presets can change in Settings while the app runs; reload them on open
+    useEffect(() => {
+        if (!showExportMenu || !onExportDocument) return;
+        api.getPdfPresets()
+            .then(setPdfPresets)
+            .catch((error) => console.error("Failed to load PDF presets:", error));
+    }, [showExportMenu, onExportDocument]);
+
+    const handleExportFormat = (format: ExportFormat, options?: ExportOptions) => {
         setShowExportMenu(false);
-        onExportDocument?.(format);
+        onExportDocument?.(format, options);
     };
 
     return (
@@ -110,7 +120,7 @@ export default function ZoomControls({
                     </button>
 
                     {showExportMenu && (
-                        <div className="absolute right-0 mt-2 w-40 bg-white dark:bg-gray-800 rounded-lg shadow-lg border border-gray-200 dark:border-gray-700 py-1 z-50">
+                        <div className="absolute right-0 mt-2 w-52 bg-white dark:bg-gray-800 rounded-lg shadow-lg border border-gray-200 dark:border-gray-700 py-1 z-50">
                             <div className="px-3 py-1 text-xs font-semibold text-gray-500 dark:text-gray-400 uppercase">
                                 Export As
                             </div>
@@ -133,6 +143,15 @@ export default function ZoomControls({
                                     🌐 HTML (.html)
                                 </button>
                             )}
+                            {onExportDocument && (
+                                <button
+                                    className="w-full px-3 py-2 text-left text-sm text-gray-700 dark:text-gray-200 hover:bg-gray-100 dark:hover:bg-gray-700 flex items-center gap-2"
+                                    onClick={() => handleExportFormat("html", { self_contained: true, static_diagrams: true })}
+                                    title="Diagrams are pre-rendered (or the Mermaid runtime included), so the file renders without network access"
+                                >
+                                    📦 HTML, offline (.html)
+                                </button>
+                            )}
                             {onExportDocument && (
                                 <button
                                     className="w-full px-3 py-2 text-left text-sm text-gray-700 dark:text-gray-200 hover:bg-gray-100 dark:hover:bg-gray-700 flex items-center gap-2"
@@ -141,6 +160,17 @@ export default function ZoomControls({
                                     📄 PDF (.pdf)
                                 </button>
                             )}
+                            {onExportDocument && Object.entries(pdfPresets).map(([name, pdf]) => (
+                                <button
+                                    key={name}
+                                    className="w-full px-3 py-2 text-left text-sm text-gray-700 dark:text-gray-200 hover:bg-gray-100 dark:hover:bg-gray-700 flex items-center gap-2"
+                                    onClick={() => handleExportFormat("pdf", { pdf })}
+                                    title={`PDF with the "${name}" layout`}
+                                >
+                                    <span>📄</span>
+                                    <span className="truncate">PDF, {name}</span>
+                                </button>
+                            ))}
                         </div>
                     )}
                 </div>
@@ -4,8 +4,13 @@ interface SettingsContextValue {
     settingsOpen: boolean;
     openSettings: () => void;
     closeSettings: () => void;
+    /** Rewrite links in other documents when an entry is renamed or moved. */
+    updateLinks: boolean;
+    setUpdateLinks: (enabled: boolean) => void;
 }
 
+const UPDATE_LINKS_KEY = 'markdown-ui-update-links';
+
 const SettingsContext = createContext<SettingsContextValue | undefined>(undefined);
 
 export function SettingsProvider({ children }: { children: ReactNode }) {
@@ -14,8 +19,18 @@ export function SettingsProvider({ children }: { children: ReactNode }) {
     const openSettings = useCallback(() => setSettingsOpen(true), []);
     const closeSettings = useCallback(() => setSettingsOpen(false), []);
 
+    const [updateLinks, setUpdateLinksState] = useState(
+        () => localStorage.getItem(UPDATE_LINKS_KEY) !== 'false'
+    );
+    const setUpdateLinks = useCallback((enabled: boolean) => {
+        setUpdateLinksState(enabled);
+        localStorage.setItem(UPDATE_LINKS_KEY, enabled.toString());
+    }, []);
+
     return (
-        <SettingsContext.Provider value={{ settingsOpen, openSettings, closeSettings }}>
+        <SettingsContext.Provider
+            value={{ settingsOpen, openSettings, closeSettings, updateLinks, setUpdateLinks }}
+        >
             {children}
         </SettingsContext.Provider>
     );
@@ -0,0 +1,29 @@
+import { useEffect, useRef } from "react";
+import { listen, type UnlistenFn } from "@tauri-apps/api/event";
+import type { FsChange } from "../api";
+
+/**
+ * Subscribe to the backend workspace watcher. `onChanges` receives each
+ * debounced batch of created/modified/removed/renamed entries.
+ */
+export function useFsChanges(onChanges: (changes: FsChange[]) => void) {
+    const handlerRef = useRef(onChanges);
+    handlerRef.current = onChanges;
+
+    useEffect(() => {
+        let unlisten: UnlistenFn | undefined;
+        let cancelled = false;
+
+        listen<FsChange[]>("fs-changes", (event) => {
+            handlerRef.current(event.payload);
+        }).then((fn) => {
+            if (cancelled) fn();
+            else unlisten = fn;
+        });
+
+        return () => {
+            cancelled = true;
+            unlisten?.();
+        };
+    }, []);
+}